@@ -12,8 +12,9 @@ use tokio::process::{Child, ChildStdin, Command};
 use tokio::sync::{oneshot, Mutex};
 use tokio::time::timeout;
 
-use crate::backend::events::{AppServerEvent, EventSink};
-use crate::types::WorkspaceEntry;
+use crate::backend::approvals::{decide, ApprovalItems};
+use crate::backend::events::{AppServerEvent, ApprovalAutoDecision, EventSink};
+use crate::types::{ApprovalRules, WorkspaceEntry};
 
 pub(crate) struct WorkspaceSession {
     pub(crate) entry: WorkspaceEntry,
@@ -21,6 +22,15 @@ pub(crate) struct WorkspaceSession {
     pub(crate) stdin: Mutex<ChildStdin>,
     pub(crate) pending: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
     pub(crate) next_id: AtomicU64,
+    /// Kept in step with the workspace settings while the session runs.
+    pub(crate) approval_rules: Mutex<ApprovalRules>,
+    replies: Mutex<HashMap<String, PendingReply>>,
+}
+
+/// The agent's latest message on a thread whose next turn someone awaits.
+struct PendingReply {
+    text: Option<String>,
+    tx: oneshot::Sender<Result<String, String>>,
 }
 
 impl WorkspaceSession {
@@ -60,6 +70,139 @@ impl WorkspaceSession {
         self.write_message(json!({ "id": id, "result": result }))
             .await
     }
+
+    /// Resolves with the agent's last message once the next turn on
+    /// `thread_id` completes. Register before starting the turn.
+    pub(crate) async fn expect_reply(
+        &self,
+        thread_id: &str,
+    ) -> oneshot::Receiver<Result<String, String>> {
+        let (tx, rx) = oneshot::channel();
+        self.replies
+            .lock()
+            .await
+            .insert(thread_id.to_string(), PendingReply { text: None, tx });
+        rx
+    }
+
+    async fn observe_reply(&self, message: &Value) {
+        let method = message.get("method").and_then(Value::as_str);
+        let Some(params) = message.get("params") else {
+            return;
+        };
+        match method {
+            Some("item/completed") => {
+                let item = params.get("item");
+                if item
+                    .and_then(|item| item.get("type"))
+                    .and_then(Value::as_str)
+                    != Some("agentMessage")
+                {
+                    return;
+                }
+                let thread_id = params.get("threadId").and_then(Value::as_str);
+                let mut replies = self.replies.lock().await;
+                if let Some(reply) = thread_id.and_then(|id| replies.get_mut(id)) {
+                    reply.text = item
+                        .and_then(|item| item.get("text"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string);
+                }
+            }
+            Some("turn/completed") => {
+                let turn = params.get("turn");
+                let thread_id = params
+                    .get("threadId")
+                    .or_else(|| turn.and_then(|turn| turn.get("threadId")))
+                    .and_then(Value::as_str);
+                let Some(reply) = ({
+                    let mut replies = self.replies.lock().await;
+                    thread_id.and_then(|id| replies.remove(id))
+                }) else {
+                    return;
+                };
+                let status = turn
+                    .and_then(|turn| turn.get("status"))
+                    .and_then(Value::as_str);
+                let result = match status {
+                    Some("failed") => Err(turn
+                        .and_then(|turn| turn.get("error"))
+                        .and_then(|error| error.get("message"))
+                        .and_then(Value::as_str)
+                        .unwrap_or("The turn failed.")
+                        .to_string()),
+                    Some("interrupted") => Err("The turn was interrupted.".to_string()),
+                    _ => reply
+                        .text
+                        .ok_or_else(|| "The agent did not reply.".to_string()),
+                };
+                let _ = reply.tx.send(result);
+            }
+            _ => {}
+        }
+    }
+
+    /// Answers an approval request when a workspace rule settles it. Returns
+    /// whether it did; otherwise the request should go to the user.
+    async fn auto_decide<E: EventSink>(
+        &self,
+        id: u64,
+        message: &Value,
+        items: &ApprovalItems,
+        event_sink: &E,
+    ) -> bool {
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let matched = {
+            let rules = self.approval_rules.lock().await;
+            decide(method, &params, items, &self.entry.path, &rules)
+        };
+        let Some(matched) = matched else {
+            return false;
+        };
+        let decision = matched.decision.as_str();
+        if let Err(err) = self
+            .send_response(id, json!({ "decision": decision }))
+            .await
+        {
+            eprintln!("Failed to answer approval request {id}: {err}");
+            return false;
+        }
+        event_sink.emit_approval_auto_decision(ApprovalAutoDecision {
+            workspace_id: self.entry.id.clone(),
+            request_id: id,
+            method: method.to_string(),
+            decision,
+            rule: matched.rule,
+            subject: matched.subject,
+        });
+        true
+    }
+}
+
+pub(crate) fn approval_rules_by_workspace(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+) -> HashMap<String, ApprovalRules> {
+    workspaces
+        .iter()
+        .map(|(id, entry)| (id.clone(), entry.settings.approval_rules.clone()))
+        .collect()
+}
+
+/// Hands connected sessions the approval rules of their workspaces.
+pub(crate) async fn update_approval_rules(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    rules: HashMap<String, ApprovalRules>,
+) {
+    let sessions: Vec<_> = sessions.lock().await.values().cloned().collect();
+    for session in sessions {
+        if let Some(rules) = rules.get(&session.entry.id) {
+            *session.approval_rules.lock().await = rules.clone();
+        }
+    }
 }
 
 pub(crate) fn build_codex_path_env(codex_bin: Option<&str>) -> Option<String> {
@@ -208,6 +351,8 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         stdin: Mutex::new(stdin),
         pending: Mutex::new(HashMap::new()),
         next_id: AtomicU64::new(1),
+        approval_rules: Mutex::new(entry.settings.approval_rules.clone()),
+        replies: Mutex::new(HashMap::new()),
     });
 
     let session_clone = Arc::clone(&session);
@@ -215,6 +360,7 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     let event_sink_clone = event_sink.clone();
     tokio::spawn(async move {
         let mut lines = BufReader::new(stdout).lines();
+        let mut approval_items = ApprovalItems::default();
         while let Ok(Some(line)) = lines.next_line().await {
             if line.trim().is_empty() {
                 continue;
@@ -243,6 +389,12 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                         let _ = tx.send(value);
                     }
                 } else if has_method {
+                    if session_clone
+                        .auto_decide(id, &value, &approval_items, &event_sink_clone)
+                        .await
+                    {
+                        continue;
+                    }
                     let payload = AppServerEvent {
                         workspace_id: workspace_id.clone(),
                         message: value,
@@ -252,6 +404,8 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                     let _ = tx.send(value);
                 }
             } else if has_method {
+                approval_items.observe(&value);
+                session_clone.observe_reply(&value).await;
                 let payload = AppServerEvent {
                     workspace_id: workspace_id.clone(),
                     message: value,
@@ -0,0 +1,364 @@
+use std::fmt;
+use std::path::Path;
+use std::process::Stdio;
+
+use git2::Repository;
+use serde::Serialize;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+
+use crate::backend::events::{CloneProgress, EventSink};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CloneErrorKind {
+    /// Credentials are missing or were rejected.
+    Auth,
+    /// The repository or branch does not exist (hosts also answer this for
+    /// private repositories without credentials).
+    NotFound,
+    Network,
+    DestinationExists,
+    Other,
+}
+
+/// A failed clone, serialized as `{ kind, message }` so the UI can offer to
+/// sign in instead of showing git's output.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CloneError {
+    pub(crate) kind: CloneErrorKind,
+    pub(crate) message: String,
+}
+
+impl CloneError {
+    fn new(kind: CloneErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<String> for CloneError {
+    fn from(message: String) -> Self {
+        Self::new(CloneErrorKind::Other, message)
+    }
+}
+
+impl fmt::Display for CloneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+fn classify_clone_error(output: &str) -> CloneErrorKind {
+    let output = output.to_ascii_lowercase();
+    let matches_any = |needles: &[&str]| needles.iter().any(|needle| output.contains(needle));
+    if matches_any(&[
+        "authentication failed",
+        "could not read username",
+        "could not read password",
+        "terminal prompts disabled",
+        "permission denied (publickey",
+        "invalid username or password",
+        "host key verification failed",
+        "error: 403",
+        "returned error: 401",
+        "returned error: 403",
+    ]) {
+        CloneErrorKind::Auth
+    } else if matches_any(&[
+        "repository not found",
+        "does not appear to be a git repository",
+        "not found in upstream",
+        "returned error: 404",
+    ]) {
+        CloneErrorKind::NotFound
+    } else if matches_any(&[
+        "could not resolve host",
+        "connection refused",
+        "connection timed out",
+        "operation timed out",
+        "network is unreachable",
+        "unable to access",
+    ]) {
+        CloneErrorKind::Network
+    } else if output.contains("already exists and is not an empty directory") {
+        CloneErrorKind::DestinationExists
+    } else {
+        CloneErrorKind::Other
+    }
+}
+
+/// Parses git's progress lines, e.g.
+/// `Receiving objects:  45% (450/1000), 1.20 MiB | 2.00 MiB/s`.
+fn parse_progress_line(destination: &str, line: &str) -> Option<CloneProgress> {
+    let text = line.strip_prefix("remote: ").unwrap_or(line);
+    let (phase, rest) = text.split_once(':')?;
+    let percent_end = rest.find('%')?;
+    let percent = rest[..percent_end].trim().parse::<u8>().ok();
+    let counts = rest[percent_end..]
+        .split_once('(')
+        .and_then(|(_, counts)| counts.split_once(')'))
+        .and_then(|(counts, _)| counts.split_once('/'));
+    Some(CloneProgress {
+        destination: destination.to_string(),
+        phase: phase.trim().to_string(),
+        percent,
+        current: counts.and_then(|(current, _)| current.trim().parse().ok()),
+        total: counts.and_then(|(_, total)| total.trim().parse().ok()),
+        line: line.to_string(),
+    })
+}
+
+/// Clears what a failed or canceled clone left behind, so retrying doesn't
+/// run into `DestinationExists`: the destination itself if the clone
+/// created it, otherwise only its contents.
+struct DestinationCleanup<'a> {
+    path: &'a Path,
+    created: bool,
+    armed: bool,
+}
+
+impl Drop for DestinationCleanup<'_> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        if self.created {
+            let _ = std::fs::remove_dir_all(self.path);
+            return;
+        }
+        for entry in self.path.read_dir().into_iter().flatten().flatten() {
+            let path = entry.path();
+            let _ = if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+        }
+    }
+}
+
+/// Clones `url` into `destination` with the git CLI, so credential helpers
+/// and SSH config apply, emitting `clone-progress` events as git reports
+/// them. Prompts are disabled; missing credentials fail with an auth error.
+pub(crate) async fn clone_repository(
+    url: &str,
+    destination: &Path,
+    branch: Option<&str>,
+    depth: Option<u32>,
+    event_sink: &impl EventSink,
+) -> Result<(), CloneError> {
+    if destination
+        .read_dir()
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false)
+    {
+        return Err(CloneError::new(
+            CloneErrorKind::DestinationExists,
+            format!("{} already exists and is not empty.", destination.display()),
+        ));
+    }
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let mut cleanup = DestinationCleanup {
+        path: destination,
+        created: !destination.exists(),
+        armed: true,
+    };
+
+    let mut command = Command::new("git");
+    command.args(["clone", "--progress"]);
+    if let Some(branch) = branch {
+        command.args(["--branch", branch]);
+    }
+    if let Some(depth) = depth {
+        command.arg(format!("--depth={depth}"));
+    }
+    command.arg("--").arg(url).arg(destination);
+    command.env("GIT_TERMINAL_PROMPT", "0");
+    if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+        command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+
+    let destination_string = destination.to_string_lossy().to_string();
+    let mut messages = Vec::new();
+    let mut last_progress: Option<(String, Option<u8>)> = None;
+    let mut handle_line = |line: String| {
+        let Some(progress) = parse_progress_line(&destination_string, &line) else {
+            messages.push(line);
+            return;
+        };
+        let key = (progress.phase.clone(), progress.percent);
+        if last_progress.as_ref() == Some(&key) {
+            return;
+        }
+        last_progress = Some(key);
+        event_sink.emit_clone_progress(progress);
+    };
+
+    if let Some(mut stderr) = child.stderr.take() {
+        // Progress lines end in `\r` while they update in place.
+        let mut line = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let read = stderr
+                .read(&mut chunk)
+                .await
+                .map_err(|e| format!("Failed to read git output: {e}"))?;
+            if read == 0 {
+                break;
+            }
+            for &byte in &chunk[..read] {
+                if byte == b'\r' || byte == b'\n' {
+                    let text = String::from_utf8_lossy(&line).trim().to_string();
+                    line.clear();
+                    if !text.is_empty() {
+                        handle_line(text);
+                    }
+                } else {
+                    line.push(byte);
+                }
+            }
+        }
+        let text = String::from_utf8_lossy(&line).trim().to_string();
+        if !text.is_empty() {
+            handle_line(text);
+        }
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if status.success() {
+        cleanup.armed = false;
+        return Ok(());
+    }
+    let detail: Vec<&str> = messages
+        .iter()
+        .map(String::as_str)
+        .filter(|line| !line.starts_with("Cloning into"))
+        .collect();
+    let message = if detail.is_empty() {
+        "git clone failed.".to_string()
+    } else {
+        detail.join("\n")
+    };
+    Err(CloneError::new(classify_clone_error(&message), message))
+}
+
+/// The git root of a fresh clone when it is not the clone destination
+/// itself, for `WorkspaceSettings::git_root`.
+pub(crate) fn clone_git_root(destination: &Path) -> Option<String> {
+    let workdir = Repository::discover(destination)
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf))?;
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    (canonical(&workdir) != canonical(destination)).then(|| workdir.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::{classify_clone_error, parse_progress_line, CloneErrorKind, DestinationCleanup};
+
+    #[test]
+    fn parse_progress_line_reads_phase_and_counts() {
+        let progress = parse_progress_line(
+            "/tmp/repo",
+            "Receiving objects:  45% (450/1000), 1.20 MiB | 2.00 MiB/s",
+        )
+        .expect("progress");
+        assert_eq!(progress.phase, "Receiving objects");
+        assert_eq!(progress.percent, Some(45));
+        assert_eq!(progress.current, Some(450));
+        assert_eq!(progress.total, Some(1000));
+
+        let progress =
+            parse_progress_line("/tmp/repo", "remote: Counting objects: 100% (12/12), done.")
+                .expect("progress");
+        assert_eq!(progress.phase, "Counting objects");
+        assert_eq!(progress.total, Some(12));
+
+        assert!(parse_progress_line("/tmp/repo", "Cloning into 'repo'...").is_none());
+        assert!(
+            parse_progress_line("/tmp/repo", "remote: Enumerating objects: 12, done.").is_none()
+        );
+    }
+
+    #[test]
+    fn classify_clone_error_detects_auth_failures() {
+        assert_eq!(
+            classify_clone_error(
+                "fatal: could not read Username for 'https://github.com': terminal prompts disabled"
+            ),
+            CloneErrorKind::Auth
+        );
+        assert_eq!(
+            classify_clone_error("git@github.com: Permission denied (publickey)."),
+            CloneErrorKind::Auth
+        );
+        assert_eq!(
+            classify_clone_error("remote: Repository not found.\nfatal: repository not found"),
+            CloneErrorKind::NotFound
+        );
+        assert_eq!(
+            classify_clone_error("fatal: unable to access 'https://x/': Could not resolve host: x"),
+            CloneErrorKind::Network
+        );
+        assert_eq!(
+            classify_clone_error("fatal: Remote branch nope not found in upstream origin"),
+            CloneErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn failed_clones_leave_the_destination_as_they_found_it() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let partial_clone = |path: &std::path::Path| {
+            std::fs::create_dir_all(path.join(".git/objects")).unwrap();
+            std::fs::write(path.join("README.md"), "partial").unwrap();
+        };
+
+        let created = root.join("created");
+        partial_clone(&created);
+        drop(DestinationCleanup {
+            path: &created,
+            created: true,
+            armed: true,
+        });
+        assert!(!created.exists());
+
+        let existing = root.join("existing");
+        partial_clone(&existing);
+        drop(DestinationCleanup {
+            path: &existing,
+            created: false,
+            armed: true,
+        });
+        assert_eq!(existing.read_dir().unwrap().count(), 0);
+
+        let cloned = root.join("cloned");
+        partial_clone(&cloned);
+        drop(DestinationCleanup {
+            path: &cloned,
+            created: true,
+            armed: false,
+        });
+        assert!(cloned.join("README.md").is_file());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
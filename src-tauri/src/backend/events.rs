@@ -1,6 +1,8 @@
 use serde::Serialize;
 use serde_json::Value;
 
+use crate::types::WorktreeSetupStatus;
+
 #[derive(Serialize, Clone)]
 pub(crate) struct AppServerEvent {
     pub(crate) workspace_id: String,
@@ -16,7 +18,65 @@ pub(crate) struct TerminalOutput {
     pub(crate) data: String,
 }
 
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct GitStatusChanged {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    pub(crate) paths: Vec<String>,
+    pub(crate) full: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct WorktreeSetupOutput {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    pub(crate) command: String,
+    /// `stdout` or `stderr`.
+    pub(crate) stream: &'static str,
+    pub(crate) line: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CloneProgress {
+    /// The clone's destination path, which identifies it.
+    pub(crate) destination: String,
+    /// Git's phase, e.g. `Receiving objects` or `Resolving deltas`.
+    pub(crate) phase: String,
+    pub(crate) percent: Option<u8>,
+    pub(crate) current: Option<u64>,
+    pub(crate) total: Option<u64>,
+    pub(crate) line: String,
+}
+
+/// A data file was reloaded after another process changed it.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct StorageChanged {
+    /// `workspaces` or `settings`.
+    pub(crate) file: &'static str,
+}
+
+/// An approval request was answered by a workspace rule instead of the user.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ApprovalAutoDecision {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    #[serde(rename = "requestId")]
+    pub(crate) request_id: u64,
+    pub(crate) method: String,
+    /// `accept` or `decline`.
+    pub(crate) decision: &'static str,
+    pub(crate) rule: String,
+    /// The command or path the rule matched.
+    pub(crate) subject: String,
+}
+
 pub(crate) trait EventSink: Clone + Send + Sync + 'static {
     fn emit_app_server_event(&self, event: AppServerEvent);
     fn emit_terminal_output(&self, event: TerminalOutput);
+    fn emit_git_status_changed(&self, event: GitStatusChanged);
+    fn emit_worktree_setup_output(&self, event: WorktreeSetupOutput);
+    fn emit_worktree_setup_status(&self, event: WorktreeSetupStatus);
+    fn emit_clone_progress(&self, event: CloneProgress);
+    fn emit_storage_changed(&self, event: StorageChanged);
+    fn emit_approval_auto_decision(&self, event: ApprovalAutoDecision);
 }
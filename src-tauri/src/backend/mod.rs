@@ -1,2 +1,6 @@
 pub(crate) mod app_server;
+pub(crate) mod approvals;
+pub(crate) mod clone;
 pub(crate) mod events;
+pub(crate) mod worktree_branches;
+pub(crate) mod worktree_setup;
@@ -0,0 +1,191 @@
+use git2::{BranchType, Repository};
+
+use crate::types::WorkspaceEntry;
+use crate::worktree_paths::{render_branch_template, utc_date_string};
+
+/// Highest `-N` suffix tried when a templated branch name is taken.
+const MAX_BRANCH_SUFFIX: usize = 1000;
+
+/// Resolves the branch a new worktree of `parent` checks out, and the ref it
+/// starts from when that branch has to be created.
+///
+/// An existing branch is used as is, or tracked from a remote when it only
+/// exists there. Otherwise the parent's (or the global) branch template is
+/// applied and the name made unique, and `base_ref` must name a commit.
+pub(crate) fn resolve_worktree_branch(
+    parent: &WorkspaceEntry,
+    global_template: Option<&str>,
+    branch: &str,
+    base_ref: Option<&str>,
+    existing_branch: bool,
+) -> Result<(String, Option<String>), String> {
+    let repo = Repository::discover(&parent.path).map_err(|e| e.to_string())?;
+    let (branch, start_point) = if existing_branch {
+        if base_ref.is_some() {
+            return Err(
+                "A base ref cannot be used when checking out an existing branch.".to_string(),
+            );
+        }
+        if local_branch_exists(&repo, branch) {
+            (branch.to_string(), None)
+        } else {
+            // A branch that only exists on a remote gets a local tracking branch.
+            let remote_ref = remote_branch_ref(&repo, branch)?
+                .ok_or_else(|| format!("Branch {branch} does not exist."))?;
+            (branch.to_string(), Some(remote_ref))
+        }
+    } else {
+        let template = parent
+            .settings
+            .worktree_branch_template
+            .as_deref()
+            .or(global_template)
+            .filter(|template| !template.trim().is_empty());
+        let branch = match template {
+            Some(template) => {
+                let rendered = render_branch_template(
+                    template.trim(),
+                    branch,
+                    &parent.name,
+                    &utc_date_string(),
+                )?;
+                unique_branch_name(&repo, &rendered)?
+            }
+            None => branch.to_string(),
+        };
+        if let Some(base_ref) = base_ref {
+            repo.revparse_single(&format!("{base_ref}^{{commit}}"))
+                .map_err(|_| format!("Unknown base ref: {base_ref}"))?;
+        }
+        (branch, base_ref.map(str::to_string))
+    };
+    if !git2::Branch::name_is_valid(&branch).unwrap_or(false) {
+        return Err(format!("Invalid branch name: {branch}"));
+    }
+    Ok((branch, start_point))
+}
+
+fn local_branch_exists(repo: &Repository, branch: &str) -> bool {
+    repo.find_branch(branch, BranchType::Local).is_ok()
+}
+
+/// Finds `refs/remotes/<remote>/<branch>` for a branch that only exists on
+/// a remote, preferring `origin`, and returns `<remote>/<branch>`.
+fn remote_branch_ref(repo: &Repository, branch: &str) -> Result<Option<String>, String> {
+    let remotes = repo.remotes().map_err(|e| e.to_string())?;
+    let mut remotes = remotes.iter().flatten().collect::<Vec<_>>();
+    remotes.sort_by_key(|remote| *remote != "origin");
+    Ok(remotes
+        .into_iter()
+        .map(|remote| format!("{remote}/{branch}"))
+        .find(|candidate| {
+            repo.find_reference(&format!("refs/remotes/{candidate}"))
+                .is_ok()
+        }))
+}
+
+/// Appends `-2`, `-3`, ... to `branch` until it names no existing branch.
+fn unique_branch_name(repo: &Repository, branch: &str) -> Result<String, String> {
+    if !local_branch_exists(repo, branch) {
+        return Ok(branch.to_string());
+    }
+    (2..MAX_BRANCH_SUFFIX)
+        .map(|index| format!("{branch}-{index}"))
+        .find(|candidate| !local_branch_exists(repo, candidate))
+        .ok_or_else(|| format!("Branch {branch} already exists."))
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::{Repository, Signature};
+    use uuid::Uuid;
+
+    use super::resolve_worktree_branch;
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+    fn parent(path: &std::path::Path, template: Option<&str>) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: "parent-id".to_string(),
+            name: "My App".to_string(),
+            path: path.to_string_lossy().to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                worktree_branch_template: template.map(str::to_string),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn init_repo() -> (std::path::PathBuf, Repository) {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        drop(tree);
+        (dir, repo)
+    }
+
+    #[test]
+    fn templated_branch_names_get_a_free_suffix() {
+        let (dir, repo) = init_repo();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("app/fix", &head, false).unwrap();
+        repo.branch("app/fix-2", &head, false).unwrap();
+
+        let resolved =
+            resolve_worktree_branch(&parent(&dir, Some("app/{slug}")), None, "Fix", None, false)
+                .unwrap();
+        assert_eq!(resolved, ("app/fix-3".to_string(), None));
+
+        let resolved = resolve_worktree_branch(
+            &parent(&dir, None),
+            Some("x/{slug}"),
+            "Fix",
+            Some("HEAD"),
+            false,
+        )
+        .unwrap();
+        assert_eq!(resolved, ("x/fix".to_string(), Some("HEAD".to_string())));
+        assert!(
+            resolve_worktree_branch(&parent(&dir, None), None, "new", Some("nope"), false)
+                .unwrap_err()
+                .contains("Unknown base ref")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn existing_branches_are_tracked_from_a_remote() {
+        let (dir, repo) = init_repo();
+        let head = repo.head().unwrap().peel_to_commit().unwrap().id();
+        repo.remote("upstream", "https://example.com/upstream.git")
+            .unwrap();
+        repo.remote("origin", "https://example.com/origin.git")
+            .unwrap();
+        for remote in ["upstream", "origin"] {
+            repo.reference(
+                &format!("refs/remotes/{remote}/feature"),
+                head,
+                false,
+                "test",
+            )
+            .unwrap();
+        }
+        let entry = parent(&dir, Some("ignored/{slug}"));
+
+        assert_eq!(
+            resolve_worktree_branch(&entry, None, "feature", None, true).unwrap(),
+            ("feature".to_string(), Some("origin/feature".to_string()))
+        );
+        assert!(resolve_worktree_branch(&entry, None, "missing", None, true).is_err());
+        assert!(resolve_worktree_branch(&entry, None, "feature", Some("HEAD"), true).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,659 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+
+use git2::Repository;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{timeout_at, Instant};
+
+use crate::backend::events::{EventSink, WorktreeSetupOutput};
+use crate::types::{WorktreeSetupConfig, WorktreeSetupState, WorktreeSetupStatus};
+
+/// How long sending a message waits for a worktree's setup before giving up.
+pub(crate) const SETUP_WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// A setup run for one worktree. Dropping it stops the run and kills the
+/// command in progress.
+pub(crate) struct WorktreeSetupRun {
+    status: watch::Receiver<WorktreeSetupStatus>,
+    task: JoinHandle<()>,
+}
+
+impl WorktreeSetupRun {
+    pub(crate) fn start(
+        workspace_id: String,
+        parent_path: PathBuf,
+        worktree_path: PathBuf,
+        config: WorktreeSetupConfig,
+        event_sink: impl EventSink,
+    ) -> Self {
+        let (tx, status) = watch::channel(WorktreeSetupStatus {
+            workspace_id,
+            state: WorktreeSetupState::Running,
+            copied_files: 0,
+            command: None,
+            error: None,
+        });
+        let task = tokio::spawn(run_setup(
+            parent_path,
+            worktree_path,
+            config,
+            event_sink,
+            tx,
+        ));
+        Self { status, task }
+    }
+
+    pub(crate) fn status(&self) -> WorktreeSetupStatus {
+        self.status.borrow().clone()
+    }
+
+    fn subscribe(&self) -> watch::Receiver<WorktreeSetupStatus> {
+        self.status.clone()
+    }
+}
+
+impl Drop for WorktreeSetupRun {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn publish(
+    tx: &watch::Sender<WorktreeSetupStatus>,
+    event_sink: &impl EventSink,
+    status: &WorktreeSetupStatus,
+) {
+    tx.send_replace(status.clone());
+    event_sink.emit_worktree_setup_status(status.clone());
+}
+
+async fn run_setup(
+    parent_path: PathBuf,
+    worktree_path: PathBuf,
+    config: WorktreeSetupConfig,
+    event_sink: impl EventSink,
+    tx: watch::Sender<WorktreeSetupStatus>,
+) {
+    let mut status = tx.borrow().clone();
+    publish(&tx, &event_sink, &status);
+
+    let copy = config.copy.clone();
+    let (source, target) = (parent_path.clone(), worktree_path.clone());
+    let copied = tokio::task::spawn_blocking(move || copy_setup_files(&source, &target, &copy))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result);
+    match copied {
+        Ok(count) => status.copied_files = count,
+        Err(error) => {
+            status.state = WorktreeSetupState::Failed;
+            status.error = Some(error);
+            publish(&tx, &event_sink, &status);
+            return;
+        }
+    }
+
+    for command in config.commands.iter().map(|command| command.trim()) {
+        if command.is_empty() {
+            continue;
+        }
+        status.command = Some(command.to_string());
+        publish(&tx, &event_sink, &status);
+        let result =
+            run_setup_command(&status.workspace_id, &worktree_path, command, &event_sink).await;
+        if let Err(error) = result {
+            status.state = WorktreeSetupState::Failed;
+            status.error = Some(error);
+            publish(&tx, &event_sink, &status);
+            return;
+        }
+    }
+    status.command = None;
+    status.state = WorktreeSetupState::Succeeded;
+    publish(&tx, &event_sink, &status);
+}
+
+/// Runs setup commands in a process group of their own, so stopping one
+/// also stops what its shell started.
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        #[cfg(unix)]
+        shell.process_group(0);
+        shell
+    }
+}
+
+/// Kills the process group led by a setup command's shell when dropped,
+/// i.e. when the run is canceled while the command is still going.
+struct ProcessGroup(Option<u32>);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            // SAFETY: killpg only sends a signal; the group id comes from
+            // a child spawned as its group leader.
+            unsafe {
+                libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+async fn forward_lines<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    stream: &'static str,
+    workspace_id: String,
+    command: String,
+    event_sink: impl EventSink,
+) {
+    let Some(reader) = reader else {
+        return;
+    };
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        event_sink.emit_worktree_setup_output(WorktreeSetupOutput {
+            workspace_id: workspace_id.clone(),
+            command: command.clone(),
+            stream,
+            line,
+        });
+    }
+}
+
+async fn run_setup_command(
+    workspace_id: &str,
+    worktree_path: &Path,
+    command: &str,
+    event_sink: &impl EventSink,
+) -> Result<(), String> {
+    let mut child = shell_command(command)
+        .current_dir(worktree_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run `{command}`: {e}"))?;
+    let mut group = ProcessGroup(child.id());
+    let stderr = tokio::spawn(forward_lines(
+        child.stderr.take(),
+        "stderr",
+        workspace_id.to_string(),
+        command.to_string(),
+        event_sink.clone(),
+    ));
+    forward_lines(
+        child.stdout.take(),
+        "stdout",
+        workspace_id.to_string(),
+        command.to_string(),
+        event_sink.clone(),
+    )
+    .await;
+    let _ = stderr.await;
+    let exit = child
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to run `{command}`: {e}"))?;
+    // Whatever the command left running in the background is its business.
+    group.0 = None;
+    if exit.success() {
+        Ok(())
+    } else {
+        Err(format!("`{command}` failed ({exit})."))
+    }
+}
+
+/// Copies one file unless the worktree already has it (e.g. because it is
+/// tracked). Returns the number of files copied.
+fn copy_file(source: &Path, target: &Path) -> Result<usize, String> {
+    if target.symlink_metadata().is_ok() {
+        return Ok(0);
+    }
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let metadata = source.symlink_metadata().map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    if metadata.file_type().is_symlink() {
+        let link = std::fs::read_link(source).map_err(|e| e.to_string())?;
+        std::os::unix::fs::symlink(link, target)
+            .map_err(|e| format!("Failed to link {}: {e}", target.display()))?;
+        return Ok(1);
+    }
+    if !metadata.is_file() {
+        return Ok(0);
+    }
+    std::fs::copy(source, target)
+        .map_err(|e| format!("Failed to copy {}: {e}", source.display()))?;
+    Ok(1)
+}
+
+fn copy_tree(source: &Path, target: &Path) -> Result<usize, String> {
+    let Ok(metadata) = source.symlink_metadata() else {
+        // Listed files are optional; not every checkout has a `.env`.
+        return Ok(0);
+    };
+    if !metadata.is_dir() {
+        return copy_file(source, target);
+    }
+    let mut copied = 0;
+    let walker = WalkBuilder::new(source)
+        .standard_filters(false)
+        .follow_links(false)
+        .build();
+    for entry in walker.filter_map(Result::ok) {
+        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+            continue;
+        }
+        if let Ok(relative) = entry.path().strip_prefix(source) {
+            copied += copy_file(entry.path(), &target.join(relative))?;
+        }
+    }
+    Ok(copied)
+}
+
+/// Copies the configured paths and globs from the parent checkout into a
+/// new worktree. Plain paths may name files or directories; globs follow
+/// gitignore syntax and are matched against files only.
+fn copy_setup_files(parent: &Path, worktree: &Path, patterns: &[String]) -> Result<usize, String> {
+    let mut globs = OverrideBuilder::new(parent);
+    let mut has_globs = false;
+    let mut copied = 0;
+    for pattern in patterns.iter().map(|pattern| pattern.trim()) {
+        if pattern.is_empty() {
+            continue;
+        }
+        let relative = Path::new(pattern);
+        let inside = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !inside {
+            return Err(format!(
+                "Setup files must be inside the workspace: {pattern}"
+            ));
+        }
+        if pattern.contains(['*', '?', '[']) {
+            globs.add(pattern).map_err(|e| e.to_string())?;
+            has_globs = true;
+        } else {
+            copied += copy_tree(&parent.join(relative), &worktree.join(relative))?;
+        }
+    }
+    if !has_globs {
+        return Ok(copied);
+    }
+    let globs = globs.build().map_err(|e| e.to_string())?;
+    // Ignored files such as `.env` are what globs are for, but ignored
+    // directories are mostly dependencies and build output like
+    // `node_modules` or `target`, so globs don't look inside them.
+    let repo = Repository::open(parent).ok().map(StdMutex::new);
+    let root = parent.to_path_buf();
+    let walker = WalkBuilder::new(parent)
+        .standard_filters(false)
+        .follow_links(false)
+        .filter_entry(move |entry| {
+            if entry.file_name() == ".git" {
+                return false;
+            }
+            let (Some(repo), Ok(relative)) = (&repo, entry.path().strip_prefix(&root)) else {
+                return true;
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                return true;
+            }
+            let relative = format!("{}/", relative.to_string_lossy());
+            !repo
+                .lock()
+                .is_ok_and(|repo| repo.is_path_ignored(&relative).unwrap_or(false))
+        })
+        .build();
+    for entry in walker.filter_map(Result::ok) {
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(parent) else {
+            continue;
+        };
+        if globs.matched(relative, false).is_whitelist() {
+            copied += copy_file(entry.path(), &worktree.join(relative))?;
+        }
+    }
+    Ok(copied)
+}
+
+pub(crate) async fn wait_for_run(
+    runs: &Mutex<HashMap<String, WorktreeSetupRun>>,
+    workspace_id: &str,
+    limit: Duration,
+) -> Result<Option<WorktreeSetupStatus>, String> {
+    let deadline = Instant::now() + limit;
+    loop {
+        let Some(mut status) = runs
+            .lock()
+            .await
+            .get(workspace_id)
+            .map(WorktreeSetupRun::subscribe)
+        else {
+            return Ok(None);
+        };
+        let finished = timeout_at(deadline, async {
+            status
+                .wait_for(|status| status.state != WorktreeSetupState::Running)
+                .await
+                .map(|status| status.clone())
+        })
+        .await;
+        match finished {
+            Ok(Ok(status)) => return Ok(Some(status)),
+            // Replaced by a rerun or canceled; look again.
+            Ok(Err(_)) => continue,
+            Err(_) => {
+                return Err(format!(
+                    "Worktree setup is still running after {} minutes. Cancel it to continue without it.",
+                    limit.as_secs() / 60
+                ))
+            }
+        }
+    }
+}
+
+/// Stops and forgets a worktree's setup run. Returns the status to publish
+/// unless it had already succeeded.
+pub(crate) async fn cancel_run(
+    runs: &Mutex<HashMap<String, WorktreeSetupRun>>,
+    workspace_id: &str,
+) -> Option<WorktreeSetupStatus> {
+    let run = runs.lock().await.remove(workspace_id)?;
+    let mut status = run.status();
+    drop(run);
+    if status.state == WorktreeSetupState::Succeeded {
+        return None;
+    }
+    status.state = WorktreeSetupState::Canceled;
+    Some(status)
+}
+
+/// Waits for a worktree's setup and rejects sending a message to it when
+/// the setup failed.
+pub(crate) async fn ensure_run_finished(
+    runs: &Mutex<HashMap<String, WorktreeSetupRun>>,
+    workspace_id: &str,
+) -> Result<(), String> {
+    match wait_for_run(runs, workspace_id, SETUP_WAIT_TIMEOUT).await? {
+        Some(status) if status.state == WorktreeSetupState::Failed => Err(format!(
+            "Worktree setup failed: {}. Rerun or cancel the setup to continue.",
+            status.error.as_deref().unwrap_or("unknown error")
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use super::{cancel_run, copy_setup_files, wait_for_run, WorktreeSetupRun};
+    use crate::backend::events::{
+        AppServerEvent, ApprovalAutoDecision, CloneProgress, EventSink, GitStatusChanged,
+        StorageChanged, TerminalOutput, WorktreeSetupOutput,
+    };
+    use crate::types::{WorktreeSetupConfig, WorktreeSetupState, WorktreeSetupStatus};
+
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_git_status_changed(&self, _event: GitStatusChanged) {}
+        fn emit_worktree_setup_output(&self, event: WorktreeSetupOutput) {
+            self.lines
+                .lock()
+                .unwrap()
+                .push(format!("{}: {}", event.stream, event.line));
+        }
+        fn emit_worktree_setup_status(&self, _event: WorktreeSetupStatus) {}
+        fn emit_clone_progress(&self, _event: CloneProgress) {}
+        fn emit_storage_changed(&self, _event: StorageChanged) {}
+        fn emit_approval_auto_decision(&self, _event: ApprovalAutoDecision) {}
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn copy_setup_files_copies_paths_and_globs() {
+        let parent = temp_dir();
+        let worktree = temp_dir();
+        std::fs::write(parent.join(".env"), "SECRET=1").unwrap();
+        std::fs::create_dir_all(parent.join("config/nested")).unwrap();
+        std::fs::write(parent.join("config/app.local.json"), "{}").unwrap();
+        std::fs::write(parent.join("config/app.json"), "{}").unwrap();
+        std::fs::create_dir_all(parent.join("cache/deep")).unwrap();
+        std::fs::write(parent.join("cache/deep/blob"), "x").unwrap();
+        std::fs::write(worktree.join(".env"), "KEEP=1").unwrap();
+
+        let patterns = vec![
+            ".env".to_string(),
+            "cache".to_string(),
+            "config/*.local.json".to_string(),
+            ".env.missing".to_string(),
+        ];
+        let copied = copy_setup_files(&parent, &worktree, &patterns).unwrap();
+
+        assert_eq!(copied, 2);
+        assert_eq!(
+            std::fs::read_to_string(worktree.join(".env")).unwrap(),
+            "KEEP=1"
+        );
+        assert!(worktree.join("cache/deep/blob").is_file());
+        assert!(worktree.join("config/app.local.json").is_file());
+        assert!(!worktree.join("config/app.json").exists());
+
+        let escape = copy_setup_files(&parent, &worktree, &["../outside".to_string()]);
+        assert!(escape.is_err());
+
+        let _ = std::fs::remove_dir_all(&parent);
+        let _ = std::fs::remove_dir_all(&worktree);
+    }
+
+    #[test]
+    fn setup_globs_skip_ignored_directories() {
+        let parent = temp_dir();
+        let worktree = temp_dir();
+        git2::Repository::init(&parent).unwrap();
+        std::fs::write(parent.join(".gitignore"), "node_modules/\n*.local.json\n").unwrap();
+        std::fs::create_dir_all(parent.join("config")).unwrap();
+        std::fs::write(parent.join("config/app.local.json"), "{}").unwrap();
+        std::fs::create_dir_all(parent.join("node_modules/dep")).unwrap();
+        std::fs::write(parent.join("node_modules/dep/app.local.json"), "{}").unwrap();
+
+        let patterns = vec![
+            "**/*.local.json".to_string(),
+            "node_modules/dep".to_string(),
+        ];
+        let copied = copy_setup_files(&parent, &worktree, &patterns).unwrap();
+
+        // Listing an ignored directory by name still copies it.
+        assert_eq!(copied, 2);
+        assert!(worktree.join("config/app.local.json").is_file());
+        assert!(worktree.join("node_modules/dep/app.local.json").is_file());
+
+        let copied = copy_setup_files(&parent, &temp_dir(), &patterns[..1]).unwrap();
+        assert_eq!(copied, 1);
+
+        let _ = std::fs::remove_dir_all(&parent);
+        let _ = std::fs::remove_dir_all(&worktree);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn setup_runs_commands_and_streams_output() {
+        let parent = temp_dir();
+        let worktree = temp_dir();
+        let sink = RecordingSink::default();
+        let config = WorktreeSetupConfig {
+            copy: Vec::new(),
+            commands: vec![
+                "echo ready".to_string(),
+                "echo oops >&2; exit 3".to_string(),
+            ],
+        };
+
+        let status = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let run = WorktreeSetupRun::start(
+                    "wt".to_string(),
+                    parent.clone(),
+                    worktree.clone(),
+                    config,
+                    sink.clone(),
+                );
+                let mut status = run.subscribe();
+                let finished = status
+                    .wait_for(|status| status.state != WorktreeSetupState::Running)
+                    .await
+                    .unwrap()
+                    .clone();
+                finished
+            });
+
+        assert_eq!(status.state, WorktreeSetupState::Failed);
+        assert_eq!(status.command.as_deref(), Some("echo oops >&2; exit 3"));
+        assert!(status.error.unwrap().contains("failed"));
+        assert_eq!(
+            *sink.lines.lock().unwrap(),
+            vec!["stdout: ready".to_string(), "stderr: oops".to_string()]
+        );
+
+        let _ = std::fs::remove_dir_all(&parent);
+        let _ = std::fs::remove_dir_all(&worktree);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn waiting_for_setup_times_out_and_ends_when_canceled() {
+        let parent = temp_dir();
+        let worktree = temp_dir();
+        let config = WorktreeSetupConfig {
+            copy: Vec::new(),
+            commands: vec!["sleep 30".to_string()],
+        };
+
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let runs = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+                let run = WorktreeSetupRun::start(
+                    "wt".to_string(),
+                    parent.clone(),
+                    worktree.clone(),
+                    config,
+                    RecordingSink::default(),
+                );
+                runs.lock().await.insert("wt".to_string(), run);
+
+                let waited = wait_for_run(&runs, "wt", Duration::from_millis(200)).await;
+                assert!(waited.unwrap_err().contains("still running"));
+
+                let waiter = tokio::spawn({
+                    let runs = runs.clone();
+                    async move { wait_for_run(&runs, "wt", Duration::from_secs(10)).await }
+                });
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                let canceled = cancel_run(&runs, "wt").await.unwrap();
+                assert_eq!(canceled.state, WorktreeSetupState::Canceled);
+                assert_eq!(canceled.command.as_deref(), Some("sleep 30"));
+                assert!(matches!(waiter.await.unwrap(), Ok(None)));
+                assert!(cancel_run(&runs, "wt").await.is_none());
+            });
+
+        let _ = std::fs::remove_dir_all(&parent);
+        let _ = std::fs::remove_dir_all(&worktree);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn canceling_setup_kills_the_commands_children() {
+        let parent = temp_dir();
+        let worktree = temp_dir();
+        let config = WorktreeSetupConfig {
+            copy: Vec::new(),
+            commands: vec!["sleep 30 & echo $! > child.pid; wait".to_string()],
+        };
+        let pid_file = worktree.join("child.pid");
+
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let runs = tokio::sync::Mutex::new(HashMap::new());
+                let run = WorktreeSetupRun::start(
+                    "wt".to_string(),
+                    parent.clone(),
+                    worktree.clone(),
+                    config,
+                    RecordingSink::default(),
+                );
+                runs.lock().await.insert("wt".to_string(), run);
+                while !std::fs::read_to_string(&pid_file).is_ok_and(|pid| pid.ends_with('\n')) {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                cancel_run(&runs, "wt").await.unwrap();
+            });
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let stat = PathBuf::from(format!("/proc/{}/stat", pid.trim()));
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        // Gone, or a zombie waiting for init to reap it.
+        let killed = || {
+            std::fs::read_to_string(&stat).map_or(true, |stat| {
+                stat.rsplit(')')
+                    .next()
+                    .unwrap_or("")
+                    .trim_start()
+                    .starts_with('Z')
+            })
+        };
+        while !killed() && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(killed());
+
+        let _ = std::fs::remove_dir_all(&parent);
+        let _ = std::fs::remove_dir_all(&worktree);
+    }
+}
@@ -2,17 +2,28 @@
 mod backend;
 #[path = "../codex_config.rs"]
 mod codex_config;
+#[path = "../git_utils.rs"]
+mod git_utils;
+#[path = "../git_watcher.rs"]
+mod git_watcher;
+#[path = "../sandbox.rs"]
+mod sandbox;
 #[path = "../storage.rs"]
 mod storage;
 #[path = "../types.rs"]
 mod types;
+#[path = "../utils.rs"]
+mod utils;
+#[path = "../worktree_paths.rs"]
+mod worktree_paths;
 
 use serde_json::{json, Map, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::env;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use ignore::WalkBuilder;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -21,12 +32,34 @@ use tokio::process::Command;
 use tokio::sync::{broadcast, mpsc, Mutex};
 use uuid::Uuid;
 
-use backend::app_server::{spawn_workspace_session, WorkspaceSession};
-use backend::events::{AppServerEvent, EventSink, TerminalOutput};
-use storage::{read_settings, read_workspaces, write_settings, write_workspaces};
+use backend::app_server::{
+    approval_rules_by_workspace, spawn_workspace_session, update_approval_rules, WorkspaceSession,
+};
+use backend::approvals::validate_approval_rules;
+use backend::clone::{clone_git_root, clone_repository};
+use backend::worktree_branches::resolve_worktree_branch;
+use backend::events::{
+    AppServerEvent, ApprovalAutoDecision, CloneProgress, EventSink, GitStatusChanged,
+    StorageChanged, TerminalOutput, WorktreeSetupOutput,
+};
+use backend::worktree_setup::{
+    cancel_run, ensure_run_finished, wait_for_run, WorktreeSetupRun, SETUP_WAIT_TIMEOUT,
+};
+use git_utils::resolve_git_root;
+use git_watcher::GitStatusWatcher;
+use sandbox::{
+    resolve_turn_policy, validate_sandbox_profiles, validate_workspace_sandbox_profile,
+};
+use storage::{
+    has_unloaded_changes, load_settings, load_workspaces, read_settings, read_workspaces,
+    remove_plaintext_token_backups, untouched_issue, watch_data_files, write_settings,
+    write_workspaces, DataFile,
+};
 use types::{
     AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
+    WorktreeSetupStatus,
 };
+use worktree_paths::worktree_root_dir;
 
 const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:4732";
 
@@ -39,6 +72,12 @@ struct DaemonEventSink {
 enum DaemonEvent {
     AppServer(AppServerEvent),
     TerminalOutput(TerminalOutput),
+    GitStatusChanged(GitStatusChanged),
+    WorktreeSetupOutput(WorktreeSetupOutput),
+    WorktreeSetupStatus(WorktreeSetupStatus),
+    CloneProgress(CloneProgress),
+    StorageChanged(StorageChanged),
+    ApprovalAutoDecision(ApprovalAutoDecision),
 }
 
 impl EventSink for DaemonEventSink {
@@ -49,6 +88,30 @@ impl EventSink for DaemonEventSink {
     fn emit_terminal_output(&self, event: TerminalOutput) {
         let _ = self.tx.send(DaemonEvent::TerminalOutput(event));
     }
+
+    fn emit_git_status_changed(&self, event: GitStatusChanged) {
+        let _ = self.tx.send(DaemonEvent::GitStatusChanged(event));
+    }
+
+    fn emit_worktree_setup_output(&self, event: WorktreeSetupOutput) {
+        let _ = self.tx.send(DaemonEvent::WorktreeSetupOutput(event));
+    }
+
+    fn emit_worktree_setup_status(&self, event: WorktreeSetupStatus) {
+        let _ = self.tx.send(DaemonEvent::WorktreeSetupStatus(event));
+    }
+
+    fn emit_clone_progress(&self, event: CloneProgress) {
+        let _ = self.tx.send(DaemonEvent::CloneProgress(event));
+    }
+
+    fn emit_storage_changed(&self, event: StorageChanged) {
+        let _ = self.tx.send(DaemonEvent::StorageChanged(event));
+    }
+
+    fn emit_approval_auto_decision(&self, event: ApprovalAutoDecision) {
+        let _ = self.tx.send(DaemonEvent::ApprovalAutoDecision(event));
+    }
 }
 
 struct DaemonConfig {
@@ -61,30 +124,98 @@ struct DaemonState {
     data_dir: PathBuf,
     workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
     sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    storage_path: PathBuf,
-    settings_path: PathBuf,
+    git_status_watchers: Mutex<HashMap<String, GitStatusWatcher>>,
+    worktree_setups: Mutex<HashMap<String, WorktreeSetupRun>>,
+    workspaces_file: DataFile,
+    settings_file: DataFile,
     app_settings: Mutex<AppSettings>,
     event_sink: DaemonEventSink,
 }
 
 impl DaemonState {
     fn load(config: &DaemonConfig, event_sink: DaemonEventSink) -> Self {
-        let storage_path = config.data_dir.join("workspaces.json");
-        let settings_path = config.data_dir.join("settings.json");
-        let workspaces = read_workspaces(&storage_path).unwrap_or_default();
-        let app_settings = read_settings(&settings_path).unwrap_or_default();
+        let workspaces_file = DataFile::new(config.data_dir.join("workspaces.json"));
+        let settings_file = DataFile::new(config.data_dir.join("settings.json"));
+        let (workspaces, workspaces_issue) =
+            load_workspaces(&workspaces_file).unwrap_or_else(|error| {
+                (
+                    HashMap::new(),
+                    Some(untouched_issue(&workspaces_file, error)),
+                )
+            });
+        let (app_settings, settings_issue) =
+            load_settings(&settings_file).unwrap_or_else(|error| {
+                (
+                    AppSettings::default(),
+                    Some(untouched_issue(&settings_file, error)),
+                )
+            });
+        for issue in workspaces_issue.iter().chain(settings_issue.iter()) {
+            eprintln!("Failed to read {}: {}", issue.file, issue.error);
+            if let Some(backup) = &issue.restored_from {
+                eprintln!("Restored from backup {backup}");
+            }
+        }
+        // The app moves plain-text tokens into its secret store; once none are
+        // left, backups from before that still hold them.
+        if settings_issue.is_none() && app_settings.legacy_secrets.is_empty() {
+            if let Err(err) = remove_plaintext_token_backups(&settings_file) {
+                eprintln!("Failed to remove old settings backups: {err}");
+            }
+        }
         Self {
             data_dir: config.data_dir.clone(),
             workspaces: Mutex::new(workspaces),
             sessions: Mutex::new(HashMap::new()),
-            storage_path,
-            settings_path,
+            git_status_watchers: Mutex::new(HashMap::new()),
+            worktree_setups: Mutex::new(HashMap::new()),
+            workspaces_file,
+            settings_file,
             app_settings: Mutex::new(app_settings),
             event_sink,
         }
     }
 
+    /// Picks up a data file changed by another process (usually the app).
+    async fn reload_data_file(&self, path: &Path) {
+        if path == self.workspaces_file.path() {
+            let rules = {
+                let mut workspaces = self.workspaces.lock().await;
+                if !has_unloaded_changes(&self.workspaces_file) {
+                    return;
+                }
+                match read_workspaces(&self.workspaces_file) {
+                    Ok(latest) => {
+                        *workspaces = latest;
+                        approval_rules_by_workspace(&workspaces)
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to reload {}: {err}", path.display());
+                        return;
+                    }
+                }
+            };
+            update_approval_rules(&self.sessions, rules).await;
+            self.event_sink.emit_storage_changed(StorageChanged { file: "workspaces" });
+        } else if path == self.settings_file.path() {
+            let mut settings = self.app_settings.lock().await;
+            if !has_unloaded_changes(&self.settings_file) {
+                return;
+            }
+            match read_settings(&self.settings_file) {
+                Ok(latest) => {
+                    *settings = latest;
+                    self.event_sink
+                        .emit_storage_changed(StorageChanged { file: "settings" });
+                }
+                Err(err) => eprintln!("Failed to reload {}: {err}", path.display()),
+            }
+        }
+    }
+
     async fn kill_session(&self, workspace_id: &str) {
+        self.git_status_watchers.lock().await.remove(workspace_id);
+        self.worktree_setups.lock().await.remove(workspace_id);
         let session = {
             let mut sessions = self.sessions.lock().await;
             sessions.remove(workspace_id)
@@ -123,6 +254,7 @@ impl DaemonState {
         &self,
         path: String,
         codex_bin: Option<String>,
+        settings: WorkspaceSettings,
         client_version: String,
     ) -> Result<WorkspaceInfo, String> {
         let name = PathBuf::from(&path)
@@ -139,7 +271,7 @@ impl DaemonState {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
-            settings: WorkspaceSettings::default(),
+            settings,
         };
 
         let default_bin = {
@@ -162,7 +294,7 @@ impl DaemonState {
             workspaces.insert(entry.id.clone(), entry.clone());
             workspaces.values().cloned().collect::<Vec<_>>()
         };
-        write_workspaces(&self.storage_path, &list)?;
+        write_workspaces(&self.workspaces_file, &list)?;
 
         self.sessions.lock().await.insert(entry.id.clone(), session);
 
@@ -179,16 +311,51 @@ impl DaemonState {
         })
     }
 
+    async fn clone_workspace(
+        &self,
+        url: String,
+        destination: String,
+        branch: Option<String>,
+        depth: Option<u32>,
+        codex_bin: Option<String>,
+        client_version: String,
+    ) -> Result<WorkspaceInfo, String> {
+        let url = url.trim();
+        if url.is_empty() {
+            return Err("Repository URL is required.".to_string());
+        }
+        let destination = PathBuf::from(destination.trim());
+        let branch = branch
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        clone_repository(url, &destination, branch, depth, &self.event_sink)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let settings = WorkspaceSettings {
+            git_root: clone_git_root(&destination),
+            ..WorkspaceSettings::default()
+        };
+        let path = destination.to_string_lossy().to_string();
+        self.add_workspace(path, codex_bin, settings, client_version).await
+    }
+
     async fn add_worktree(
         &self,
         parent_id: String,
         branch: String,
+        base_ref: Option<String>,
+        existing_branch: bool,
         client_version: String,
     ) -> Result<WorkspaceInfo, String> {
         let branch = branch.trim().to_string();
         if branch.trim().is_empty() {
             return Err("Branch name is required.".to_string());
         }
+        let base_ref = base_ref
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
 
         let parent_entry = {
             let workspaces = self.workspaces.lock().await;
@@ -202,7 +369,24 @@ impl DaemonState {
             return Err("Cannot create a worktree from another worktree.".to_string());
         }
 
-        let worktree_root = self.data_dir.join("worktrees").join(&parent_entry.id);
+        let repo_path = PathBuf::from(&parent_entry.path);
+        let (global_root, global_template) = {
+            let settings = self.app_settings.lock().await;
+            (
+                settings.worktree_root.clone(),
+                settings.worktree_branch_template.clone(),
+            )
+        };
+        let (branch, base_ref) = resolve_worktree_branch(
+            &parent_entry,
+            global_template.as_deref(),
+            &branch,
+            base_ref.as_deref(),
+            existing_branch,
+        )?;
+
+        let worktree_root =
+            worktree_root_dir(&parent_entry, global_root.as_deref(), &self.data_dir);
         std::fs::create_dir_all(&worktree_root)
             .map_err(|e| format!("Failed to create worktree directory: {e}"))?;
 
@@ -210,26 +394,19 @@ impl DaemonState {
         let worktree_path = unique_worktree_path(&worktree_root, &safe_name)?;
         let worktree_path_string = worktree_path.to_string_lossy().to_string();
 
-        let repo_path = PathBuf::from(&parent_entry.path);
-        let branch_exists = git_branch_exists(&repo_path, &branch).await?;
-        if branch_exists {
+        if git_branch_exists(&repo_path, &branch).await? {
+            if base_ref.is_some() {
+                return Err(format!("Branch {branch} already exists."));
+            }
             run_git_command(
                 &repo_path,
                 &["worktree", "add", &worktree_path_string, &branch],
             )
             .await?;
-        } else if let Some(remote_ref) = git_find_remote_tracking_branch(&repo_path, &branch).await? {
-            run_git_command(
-                &repo_path,
-                &["worktree", "add", "-b", &branch, &worktree_path_string, &remote_ref],
-            )
-            .await?;
         } else {
-            run_git_command(
-                &repo_path,
-                &["worktree", "add", "-b", &branch, &worktree_path_string],
-            )
-            .await?;
+            let mut args = vec!["worktree", "add", "-b", &branch, &worktree_path_string];
+            args.extend(base_ref.as_deref());
+            run_git_command(&repo_path, &args).await?;
         }
 
         let entry = WorkspaceEntry {
@@ -241,6 +418,8 @@ impl DaemonState {
             parent_id: Some(parent_entry.id.clone()),
             worktree: Some(WorktreeInfo {
                 branch: branch.to_string(),
+                issue: None,
+                pull_request: None,
             }),
             settings: WorkspaceSettings::default(),
         };
@@ -265,9 +444,10 @@ impl DaemonState {
             workspaces.insert(entry.id.clone(), entry.clone());
             workspaces.values().cloned().collect::<Vec<_>>()
         };
-        write_workspaces(&self.storage_path, &list)?;
+        write_workspaces(&self.workspaces_file, &list)?;
 
         self.sessions.lock().await.insert(entry.id.clone(), session);
+        self.start_worktree_setup(&parent_entry, &entry).await;
 
         Ok(WorkspaceInfo {
             id: entry.id,
@@ -282,6 +462,54 @@ impl DaemonState {
         })
     }
 
+    /// Starts the parent's setup for a new worktree, replacing any earlier
+    /// run. Does nothing when the parent has no setup configured.
+    async fn start_worktree_setup(&self, parent: &WorkspaceEntry, worktree: &WorkspaceEntry) {
+        let config = parent.settings.worktree_setup.clone();
+        if config.is_empty() {
+            return;
+        }
+        let run = WorktreeSetupRun::start(
+            worktree.id.clone(),
+            PathBuf::from(&parent.path),
+            PathBuf::from(&worktree.path),
+            config,
+            self.event_sink.clone(),
+        );
+        self.worktree_setups
+            .lock()
+            .await
+            .insert(worktree.id.clone(), run);
+    }
+
+    async fn rerun_worktree_setup(&self, workspace_id: String) -> Result<(), String> {
+        let (entry, parent) = {
+            let workspaces = self.workspaces.lock().await;
+            let entry = workspaces
+                .get(&workspace_id)
+                .cloned()
+                .ok_or("workspace not found")?;
+            let parent = entry
+                .parent_id
+                .as_ref()
+                .and_then(|parent_id| workspaces.get(parent_id))
+                .cloned()
+                .ok_or("Not a worktree workspace.")?;
+            (entry, parent)
+        };
+        if parent.settings.worktree_setup.is_empty() {
+            return Err("No worktree setup is configured for this workspace.".to_string());
+        }
+        self.start_worktree_setup(&parent, &entry).await;
+        Ok(())
+    }
+
+    async fn cancel_worktree_setup(&self, workspace_id: String) {
+        if let Some(status) = cancel_run(&self.worktree_setups, &workspace_id).await {
+            self.event_sink.emit_worktree_setup_status(status);
+        }
+    }
+
     async fn remove_workspace(&self, id: String) -> Result<(), String> {
         let (entry, child_worktrees) = {
             let workspaces = self.workspaces.lock().await;
@@ -335,7 +563,7 @@ impl DaemonState {
                 }
                 workspaces.values().cloned().collect::<Vec<_>>()
             };
-            write_workspaces(&self.storage_path, &list)?;
+            write_workspaces(&self.workspaces_file, &list)?;
         }
 
         if failures.is_empty() {
@@ -383,7 +611,7 @@ impl DaemonState {
             workspaces.remove(&entry.id);
             workspaces.values().cloned().collect::<Vec<_>>()
         };
-        write_workspaces(&self.storage_path, &list)?;
+        write_workspaces(&self.workspaces_file, &list)?;
 
         Ok(())
     }
@@ -393,6 +621,11 @@ impl DaemonState {
         id: String,
         settings: WorkspaceSettings,
     ) -> Result<WorkspaceInfo, String> {
+        validate_approval_rules(&settings.approval_rules)?;
+        {
+            let app_settings = self.app_settings.lock().await;
+            validate_workspace_sandbox_profile(&settings, &app_settings.sandbox_profiles)?;
+        }
         let (entry_snapshot, list) = {
             let mut workspaces = self.workspaces.lock().await;
             let entry_snapshot = match workspaces.get_mut(&id) {
@@ -405,9 +638,13 @@ impl DaemonState {
             let list: Vec<_> = workspaces.values().cloned().collect();
             (entry_snapshot, list)
         };
-        write_workspaces(&self.storage_path, &list)?;
+        write_workspaces(&self.workspaces_file, &list)?;
 
-        let connected = self.sessions.lock().await.contains_key(&id);
+        let session = self.sessions.lock().await.get(&id).cloned();
+        if let Some(session) = &session {
+            *session.approval_rules.lock().await = entry_snapshot.settings.approval_rules.clone();
+        }
+        let connected = session.is_some();
         Ok(WorkspaceInfo {
             id: entry_snapshot.id,
             name: entry_snapshot.name,
@@ -438,7 +675,7 @@ impl DaemonState {
             let list: Vec<_> = workspaces.values().cloned().collect();
             (entry_snapshot, list)
         };
-        write_workspaces(&self.storage_path, &list)?;
+        write_workspaces(&self.workspaces_file, &list)?;
 
         let connected = self.sessions.lock().await.contains_key(&id);
         Ok(WorkspaceInfo {
@@ -486,6 +723,10 @@ impl DaemonState {
             None
         };
         let codex_home = resolve_codex_home(&entry, parent_path.as_deref());
+        // Workspaces that aren't repositories simply get no status events.
+        let watcher = resolve_git_root(&entry).and_then(|repo_root| {
+            GitStatusWatcher::start(entry.id.clone(), repo_root, self.event_sink.clone())
+        });
         let session = spawn_workspace_session(
             entry,
             default_bin,
@@ -495,17 +736,29 @@ impl DaemonState {
         )
         .await?;
 
+        if let Ok(watcher) = watcher {
+            self.git_status_watchers
+                .lock()
+                .await
+                .insert(id.clone(), watcher);
+        }
         self.sessions.lock().await.insert(id, session);
         Ok(())
     }
 
-    async fn update_app_settings(&self, settings: AppSettings) -> Result<AppSettings, String> {
+    async fn update_app_settings(&self, mut settings: AppSettings) -> Result<AppSettings, String> {
+        {
+            let workspaces = self.workspaces.lock().await;
+            validate_sandbox_profiles(&settings.sandbox_profiles, workspaces.values())?;
+        }
         let _ = codex_config::write_collab_enabled(settings.experimental_collab_enabled);
         let _ = codex_config::write_steer_enabled(settings.experimental_steer_enabled);
         let _ = codex_config::write_unified_exec_enabled(settings.experimental_unified_exec_enabled);
-        write_settings(&self.settings_path, &settings)?;
         let mut current = self.app_settings.lock().await;
+        settings.keep_secrets_from(&current);
+        write_settings(&self.settings_file, &settings)?;
         *current = settings.clone();
+        settings.legacy_secrets.clear();
         Ok(settings)
     }
 
@@ -575,29 +828,31 @@ impl DaemonState {
         model: Option<String>,
         effort: Option<String>,
         access_mode: Option<String>,
+        sandbox_profile: Option<String>,
         images: Option<Vec<String>>,
         collaboration_mode: Option<Value>,
     ) -> Result<Value, String> {
+        // A new worktree may still be copying files or installing dependencies.
+        ensure_run_finished(&self.worktree_setups, &workspace_id).await?;
         let session = self.get_session(&workspace_id).await?;
-        let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
-        let sandbox_policy = match access_mode.as_str() {
-            "full-access" => json!({
-                "type": "dangerFullAccess"
-            }),
-            "read-only" => json!({
-                "type": "readOnly"
-            }),
-            _ => json!({
-                "type": "workspaceWrite",
-                "writableRoots": [session.entry.path],
-                "networkAccess": true
-            }),
-        };
-
-        let approval_policy = if access_mode == "full-access" {
-            "never"
-        } else {
-            "on-request"
+        let workspace = self
+            .workspaces
+            .lock()
+            .await
+            .get(&workspace_id)
+            .map(|entry| entry.settings.clone())
+            .unwrap_or_else(|| session.entry.settings.clone());
+        let policy = {
+            let app_settings = self.app_settings.lock().await;
+            resolve_turn_policy(
+                model,
+                effort,
+                access_mode,
+                sandbox_profile,
+                &session.entry.path,
+                &workspace,
+                &app_settings,
+            )?
         };
 
         let trimmed_text = text.trim();
@@ -629,10 +884,10 @@ impl DaemonState {
             "threadId": thread_id,
             "input": input,
             "cwd": session.entry.path,
-            "approvalPolicy": approval_policy,
-            "sandboxPolicy": sandbox_policy,
-            "model": model,
-            "effort": effort,
+            "approvalPolicy": policy.approval_policy,
+            "sandboxPolicy": policy.sandbox_policy,
+            "model": policy.model,
+            "effort": policy.effort,
             "collaborationMode": collaboration_mode,
         });
         session.send_request("turn/start", params).await
@@ -824,47 +1079,6 @@ async fn git_branch_exists(repo_path: &PathBuf, branch: &str) -> Result<bool, St
     Ok(status.success())
 }
 
-async fn git_remote_branch_exists(repo_path: &PathBuf, remote: &str, branch: &str) -> Result<bool, String> {
-    let status = Command::new("git")
-        .args([
-            "show-ref",
-            "--verify",
-            &format!("refs/remotes/{remote}/{branch}"),
-        ])
-        .current_dir(repo_path)
-        .status()
-        .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-    Ok(status.success())
-}
-
-async fn git_list_remotes(repo_path: &PathBuf) -> Result<Vec<String>, String> {
-    let output = run_git_command(repo_path, &["remote"]).await?;
-    Ok(output
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|line| line.to_string())
-        .collect())
-}
-
-async fn git_find_remote_tracking_branch(repo_path: &PathBuf, branch: &str) -> Result<Option<String>, String> {
-    if git_remote_branch_exists(repo_path, "origin", branch).await? {
-        return Ok(Some(format!("origin/{branch}")));
-    }
-
-    for remote in git_list_remotes(repo_path).await? {
-        if remote == "origin" {
-            continue;
-        }
-        if git_remote_branch_exists(repo_path, &remote, branch).await? {
-            return Ok(Some(format!("{remote}/{branch}")));
-        }
-    }
-
-    Ok(None)
-}
-
 fn sanitize_worktree_name(branch: &str) -> String {
     let mut result = String::new();
     for ch in branch.chars() {
@@ -1011,6 +1225,30 @@ fn build_event_notification(event: DaemonEvent) -> Option<String> {
             "method": "terminal-output",
             "params": payload,
         }),
+        DaemonEvent::GitStatusChanged(payload) => json!({
+            "method": "git-status-changed",
+            "params": payload,
+        }),
+        DaemonEvent::WorktreeSetupOutput(payload) => json!({
+            "method": "worktree-setup-output",
+            "params": payload,
+        }),
+        DaemonEvent::WorktreeSetupStatus(payload) => json!({
+            "method": "worktree-setup-status",
+            "params": payload,
+        }),
+        DaemonEvent::CloneProgress(payload) => json!({
+            "method": "clone-progress",
+            "params": payload,
+        }),
+        DaemonEvent::StorageChanged(payload) => json!({
+            "method": "storage-changed",
+            "params": payload,
+        }),
+        DaemonEvent::ApprovalAutoDecision(payload) => json!({
+            "method": "approval-auto-decision",
+            "params": payload,
+        }),
     };
     serde_json::to_string(&payload).ok()
 }
@@ -1101,14 +1339,29 @@ async fn handle_rpc_request(
         "add_workspace" => {
             let path = parse_string(&params, "path")?;
             let codex_bin = parse_optional_string(&params, "codex_bin");
-            let workspace = state.add_workspace(path, codex_bin, client_version).await?;
+            let workspace = state
+                .add_workspace(path, codex_bin, WorkspaceSettings::default(), client_version)
+                .await?;
+            serde_json::to_value(workspace).map_err(|err| err.to_string())
+        }
+        "clone_workspace" => {
+            let url = parse_string(&params, "url")?;
+            let destination = parse_string(&params, "destination")?;
+            let branch = parse_optional_string(&params, "branch");
+            let depth = parse_optional_u32(&params, "depth");
+            let codex_bin = parse_optional_string(&params, "codex_bin");
+            let workspace = state
+                .clone_workspace(url, destination, branch, depth, codex_bin, client_version)
+                .await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
         "add_worktree" => {
             let parent_id = parse_string(&params, "parentId")?;
             let branch = parse_string(&params, "branch")?;
+            let base_ref = parse_optional_string(&params, "baseRef");
+            let existing_branch = parse_optional_bool(&params, "existingBranch").unwrap_or(false);
             let workspace = state
-                .add_worktree(parent_id, branch, client_version)
+                .add_worktree(parent_id, branch, base_ref, existing_branch, client_version)
                 .await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
@@ -1127,6 +1380,32 @@ async fn handle_rpc_request(
             state.remove_worktree(id).await?;
             Ok(json!({ "ok": true }))
         }
+        "get_worktree_setup_status" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let status = state
+                .worktree_setups
+                .lock()
+                .await
+                .get(&workspace_id)
+                .map(WorktreeSetupRun::status);
+            serde_json::to_value(status).map_err(|err| err.to_string())
+        }
+        "wait_for_worktree_setup" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let status =
+                wait_for_run(&state.worktree_setups, &workspace_id, SETUP_WAIT_TIMEOUT).await?;
+            serde_json::to_value(status).map_err(|err| err.to_string())
+        }
+        "rerun_worktree_setup" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.rerun_worktree_setup(workspace_id).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "cancel_worktree_setup" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.cancel_worktree_setup(workspace_id).await;
+            Ok(json!({ "ok": true }))
+        }
         "update_workspace_settings" => {
             let id = parse_string(&params, "id")?;
             let settings_value = match params {
@@ -1151,6 +1430,7 @@ async fn handle_rpc_request(
         }
         "get_app_settings" => {
             let mut settings = state.app_settings.lock().await.clone();
+            settings.legacy_secrets.clear();
             if let Ok(Some(collab_enabled)) = codex_config::read_collab_enabled() {
                 settings.experimental_collab_enabled = collab_enabled;
             }
@@ -1199,6 +1479,7 @@ async fn handle_rpc_request(
             let model = parse_optional_string(&params, "model");
             let effort = parse_optional_string(&params, "effort");
             let access_mode = parse_optional_string(&params, "accessMode");
+            let sandbox_profile = parse_optional_string(&params, "sandboxProfile");
             let images = parse_optional_string_array(&params, "images");
             let collaboration_mode = parse_optional_value(&params, "collaborationMode");
             state
@@ -1209,6 +1490,7 @@ async fn handle_rpc_request(
                     model,
                     effort,
                     access_mode,
+                    sandbox_profile,
                     images,
                     collaboration_mode,
                 )
@@ -1402,17 +1684,38 @@ fn main() {
         let state = Arc::new(DaemonState::load(&config, event_sink));
         let config = Arc::new(config);
 
+        let (reload_tx, mut reload_rx) = mpsc::unbounded_channel::<PathBuf>();
+        match watch_data_files(
+            &[&state.workspaces_file, &state.settings_file],
+            reload_tx,
+        ) {
+            Ok(watcher) => {
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    let _watcher = watcher;
+                    while let Some(path) = reload_rx.recv().await {
+                        // Atomic writes show up as several events.
+                        tokio::time::sleep(Duration::from_millis(150)).await;
+                        let mut changed = BTreeSet::from([path]);
+                        while let Ok(path) = reload_rx.try_recv() {
+                            changed.insert(path);
+                        }
+                        for path in changed {
+                            state.reload_data_file(&path).await;
+                        }
+                    }
+                });
+            }
+            Err(err) => eprintln!("Failed to watch the data dir: {err}"),
+        }
+
         let listener = TcpListener::bind(config.listen)
             .await
             .unwrap_or_else(|err| panic!("failed to bind {}: {err}", config.listen));
         eprintln!(
             "codex-monitor-daemon listening on {} (data dir: {})",
             config.listen,
-            state
-                .storage_path
-                .parent()
-                .unwrap_or(&state.storage_path)
-                .display()
+            state.data_dir.display()
         );
 
         loop {
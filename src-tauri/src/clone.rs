@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::backend::clone::{clone_git_root, clone_repository, CloneError};
+use crate::event_sink::TauriEventSink;
+use crate::state::AppState;
+use crate::types::{WorkspaceInfo, WorkspaceSettings};
+use crate::workspaces::register_workspace;
+
+/// Clones a repository and registers it as a workspace, the way
+/// `add_workspace` does for a local path.
+#[tauri::command]
+pub(crate) async fn clone_workspace(
+    url: String,
+    destination: String,
+    branch: Option<String>,
+    depth: Option<u32>,
+    codex_bin: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, CloneError> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err("Repository URL is required.".to_string().into());
+    }
+    let destination = PathBuf::from(destination.trim());
+    let branch = branch
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let event_sink = TauriEventSink::new(app.clone());
+    clone_repository(url, &destination, branch, depth, &event_sink).await?;
+
+    let settings = WorkspaceSettings {
+        git_root: clone_git_root(&destination),
+        ..WorkspaceSettings::default()
+    };
+    let path = destination.to_string_lossy().to_string();
+    Ok(register_workspace(path, codex_bin, settings, &state, app).await?)
+}
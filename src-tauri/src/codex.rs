@@ -1,4 +1,5 @@
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -6,6 +7,7 @@ use std::time::Duration;
 
 use tauri::{AppHandle, State};
 use tokio::process::Command;
+use tokio::sync::Mutex;
 use tokio::time::timeout;
 
 pub(crate) use crate::backend::app_server::WorkspaceSession;
@@ -14,8 +16,13 @@ use crate::backend::app_server::{
     spawn_workspace_session as spawn_workspace_session_inner,
 };
 use crate::event_sink::TauriEventSink;
+use crate::sandbox::{resolve_turn_policy, TurnPolicy};
 use crate::state::AppState;
-use crate::types::WorkspaceEntry;
+use crate::types::{AppSettings, WorkspaceEntry};
+use crate::worktree_setup::ensure_setup_finished;
+
+/// How long a command waits for an agent turn it started to finish.
+const AGENT_REPLY_TIMEOUT: Duration = Duration::from_secs(600);
 
 pub(crate) async fn spawn_workspace_session(
     entry: WorkspaceEntry,
@@ -35,6 +42,162 @@ pub(crate) async fn spawn_workspace_session(
     .await
 }
 
+/// Fills in what the caller left out from the workspace's current settings
+/// (the session keeps the ones it was spawned with).
+async fn turn_policy(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    entry: &WorkspaceEntry,
+    model: Option<String>,
+    effort: Option<String>,
+    access_mode: Option<String>,
+    sandbox_profile: Option<String>,
+) -> Result<TurnPolicy, String> {
+    let workspace = workspaces
+        .lock()
+        .await
+        .get(&entry.id)
+        .map(|entry| entry.settings.clone())
+        .unwrap_or_else(|| entry.settings.clone());
+    let app_settings = app_settings.lock().await;
+    resolve_turn_policy(
+        model,
+        effort,
+        access_mode,
+        sandbox_profile,
+        &entry.path,
+        &workspace,
+        &app_settings,
+    )
+}
+
+fn response_error(response: &Value) -> Option<String> {
+    let error = response.get("error")?;
+    Some(
+        error
+            .get("message")
+            .and_then(|message| message.as_str())
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| error.to_string()),
+    )
+}
+
+/// Returns `thread_id` when given, otherwise starts a new thread and returns its id.
+async fn ensure_thread(
+    session: &WorkspaceSession,
+    thread_id: Option<String>,
+) -> Result<String, String> {
+    if let Some(thread_id) = thread_id.filter(|value| !value.trim().is_empty()) {
+        return Ok(thread_id);
+    }
+    let response = session
+        .send_request(
+            "thread/start",
+            json!({
+                "cwd": session.entry.path,
+                "approvalPolicy": "on-request"
+            }),
+        )
+        .await?;
+    if let Some(error) = response_error(&response) {
+        return Err(error);
+    }
+    response
+        .get("result")
+        .and_then(|result| result.get("thread"))
+        .and_then(|thread| thread.get("id"))
+        .and_then(|id| id.as_str())
+        .map(|id| id.to_string())
+        .ok_or_else(|| "thread/start did not return a thread id".to_string())
+}
+
+/// Starts a turn with a prompt composed by the backend, opening a new thread
+/// first when `thread_id` is not provided.
+pub(crate) async fn start_agent_turn(
+    state: &AppState,
+    session: &WorkspaceSession,
+    thread_id: Option<String>,
+    prompt: String,
+) -> Result<Value, String> {
+    let thread_id = ensure_thread(session, thread_id).await?;
+    let turn = start_turn(state, session, &thread_id, prompt, None).await?;
+    Ok(json!({ "threadId": thread_id, "turn": turn }))
+}
+
+/// Runs a read-only turn like [`start_agent_turn`] and waits for it, returning
+/// the thread id and the agent's final message.
+pub(crate) async fn run_read_only_agent_turn(
+    state: &AppState,
+    session: &WorkspaceSession,
+    thread_id: Option<String>,
+    prompt: String,
+) -> Result<(String, String), String> {
+    let thread_id = ensure_thread(session, thread_id).await?;
+    let reply = session.expect_reply(&thread_id).await;
+    let turn = start_turn(
+        state,
+        session,
+        &thread_id,
+        prompt,
+        Some("read-only".to_string()),
+    )
+    .await?;
+    if let Some(error) = response_error(&turn) {
+        return Err(error);
+    }
+    let text = timeout(AGENT_REPLY_TIMEOUT, reply)
+        .await
+        .map_err(|_| "The agent did not finish in time.".to_string())?
+        .map_err(|_| "The Codex session ended before the agent replied.".to_string())??;
+    Ok((thread_id, text))
+}
+
+async fn start_turn(
+    state: &AppState,
+    session: &WorkspaceSession,
+    thread_id: &str,
+    prompt: String,
+    access_mode: Option<String>,
+) -> Result<Value, String> {
+    let policy = turn_policy(
+        &state.workspaces,
+        &state.app_settings,
+        &session.entry,
+        None,
+        None,
+        access_mode,
+        None,
+    )
+    .await?;
+    let params = json!({
+        "threadId": thread_id,
+        "input": [{ "type": "text", "text": prompt }],
+        "cwd": session.entry.path,
+        "approvalPolicy": policy.approval_policy,
+        "sandboxPolicy": policy.sandbox_policy,
+        "model": policy.model,
+        "effort": policy.effort,
+    });
+    session.send_request("turn/start", params).await
+}
+
+/// Starts a review of `target` (e.g. `{"type": "baseBranch", "branch": "origin/main"}`),
+/// opening a new thread first when `thread_id` is not provided.
+pub(crate) async fn start_agent_review(
+    session: &WorkspaceSession,
+    thread_id: Option<String>,
+    target: Value,
+) -> Result<Value, String> {
+    let thread_id = ensure_thread(session, thread_id).await?;
+    let review = session
+        .send_request(
+            "review/start",
+            json!({ "threadId": thread_id, "target": target }),
+        )
+        .await?;
+    Ok(json!({ "threadId": thread_id, "review": review }))
+}
+
 #[tauri::command]
 pub(crate) async fn codex_doctor(
     codex_bin: Option<String>,
@@ -201,34 +364,30 @@ pub(crate) async fn send_user_message(
     model: Option<String>,
     effort: Option<String>,
     access_mode: Option<String>,
+    sandbox_profile: Option<String>,
     images: Option<Vec<String>>,
     collaboration_mode: Option<Value>,
     state: State<'_, AppState>,
 ) -> Result<Value, String> {
-    let sessions = state.sessions.lock().await;
-    let session = sessions
+    // A new worktree may still be copying files or installing dependencies.
+    ensure_setup_finished(&state, &workspace_id).await?;
+    let session = state
+        .sessions
+        .lock()
+        .await
         .get(&workspace_id)
+        .cloned()
         .ok_or("workspace not connected")?;
-    let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
-    let sandbox_policy = match access_mode.as_str() {
-        "full-access" => json!({
-            "type": "dangerFullAccess"
-        }),
-        "read-only" => json!({
-            "type": "readOnly"
-        }),
-        _ => json!({
-            "type": "workspaceWrite",
-            "writableRoots": [session.entry.path],
-            "networkAccess": true
-        }),
-    };
-
-    let approval_policy = if access_mode == "full-access" {
-        "never"
-    } else {
-        "on-request"
-    };
+    let policy = turn_policy(
+        &state.workspaces,
+        &state.app_settings,
+        &session.entry,
+        model,
+        effort,
+        access_mode,
+        sandbox_profile,
+    )
+    .await?;
 
     let trimmed_text = text.trim();
     let mut input: Vec<Value> = Vec::new();
@@ -259,10 +418,10 @@ pub(crate) async fn send_user_message(
         "threadId": thread_id,
         "input": input,
         "cwd": session.entry.path,
-        "approvalPolicy": approval_policy,
-        "sandboxPolicy": sandbox_policy,
-        "model": model,
-        "effort": effort,
+        "approvalPolicy": policy.approval_policy,
+        "sandboxPolicy": policy.sandbox_policy,
+        "model": policy.model,
+        "effort": policy.effort,
         "collaborationMode": collaboration_mode,
     });
     session.send_request("turn/start", params).await
@@ -378,3 +537,73 @@ pub(crate) async fn respond_to_server_request(
         .ok_or("workspace not connected")?;
     session.send_response(request_id, result).await
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tokio::sync::Mutex;
+
+    use super::turn_policy;
+    use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+    #[test]
+    fn untouched_composer_gets_the_saved_workspace_defaults() {
+        // The session keeps the entry it was spawned with; the defaults are
+        // saved afterwards.
+        let spawned = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "ws".to_string(),
+            path: "/repo".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+        let mut saved = spawned.clone();
+        saved.settings = WorkspaceSettings {
+            default_model: Some("gpt-5".to_string()),
+            default_effort: Some("high".to_string()),
+            default_access_mode: Some("read-only".to_string()),
+            ..Default::default()
+        };
+        let workspaces = Mutex::new(HashMap::from([("ws".to_string(), saved)]));
+        let app_settings = Mutex::new(AppSettings {
+            default_access_mode: "full-access".to_string(),
+            ..Default::default()
+        });
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime");
+
+        // The composer sends nothing it wasn't told to change.
+        let policy = runtime
+            .block_on(turn_policy(
+                &workspaces,
+                &app_settings,
+                &spawned,
+                None,
+                None,
+                None,
+                None,
+            ))
+            .expect("policy");
+        assert_eq!(policy.model.as_deref(), Some("gpt-5"));
+        assert_eq!(policy.effort.as_deref(), Some("high"));
+        assert_eq!(policy.sandbox_policy["type"], "readOnly");
+
+        let policy = runtime
+            .block_on(turn_policy(
+                &workspaces,
+                &app_settings,
+                &spawned,
+                None,
+                None,
+                Some("current".to_string()),
+                None,
+            ))
+            .expect("policy");
+        assert_eq!(policy.sandbox_policy["type"], "workspaceWrite");
+    }
+}
@@ -1,6 +1,10 @@
 use tauri::{AppHandle, Emitter};
 
-use crate::backend::events::{AppServerEvent, EventSink, TerminalOutput};
+use crate::backend::events::{
+    AppServerEvent, ApprovalAutoDecision, CloneProgress, EventSink, GitStatusChanged,
+    StorageChanged, TerminalOutput, WorktreeSetupOutput,
+};
+use crate::types::WorktreeSetupStatus;
 
 #[derive(Clone)]
 pub(crate) struct TauriEventSink {
@@ -21,4 +25,28 @@ impl EventSink for TauriEventSink {
     fn emit_terminal_output(&self, event: TerminalOutput) {
         let _ = self.app.emit("terminal-output", event);
     }
+
+    fn emit_git_status_changed(&self, event: GitStatusChanged) {
+        let _ = self.app.emit("git-status-changed", event);
+    }
+
+    fn emit_worktree_setup_output(&self, event: WorktreeSetupOutput) {
+        let _ = self.app.emit("worktree-setup-output", event);
+    }
+
+    fn emit_worktree_setup_status(&self, event: WorktreeSetupStatus) {
+        let _ = self.app.emit("worktree-setup-status", event);
+    }
+
+    fn emit_clone_progress(&self, event: CloneProgress) {
+        let _ = self.app.emit("clone-progress", event);
+    }
+
+    fn emit_storage_changed(&self, event: StorageChanged) {
+        let _ = self.app.emit("storage-changed", event);
+    }
+
+    fn emit_approval_auto_decision(&self, event: ApprovalAutoDecision) {
+        let _ = self.app.emit("approval-auto-decision", event);
+    }
 }
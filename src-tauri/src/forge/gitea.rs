@@ -0,0 +1,405 @@
+use serde::Deserialize;
+
+use super::{
+    encode_path_segment, filter_value, header_count, list_state, page_size, Forge, ForgeFuture,
+    ForgeHttp,
+};
+use crate::git_utils::parse_pr_diff;
+use crate::types::{
+    ForgeListQuery, ForgeRemote, GitHubIssue, GitHubIssuesResponse, GitHubPullRequest,
+    GitHubPullRequestAuthor, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestsResponse,
+};
+
+/// Gitea and Forgejo (including Codeberg) through the v1 REST API.
+pub(crate) struct GiteaForge {
+    remote: ForgeRemote,
+    http: ForgeHttp,
+}
+
+#[derive(Deserialize)]
+struct GiteaUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaIssue {
+    number: u64,
+    title: String,
+    html_url: String,
+    updated_at: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaLabel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaBranch {
+    #[serde(rename = "ref")]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaPullRequest {
+    number: u64,
+    title: String,
+    html_url: String,
+    updated_at: String,
+    created_at: String,
+    #[serde(default)]
+    body: Option<String>,
+    head: GiteaBranch,
+    base: GiteaBranch,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    merged: bool,
+    #[serde(default)]
+    user: Option<GiteaUser>,
+    #[serde(default)]
+    labels: Vec<GiteaLabel>,
+    #[serde(default)]
+    assignees: Option<Vec<GiteaUser>>,
+}
+
+#[derive(Deserialize)]
+struct GiteaComment {
+    id: u64,
+    #[serde(default)]
+    body: String,
+    created_at: String,
+    #[serde(default)]
+    html_url: String,
+    #[serde(default)]
+    user: Option<GiteaUser>,
+}
+
+fn author(user: Option<GiteaUser>) -> Option<GitHubPullRequestAuthor> {
+    user.map(|user| GitHubPullRequestAuthor { login: user.login })
+}
+
+/// Gitea pages by number; the cursor is the page to fetch.
+fn page_number(query: &ForgeListQuery) -> usize {
+    filter_value(&query.cursor)
+        .and_then(|cursor| cursor.parse().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+fn gitea_state(query: &ForgeListQuery) -> &'static str {
+    match list_state(query).as_str() {
+        "closed" | "merged" => "closed",
+        "all" => "all",
+        _ => "open",
+    }
+}
+
+fn next_page(page: usize, limit: usize, returned: usize, total: Option<usize>) -> Option<String> {
+    let more = match total {
+        Some(total) => page * limit < total,
+        None => returned == limit,
+    };
+    more.then(|| (page + 1).to_string())
+}
+
+fn issue_params(query: &ForgeListQuery) -> String {
+    let mut params = vec![
+        format!("state={}", gitea_state(query)),
+        "type=issues".to_string(),
+        format!("page={}", page_number(query)),
+        format!("limit={}", page_size(query)),
+    ];
+    let labels: Vec<&str> = query
+        .labels
+        .iter()
+        .map(|label| label.trim())
+        .filter(|label| !label.is_empty())
+        .collect();
+    if !labels.is_empty() {
+        params.push(format!("labels={}", encode_path_segment(&labels.join(","))));
+    }
+    if let Some(author) = filter_value(&query.author) {
+        params.push(format!("created_by={}", encode_path_segment(author)));
+    }
+    if let Some(assignee) = filter_value(&query.assignee) {
+        params.push(format!("assigned_by={}", encode_path_segment(assignee)));
+    }
+    if let Some(search) = filter_value(&query.search) {
+        params.push(format!("q={}", encode_path_segment(search)));
+    }
+    params.join("&")
+}
+
+/// The pull list endpoint only filters by state and poster, so labels,
+/// assignee, search and `merged` are applied to the fetched page.
+fn pull_matches(pull: &GiteaPullRequest, query: &ForgeListQuery) -> bool {
+    if list_state(query) == "merged" && !pull.merged {
+        return false;
+    }
+    let has_labels = query.labels.iter().all(|wanted| {
+        let wanted = wanted.trim();
+        wanted.is_empty()
+            || pull
+                .labels
+                .iter()
+                .any(|label| label.name.eq_ignore_ascii_case(wanted))
+    });
+    let has_assignee = filter_value(&query.assignee).is_none_or(|wanted| {
+        pull.assignees
+            .iter()
+            .flatten()
+            .any(|user| user.login.eq_ignore_ascii_case(wanted))
+    });
+    let has_text = filter_value(&query.search).is_none_or(|search| {
+        let search = search.to_lowercase();
+        pull.title.to_lowercase().contains(&search)
+            || pull
+                .body
+                .as_deref()
+                .is_some_and(|body| body.to_lowercase().contains(&search))
+    });
+    has_labels && has_assignee && has_text
+}
+
+fn has_client_filters(query: &ForgeListQuery) -> bool {
+    list_state(query) == "merged"
+        || query.labels.iter().any(|label| !label.trim().is_empty())
+        || filter_value(&query.assignee).is_some()
+        || filter_value(&query.search).is_some()
+}
+
+impl GiteaForge {
+    pub(crate) fn new(remote: ForgeRemote, token: Option<String>) -> Result<Self, String> {
+        let http = ForgeHttp::new(token.map(|token| ("authorization", format!("token {token}"))))?;
+        Ok(Self { remote, http })
+    }
+
+    fn repo_url(&self, path: &str) -> String {
+        format!("{}/repos/{}{path}", self.remote.api_base, self.remote.repo)
+    }
+
+    async fn issues(&self, query: &ForgeListQuery) -> Result<GitHubIssuesResponse, String> {
+        let url = self.repo_url(&format!("/issues?{}", issue_params(query)));
+        let (issues, headers) = self.http.get_json::<Vec<GiteaIssue>>(&url).await?;
+        let returned = issues.len();
+        let issues: Vec<GitHubIssue> = issues
+            .into_iter()
+            .map(|issue| GitHubIssue {
+                number: issue.number,
+                title: issue.title,
+                url: issue.html_url,
+                updated_at: issue.updated_at,
+            })
+            .collect();
+        let total = header_count(&headers, "x-total-count");
+        let next_cursor = next_page(page_number(query), page_size(query), returned, total);
+        Ok(GitHubIssuesResponse {
+            total: total.unwrap_or(returned),
+            issues,
+            next_cursor,
+        })
+    }
+
+    async fn pull_requests(
+        &self,
+        query: &ForgeListQuery,
+    ) -> Result<GitHubPullRequestsResponse, String> {
+        let page = page_number(query);
+        let limit = page_size(query);
+        let mut params = format!(
+            "state={}&sort=recentupdate&page={page}&limit={limit}",
+            gitea_state(query)
+        );
+        if let Some(author) = filter_value(&query.author) {
+            params.push_str(&format!("&poster={}", encode_path_segment(author)));
+        }
+        let url = self.repo_url(&format!("/pulls?{params}"));
+        let (pulls, headers) = self.http.get_json::<Vec<GiteaPullRequest>>(&url).await?;
+        let returned = pulls.len();
+        let pull_requests: Vec<GitHubPullRequest> = pulls
+            .into_iter()
+            .filter(|pull| pull_matches(pull, query))
+            .map(|pull| GitHubPullRequest {
+                number: pull.number,
+                title: pull.title,
+                url: pull.html_url,
+                updated_at: pull.updated_at,
+                created_at: pull.created_at,
+                body: pull.body.unwrap_or_default(),
+                head_ref_name: pull.head.name,
+                base_ref_name: pull.base.name,
+                is_draft: pull.draft,
+                author: author(pull.user),
+            })
+            .collect();
+        let header_total = header_count(&headers, "x-total-count");
+        let next_cursor = next_page(page, limit, returned, header_total);
+        let total = match header_total {
+            Some(total) if !has_client_filters(query) => total,
+            _ => pull_requests.len(),
+        };
+        Ok(GitHubPullRequestsResponse {
+            total,
+            pull_requests,
+            next_cursor,
+        })
+    }
+
+    async fn diff(&self, number: u64) -> Result<Vec<GitHubPullRequestDiff>, String> {
+        let url = self.repo_url(&format!("/pulls/{number}.diff"));
+        let (body, _) = self.http.get(&url).await?;
+        Ok(parse_pr_diff(&String::from_utf8_lossy(&body)))
+    }
+
+    async fn comments(&self, number: u64) -> Result<Vec<GitHubPullRequestComment>, String> {
+        let url = self.repo_url(&format!("/issues/{number}/comments"));
+        let (comments, _) = self.http.get_json::<Vec<GiteaComment>>(&url).await?;
+        Ok(comments
+            .into_iter()
+            .map(|comment| GitHubPullRequestComment {
+                id: comment.id,
+                body: comment.body,
+                created_at: comment.created_at,
+                url: comment.html_url,
+                author: author(comment.user),
+            })
+            .collect())
+    }
+}
+
+impl Forge for GiteaForge {
+    fn remote(&self) -> &ForgeRemote {
+        &self.remote
+    }
+
+    fn list_issues<'a>(
+        &'a self,
+        query: &'a ForgeListQuery,
+    ) -> ForgeFuture<'a, GitHubIssuesResponse> {
+        Box::pin(self.issues(query))
+    }
+
+    fn list_merge_requests<'a>(
+        &'a self,
+        query: &'a ForgeListQuery,
+    ) -> ForgeFuture<'a, GitHubPullRequestsResponse> {
+        Box::pin(self.pull_requests(query))
+    }
+
+    fn merge_request_diff(&self, number: u64) -> ForgeFuture<'_, Vec<GitHubPullRequestDiff>> {
+        Box::pin(self.diff(number))
+    }
+
+    fn merge_request_comments(
+        &self,
+        number: u64,
+    ) -> ForgeFuture<'_, Vec<GitHubPullRequestComment>> {
+        Box::pin(self.comments(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use mockito::{Matcher, Server};
+
+    use super::GiteaForge;
+    use crate::forge::test_support::block_on;
+    use crate::forge::{parse_forge_remote, Forge};
+    use crate::types::{ForgeKind, ForgeListQuery};
+
+    #[test]
+    fn lists_pull_requests_and_parses_diffs() {
+        let mut server = Server::new();
+        let pulls = server
+            .mock("GET", "/api/v1/repos/me/app/pulls")
+            .match_query(Matcher::UrlEncoded("state".into(), "open".into()))
+            .match_header("authorization", "token secret")
+            .with_header("x-total-count", "12")
+            .with_body(
+                r#"[{"number": 4, "merged": false, "labels": [{"name": "ui"}], "title": "Add search", "html_url": "https://gitea/me/app/pulls/4", "updated_at": "2024-05-02T10:00:00Z", "created_at": "2024-05-01T10:00:00Z", "body": "Adds search", "head": {"ref": "search"}, "base": {"ref": "main"}, "user": {"login": "kim"}}]"#,
+            )
+            .create();
+        server
+            .mock("GET", "/api/v1/repos/me/app/pulls/4.diff")
+            .with_body("diff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-old\n+new\n")
+            .create();
+        let url = format!("{}/me/app.git", server.url());
+        let overrides = BTreeMap::from([(server.host_with_port(), ForgeKind::Gitea)]);
+        let remote = parse_forge_remote(&url, &overrides).unwrap();
+        let forge = GiteaForge::new(remote, Some("secret".to_string())).unwrap();
+
+        let response = block_on(forge.list_merge_requests(&ForgeListQuery::default())).unwrap();
+        assert_eq!(response.total, 12);
+        assert!(response.next_cursor.is_none());
+        let pull = &response.pull_requests[0];
+        assert_eq!(pull.number, 4);
+        assert_eq!(pull.head_ref_name, "search");
+        assert_eq!(pull.base_ref_name, "main");
+        assert!(!pull.is_draft);
+
+        let diffs = block_on(forge.merge_request_diff(4)).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path, "README.md");
+        assert_eq!(diffs[0].status, "M");
+
+        pulls.assert();
+    }
+
+    #[test]
+    fn pages_issues_and_filters_pulls_on_the_client() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/api/v1/repos/me/app/issues")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("page".into(), "2".into()),
+                Matcher::UrlEncoded("limit".into(), "1".into()),
+                Matcher::UrlEncoded("created_by".into(), "kim".into()),
+                Matcher::UrlEncoded("q".into(), "search box".into()),
+            ]))
+            .with_header("x-total-count", "3")
+            .with_body(
+                r#"[{"number": 9, "title": "Search box lags", "html_url": "https://gitea/me/app/issues/9", "updated_at": "2024-05-02T10:00:00Z"}]"#,
+            )
+            .create();
+        server
+            .mock("GET", "/api/v1/repos/me/app/pulls")
+            .match_query(Matcher::UrlEncoded("state".into(), "all".into()))
+            .with_header("x-total-count", "2")
+            .with_body(
+                r#"[
+                    {"number": 4, "merged": false, "labels": [{"name": "UI"}], "title": "Add search", "html_url": "u", "updated_at": "t", "created_at": "t", "head": {"ref": "a"}, "base": {"ref": "main"}},
+                    {"number": 5, "merged": true, "labels": [], "title": "Fix build", "html_url": "u", "updated_at": "t", "created_at": "t", "head": {"ref": "b"}, "base": {"ref": "main"}}
+                ]"#,
+            )
+            .create();
+        let url = format!("{}/me/app.git", server.url());
+        let overrides = BTreeMap::from([(server.host_with_port(), ForgeKind::Gitea)]);
+        let remote = parse_forge_remote(&url, &overrides).unwrap();
+        let forge = GiteaForge::new(remote, None).unwrap();
+
+        let query = ForgeListQuery {
+            author: Some("kim".to_string()),
+            search: Some("search box".to_string()),
+            cursor: Some("2".to_string()),
+            page_size: Some(1),
+            ..ForgeListQuery::default()
+        };
+        let response = block_on(forge.list_issues(&query)).unwrap();
+        assert_eq!(response.total, 3);
+        assert_eq!(response.issues[0].number, 9);
+        assert_eq!(response.next_cursor.as_deref(), Some("3"));
+
+        let query = ForgeListQuery {
+            state: Some("all".to_string()),
+            labels: vec!["ui".to_string()],
+            ..ForgeListQuery::default()
+        };
+        let response = block_on(forge.list_merge_requests(&query)).unwrap();
+        assert_eq!(response.total, 1);
+        assert_eq!(response.pull_requests[0].number, 4);
+    }
+}
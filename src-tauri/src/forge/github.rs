@@ -0,0 +1,1467 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::github_api::{GitHubApiError, GitHubClient, DIFF_MEDIA_TYPE, JSON_MEDIA_TYPE};
+use super::{
+    filter_value, forge_remote_from_path, host_trust, list_state, page_size, Forge, ForgeFuture,
+    HostTrust,
+};
+use crate::git::{
+    fetch_pull_request_diff, run_gh_command, run_gh_command_with_input, PULL_REQUEST_FIELDS,
+};
+use crate::git_utils::parse_pr_diff;
+use crate::settings::read_secret;
+use crate::state::AppState;
+use crate::types::{
+    ForgeKind, ForgeListQuery, ForgeRemote, GitHubCheckRun, GitHubCommitStatus, GitHubIssue,
+    GitHubIssueComment, GitHubIssueDetail, GitHubIssuesResponse, GitHubLabel, GitHubPullRequest,
+    GitHubPullRequestAuthor, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestReview, GitHubPullRequestsResponse, GitHubReviewCommentPreview,
+    GitHubReviewDraft, GitHubReviewPreview, SecretKind,
+};
+
+const ISSUE_SEARCH: &str = "query($q: String!, $first: Int!, $after: String) { search(query: $q, type: ISSUE, first: $first, after: $after) { issueCount pageInfo { endCursor hasNextPage } nodes { ... on Issue { number title url updatedAt } } } }";
+
+const PULL_REQUEST_SEARCH: &str = "query($q: String!, $first: Int!, $after: String) { search(query: $q, type: ISSUE, first: $first, after: $after) { issueCount pageInfo { endCursor hasNextPage } nodes { ... on PullRequest { number title url updatedAt createdAt body headRefName baseRefName isDraft author { login } } } } }";
+
+#[derive(Deserialize)]
+struct PageInfo {
+    #[serde(rename = "endCursor")]
+    end_cursor: Option<String>,
+    #[serde(rename = "hasNextPage")]
+    has_next_page: bool,
+}
+
+#[derive(Deserialize)]
+struct SearchPage<T> {
+    #[serde(rename = "issueCount")]
+    issue_count: usize,
+    #[serde(rename = "pageInfo")]
+    page_info: PageInfo,
+    nodes: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct SearchData<T> {
+    search: SearchPage<T>,
+}
+
+#[derive(Deserialize)]
+struct RestUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct RestComment {
+    id: u64,
+    #[serde(default)]
+    body: Option<String>,
+    created_at: String,
+    #[serde(default)]
+    html_url: String,
+    #[serde(default)]
+    user: Option<RestUser>,
+}
+
+#[derive(Deserialize)]
+struct RestIssue {
+    number: u64,
+    title: String,
+    html_url: String,
+    state: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+    #[serde(default)]
+    user: Option<RestUser>,
+    updated_at: String,
+}
+
+#[derive(Deserialize)]
+struct RestBranch {
+    #[serde(rename = "ref")]
+    name: String,
+    #[serde(default)]
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct RestPullRequest {
+    number: u64,
+    title: String,
+    html_url: String,
+    updated_at: String,
+    created_at: String,
+    #[serde(default)]
+    body: Option<String>,
+    head: RestBranch,
+    base: RestBranch,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    user: Option<RestUser>,
+}
+
+#[derive(Deserialize)]
+struct RestApp {
+    slug: String,
+}
+
+#[derive(Deserialize)]
+struct RestCheckRun {
+    id: u64,
+    name: String,
+    status: String,
+    #[serde(default)]
+    conclusion: Option<String>,
+    #[serde(default)]
+    html_url: Option<String>,
+    #[serde(default)]
+    started_at: Option<String>,
+    #[serde(default)]
+    completed_at: Option<String>,
+    #[serde(default)]
+    app: Option<RestApp>,
+}
+
+#[derive(Deserialize)]
+struct RestCheckRuns {
+    check_runs: Vec<RestCheckRun>,
+}
+
+#[derive(Deserialize)]
+struct RestStatus {
+    context: String,
+    state: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    target_url: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RestCombinedStatus {
+    statuses: Vec<RestStatus>,
+}
+
+#[derive(Deserialize)]
+struct RestReview {
+    id: u64,
+    state: String,
+    #[serde(default)]
+    html_url: String,
+    #[serde(default)]
+    submitted_at: Option<String>,
+}
+
+impl From<RestCheckRun> for GitHubCheckRun {
+    fn from(run: RestCheckRun) -> Self {
+        Self {
+            id: run.id,
+            name: run.name,
+            status: run.status,
+            conclusion: run.conclusion,
+            url: run.html_url,
+            started_at: run.started_at,
+            completed_at: run.completed_at,
+            app_slug: run.app.map(|app| app.slug),
+            log_excerpt: None,
+        }
+    }
+}
+
+impl From<RestStatus> for GitHubCommitStatus {
+    fn from(status: RestStatus) -> Self {
+        Self {
+            context: status.context,
+            state: status.state,
+            description: status.description,
+            url: status.target_url,
+            updated_at: status.updated_at,
+        }
+    }
+}
+
+fn author(user: Option<RestUser>) -> Option<GitHubPullRequestAuthor> {
+    user.map(|user| GitHubPullRequestAuthor { login: user.login })
+}
+
+impl From<RestPullRequest> for GitHubPullRequest {
+    fn from(pull_request: RestPullRequest) -> Self {
+        Self {
+            number: pull_request.number,
+            title: pull_request.title,
+            url: pull_request.html_url,
+            updated_at: pull_request.updated_at,
+            created_at: pull_request.created_at,
+            body: pull_request.body.unwrap_or_default(),
+            head_ref_name: pull_request.head.name,
+            base_ref_name: pull_request.base.name,
+            is_draft: pull_request.draft,
+            author: author(pull_request.user),
+        }
+    }
+}
+
+/// Maps an API result to `None` when the `gh` CLI is worth trying instead.
+fn api_result<T>(result: Result<T, GitHubApiError>) -> Option<Result<T, String>> {
+    match result {
+        Ok(value) => Some(Ok(value)),
+        Err(error) if error.allows_cli_fallback() => None,
+        Err(error) => Some(Err(error.to_string())),
+    }
+}
+
+/// Like `api_result`, for requests that change something. A network error
+/// may come after GitHub acted on the request, so only a rejected token is
+/// retried through `gh`.
+fn api_write_result<T>(result: Result<T, GitHubApiError>) -> Option<Result<T, String>> {
+    match result {
+        Err(GitHubApiError::Unauthorized(_)) => None,
+        result => Some(result.map_err(|error| error.to_string())),
+    }
+}
+
+/// Splits `gh`-style reviewers into users and `org/team` team slugs.
+fn split_reviewers(reviewers: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut users = Vec::new();
+    let mut teams = Vec::new();
+    for reviewer in reviewers {
+        match reviewer.split_once('/') {
+            Some((_, team)) => teams.push(team.to_string()),
+            None => users.push(reviewer.clone()),
+        }
+    }
+    (users, teams)
+}
+
+/// Where a GitHub token may come from for a host trusted this far: whether
+/// the saved token applies, and which environment variables `gh` honours.
+/// The saved token is for github.com, so Enterprise hosts need their own.
+fn token_sources(trust: HostTrust) -> Option<(bool, &'static [&'static str])> {
+    match trust {
+        HostTrust::Public => Some((true, &["GH_TOKEN", "GITHUB_TOKEN"])),
+        HostTrust::Configured => Some((false, &["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"])),
+        HostTrust::Untrusted => None,
+    }
+}
+
+/// Reads a token for the remote's host: the one in the secret store, then
+/// the environment, then `gh auth token` for that host. Hosts that are
+/// neither github.com nor an https Enterprise host in `forgeHosts` get none.
+async fn github_token(remote: &ForgeRemote, state: &AppState) -> Option<String> {
+    let overrides = state.app_settings.lock().await.forge_hosts.clone();
+    let (use_saved, env_vars) = token_sources(host_trust(remote, &overrides))?;
+    let configured = if use_saved {
+        read_secret(state, SecretKind::GitHub).await
+    } else {
+        None
+    };
+    let found = configured
+        .into_iter()
+        .chain(env_vars.iter().filter_map(|name| std::env::var(name).ok()))
+        .map(|token| token.trim().to_string())
+        .find(|token| !token.is_empty());
+    if found.is_some() {
+        return found;
+    }
+    let host = remote.host.as_str();
+    if let Some(token) = state.github_api.lock().await.cli_token(host) {
+        return Some(token);
+    }
+    let output = tokio::process::Command::new("gh")
+        .args(["auth", "token", "--hostname", host])
+        .output()
+        .await
+        .ok()?;
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || token.is_empty() {
+        return None;
+    }
+    state
+        .github_api
+        .lock()
+        .await
+        .set_cli_token(host, token.clone());
+    Some(token)
+}
+
+fn search_term(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{}\"", value.replace('"', ""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Builds a search query for `kind` (`issue` or `pr`) in `repo`, newest
+/// activity first.
+pub(crate) fn github_search_query(repo: &str, kind: &str, query: &ForgeListQuery) -> String {
+    let mut terms = vec![format!("repo:{repo}"), format!("is:{kind}")];
+    match list_state(query).as_str() {
+        "all" => {}
+        state => terms.push(format!("is:{state}")),
+    }
+    for label in query.labels.iter().map(|label| label.trim()) {
+        if !label.is_empty() {
+            terms.push(format!("label:{}", search_term(label)));
+        }
+    }
+    if let Some(author) = filter_value(&query.author) {
+        terms.push(format!("author:{}", search_term(author)));
+    }
+    if let Some(assignee) = filter_value(&query.assignee) {
+        terms.push(format!("assignee:{}", search_term(assignee)));
+    }
+    if let Some(search) = filter_value(&query.search) {
+        terms.push(search.to_string());
+    }
+    terms.push("sort:updated-desc".to_string());
+    terms.join(" ")
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DiffLineIndex {
+    /// New-file line numbers (added or context) mapped to their text.
+    pub(crate) right: HashMap<u64, String>,
+    /// Old-file line numbers (removed or context) mapped to their text.
+    pub(crate) left: HashMap<u64, String>,
+}
+
+/// Indexes the lines of a unified diff that GitHub accepts review comments on.
+pub(crate) fn index_diff_lines(diff: &str) -> DiffLineIndex {
+    let mut index = DiffLineIndex::default();
+    let mut old_line = 0u64;
+    let mut new_line = 0u64;
+    let mut in_hunk = false;
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("@@ ") {
+            let mut ranges = header.split_whitespace();
+            let start = |range: Option<&str>, prefix: char| {
+                range
+                    .and_then(|value| value.strip_prefix(prefix))
+                    .and_then(|value| value.split(',').next())
+                    .and_then(|value| value.parse::<u64>().ok())
+            };
+            let old_start = start(ranges.next(), '-');
+            let new_start = start(ranges.next(), '+');
+            in_hunk = old_start.is_some() && new_start.is_some();
+            old_line = old_start.unwrap_or(0);
+            new_line = new_start.unwrap_or(0);
+            continue;
+        }
+        if !in_hunk {
+            continue;
+        }
+        if let Some(text) = line.strip_prefix('+') {
+            index.right.insert(new_line, text.to_string());
+            new_line += 1;
+        } else if let Some(text) = line.strip_prefix('-') {
+            index.left.insert(old_line, text.to_string());
+            old_line += 1;
+        } else if let Some(text) = line.strip_prefix(' ') {
+            index.right.insert(new_line, text.to_string());
+            index.left.insert(old_line, text.to_string());
+            old_line += 1;
+            new_line += 1;
+        } else if line.starts_with("diff --git") {
+            in_hunk = false;
+        }
+    }
+    index
+}
+
+const REVIEW_EVENTS: &[&str] = &["COMMENT", "APPROVE", "REQUEST_CHANGES"];
+
+/// Checks a review against the pull request diff and attaches the commented
+/// line to each inline comment so it can be shown before posting.
+pub(crate) fn build_review_preview(
+    draft: &GitHubReviewDraft,
+    diffs: &[GitHubPullRequestDiff],
+) -> GitHubReviewPreview {
+    let mut errors = Vec::new();
+    let event = draft.event.trim().to_ascii_uppercase();
+    if !REVIEW_EVENTS.contains(&event.as_str()) {
+        errors.push(format!("Unknown review event: {}.", draft.event));
+    }
+    let body = draft.body.trim().to_string();
+    if event != "APPROVE" && body.is_empty() && draft.comments.is_empty() {
+        errors.push("Add a summary or at least one inline comment.".to_string());
+    }
+    if event == "REQUEST_CHANGES" && body.is_empty() {
+        errors.push("Requesting changes needs a summary.".to_string());
+    }
+
+    let indexes: HashMap<&str, DiffLineIndex> = diffs
+        .iter()
+        .map(|diff| (diff.path.as_str(), index_diff_lines(&diff.diff)))
+        .collect();
+    let comments = draft
+        .comments
+        .iter()
+        .map(|comment| {
+            let mut comment = comment.clone();
+            comment.side = comment.side.trim().to_ascii_uppercase();
+            let lines = indexes.get(comment.path.as_str()).map(|index| {
+                if comment.side == "LEFT" {
+                    &index.left
+                } else {
+                    &index.right
+                }
+            });
+            let excerpt = lines.and_then(|lines| lines.get(&comment.line).cloned());
+            let error = if comment.body.trim().is_empty() {
+                Some("Comment is empty.".to_string())
+            } else if comment.side != "LEFT" && comment.side != "RIGHT" {
+                Some(format!("Unknown side: {}.", comment.side))
+            } else if lines.is_none() {
+                Some(format!(
+                    "{} is not part of this pull request.",
+                    comment.path
+                ))
+            } else if excerpt.is_none() {
+                Some(format!(
+                    "Line {} is outside the changed hunks of {}.",
+                    comment.line, comment.path
+                ))
+            } else if comment.start_line.is_some_and(|start| {
+                start > comment.line || lines.is_some_and(|lines| !lines.contains_key(&start))
+            }) {
+                Some("The comment range must start inside the same hunk.".to_string())
+            } else {
+                None
+            };
+            GitHubReviewCommentPreview {
+                comment,
+                excerpt,
+                error,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let valid = errors.is_empty() && comments.iter().all(|comment| comment.error.is_none());
+    let mut preview = GitHubReviewPreview {
+        event,
+        body,
+        comments,
+        errors,
+        digest: None,
+    };
+    if valid {
+        preview.digest = Some(review_digest(&review_request_body(&preview)));
+    }
+    preview
+}
+
+/// The JSON body for `POST /repos/{repo}/pulls/{number}/reviews`.
+pub(crate) fn review_request_body(preview: &GitHubReviewPreview) -> Value {
+    let comments = preview
+        .comments
+        .iter()
+        .map(|preview| {
+            let comment = &preview.comment;
+            let mut value = json!({
+                "path": comment.path,
+                "line": comment.line,
+                "side": comment.side,
+                "body": comment.body,
+            });
+            if let Some(start_line) = comment.start_line.filter(|start| *start < comment.line) {
+                value["start_line"] = json!(start_line);
+                value["start_side"] = json!(comment.side);
+            }
+            value
+        })
+        .collect::<Vec<_>>();
+    let mut body = json!({ "event": preview.event, "comments": comments });
+    if !preview.body.is_empty() {
+        body["body"] = json!(preview.body);
+    }
+    body
+}
+
+/// Splits an agent's pull request draft into its title (the first non-empty
+/// line) and body. Tolerates a surrounding code fence, a Markdown heading or
+/// bold on the title and `Title:`/`Body:` labels.
+pub(crate) fn parse_pull_request_draft(reply: &str) -> Option<(String, String)> {
+    let mut lines: Vec<&str> = reply.trim().lines().collect();
+    if lines.len() >= 2
+        && lines[0].trim_start().starts_with("```")
+        && lines[lines.len() - 1].trim() == "```"
+    {
+        lines = lines[1..lines.len() - 1].to_vec();
+    }
+    let start = lines.iter().position(|line| !line.trim().is_empty())?;
+    let title = draft_line(lines[start], "title:");
+    if title.is_empty() {
+        return None;
+    }
+    let mut rest = &lines[start + 1..];
+    while rest.first().is_some_and(|line| line.trim().is_empty()) {
+        rest = &rest[1..];
+    }
+    if rest
+        .first()
+        .is_some_and(|line| draft_line(line, "body:").is_empty())
+    {
+        rest = &rest[1..];
+    }
+    Some((title, rest.join("\n").trim().to_string()))
+}
+
+/// `line` without heading or bold markup and without a leading `label`.
+fn draft_line(line: &str, label: &str) -> String {
+    let line = line
+        .trim()
+        .trim_start_matches('#')
+        .trim()
+        .trim_matches('*')
+        .trim();
+    let line = match line.get(..label.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(label) => &line[label.len()..],
+        _ => line,
+    };
+    line.trim().trim_matches('*').trim().to_string()
+}
+
+pub(crate) fn review_digest(body: &Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(body.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+const CHECK_FAILURE_CONCLUSIONS: &[&str] =
+    &["failure", "timed_out", "action_required", "startup_failure"];
+
+pub(crate) fn check_run_failed(run: &GitHubCheckRun) -> bool {
+    run.conclusion
+        .as_deref()
+        .is_some_and(|conclusion| CHECK_FAILURE_CONCLUSIONS.contains(&conclusion))
+}
+
+pub(crate) fn commit_status_failed(status: &GitHubCommitStatus) -> bool {
+    matches!(status.state.as_str(), "failure" | "error")
+}
+
+/// Rolls check runs and commit statuses up into one state, failures first.
+pub(crate) fn combined_check_state(
+    check_runs: &[GitHubCheckRun],
+    statuses: &[GitHubCommitStatus],
+) -> &'static str {
+    if check_runs.iter().any(check_run_failed) || statuses.iter().any(commit_status_failed) {
+        return "failure";
+    }
+    let pending = check_runs.iter().any(|run| run.status != "completed")
+        || statuses.iter().any(|status| status.state == "pending");
+    if pending {
+        "pending"
+    } else if check_runs.is_empty() && statuses.is_empty() {
+        "neutral"
+    } else {
+        "success"
+    }
+}
+
+/// Keeps the interesting part of a GitHub Actions job log: lines flagged as
+/// errors plus the tail of the output, with runner timestamps stripped.
+pub(crate) fn failing_log_excerpt(log: &str, tail_lines: usize) -> String {
+    let lines = log
+        .lines()
+        .map(|line| {
+            let mut parts = line.splitn(2, ' ');
+            let first = parts.next().unwrap_or("");
+            match parts.next() {
+                Some(rest) if first.len() >= 20 && first.ends_with('Z') && first.contains('T') => {
+                    rest
+                }
+                _ => line,
+            }
+        })
+        .collect::<Vec<_>>();
+    let tail_start = lines.len().saturating_sub(tail_lines);
+    let mut excerpt = Vec::new();
+    for line in &lines[..tail_start] {
+        if line.contains("##[error]") {
+            excerpt.push(*line);
+        }
+    }
+    if !excerpt.is_empty() && tail_start > 0 {
+        excerpt.push("...");
+    }
+    excerpt.extend_from_slice(&lines[tail_start..]);
+    excerpt.join("\n")
+}
+
+/// GitHub over its REST and GraphQL APIs, falling back to the `gh` CLI when
+/// no token is available or the API rejects it.
+pub(crate) struct GitHubForge {
+    repo_root: PathBuf,
+    remote: ForgeRemote,
+    api: Option<GitHubClient>,
+}
+
+impl GitHubForge {
+    pub(crate) async fn with_remote(
+        repo_root: PathBuf,
+        remote: ForgeRemote,
+        state: &AppState,
+    ) -> Self {
+        let api = match github_token(&remote, state).await {
+            Some(token) => GitHubClient::new(&remote, token, state.github_api.clone()).ok(),
+            None => None,
+        };
+        Self {
+            repo_root,
+            remote,
+            api,
+        }
+    }
+
+    pub(crate) async fn connect(repo_root: &Path, state: &AppState) -> Result<Self, String> {
+        let overrides = state.app_settings.lock().await.forge_hosts.clone();
+        let remote = forge_remote_from_path(repo_root, &overrides)
+            .ok()
+            .filter(|remote| remote.kind == ForgeKind::GitHub)
+            .ok_or("Remote is not a GitHub repository.")?;
+        Ok(Self::with_remote(repo_root.to_path_buf(), remote, state).await)
+    }
+
+    /// The `--repo` argument, host-qualified for GitHub Enterprise.
+    fn gh_repo(&self) -> String {
+        if self.remote.host == "github.com" {
+            self.remote.repo.clone()
+        } else {
+            format!("{}/{}", self.remote.host, self.remote.repo)
+        }
+    }
+
+    async fn gh_api(&self, endpoint: &str, jq_filter: &str) -> Result<Vec<u8>, String> {
+        self.gh_api_with(vec![endpoint, "--jq", jq_filter], None)
+            .await
+    }
+
+    /// Runs `gh api` with `args`, on this remote's host.
+    async fn gh_api_with(&self, args: Vec<&str>, input: Option<&[u8]>) -> Result<Vec<u8>, String> {
+        let mut args = [vec!["api"], args].concat();
+        if self.remote.host != "github.com" {
+            args.extend(["--hostname", self.remote.host.as_str()]);
+        }
+        run_gh_command_with_input(&self.repo_root, &args, input).await
+    }
+
+    async fn gh_search<T: DeserializeOwned>(
+        &self,
+        graphql: &str,
+        search: &str,
+        query: &ForgeListQuery,
+    ) -> Result<SearchPage<T>, String> {
+        let query_arg = format!("query={graphql}");
+        let search_arg = format!("q={search}");
+        let first_arg = format!("first={}", page_size(query));
+        let mut args = vec![
+            "api",
+            "graphql",
+            "-f",
+            &query_arg,
+            "-f",
+            &search_arg,
+            "-F",
+            &first_arg,
+        ];
+        let after_arg = filter_value(&query.cursor).map(|cursor| format!("after={cursor}"));
+        if let Some(after_arg) = &after_arg {
+            args.extend(["-f", after_arg.as_str()]);
+        }
+        if self.remote.host != "github.com" {
+            args.extend(["--hostname", self.remote.host.as_str()]);
+        }
+        args.extend(["--jq", ".data.search"]);
+        let output = run_gh_command(&self.repo_root, &args).await?;
+        serde_json::from_slice(&output).map_err(|e| e.to_string())
+    }
+
+    /// Runs one page of a GraphQL issue search; returns the matching nodes,
+    /// the total count and the cursor for the next page.
+    async fn search<T: DeserializeOwned>(
+        &self,
+        graphql: &str,
+        kind: &str,
+        query: &ForgeListQuery,
+    ) -> Result<(Vec<T>, usize, Option<String>), String> {
+        let search = github_search_query(&self.remote.repo, kind, query);
+        let api_page = match &self.api {
+            Some(api) => {
+                let variables = json!({
+                    "q": search,
+                    "first": page_size(query),
+                    "after": filter_value(&query.cursor),
+                });
+                api_result(
+                    api.graphql::<SearchData<T>>(graphql, variables)
+                        .await
+                        .map(|data| data.search),
+                )
+            }
+            None => None,
+        };
+        let page = match api_page {
+            Some(page) => page?,
+            None => self.gh_search(graphql, &search, query).await?,
+        };
+        let next_cursor = page
+            .page_info
+            .end_cursor
+            .filter(|_| page.page_info.has_next_page);
+        Ok((page.nodes, page.issue_count, next_cursor))
+    }
+
+    async fn issues(&self, query: &ForgeListQuery) -> Result<GitHubIssuesResponse, String> {
+        let (issues, total, next_cursor) = self
+            .search::<GitHubIssue>(ISSUE_SEARCH, "issue", query)
+            .await?;
+        Ok(GitHubIssuesResponse {
+            total,
+            issues,
+            next_cursor,
+        })
+    }
+
+    async fn pull_requests(
+        &self,
+        query: &ForgeListQuery,
+    ) -> Result<GitHubPullRequestsResponse, String> {
+        let (pull_requests, total, next_cursor) = self
+            .search::<GitHubPullRequest>(PULL_REQUEST_SEARCH, "pr", query)
+            .await?;
+        Ok(GitHubPullRequestsResponse {
+            total,
+            pull_requests,
+            next_cursor,
+        })
+    }
+
+    async fn rest_comments(
+        &self,
+        api: &GitHubClient,
+        number: u64,
+        per_page: u32,
+    ) -> Result<Vec<RestComment>, GitHubApiError> {
+        let path = format!(
+            "/repos/{}/issues/{number}/comments?per_page={per_page}",
+            self.remote.repo
+        );
+        api.get_json(&path).await
+    }
+
+    async fn comments(&self, number: u64) -> Result<Vec<GitHubPullRequestComment>, String> {
+        if let Some(api) = &self.api {
+            let comments = self.rest_comments(api, number, 30).await.map(|comments| {
+                comments
+                    .into_iter()
+                    .map(|comment| GitHubPullRequestComment {
+                        id: comment.id,
+                        body: comment.body.unwrap_or_default(),
+                        created_at: comment.created_at,
+                        url: comment.html_url,
+                        author: author(comment.user),
+                    })
+                    .collect()
+            });
+            if let Some(result) = api_result(comments) {
+                return result;
+            }
+        }
+        let endpoint = format!(
+            "/repos/{}/issues/{number}/comments?per_page=30",
+            self.remote.repo
+        );
+        let jq_filter = r#"[.[] | {id, body, createdAt: .created_at, url: .html_url, author: (if .user then {login: .user.login} else null end)}]"#;
+        let output = self.gh_api(&endpoint, jq_filter).await?;
+        serde_json::from_slice(&output).map_err(|e| e.to_string())
+    }
+
+    async fn diff(&self, number: u64) -> Result<Vec<GitHubPullRequestDiff>, String> {
+        if let Some(api) = &self.api {
+            let path = format!("/repos/{}/pulls/{number}", self.remote.repo);
+            let diff = api
+                .get(&path, DIFF_MEDIA_TYPE)
+                .await
+                .map(|body| parse_pr_diff(&String::from_utf8_lossy(&body)));
+            if let Some(result) = api_result(diff) {
+                return result;
+            }
+        }
+        fetch_pull_request_diff(&self.repo_root, &self.gh_repo(), number).await
+    }
+
+    async fn api_issue(
+        &self,
+        api: &GitHubClient,
+        number: u64,
+    ) -> Result<GitHubIssueDetail, GitHubApiError> {
+        let path = format!("/repos/{}/issues/{number}", self.remote.repo);
+        let issue: RestIssue = api.get_json(&path).await?;
+        let comments = self.rest_comments(api, number, 100).await?;
+        Ok(GitHubIssueDetail {
+            number: issue.number,
+            title: issue.title,
+            url: issue.html_url,
+            // Match the `gh` CLI, which reports OPEN and CLOSED.
+            state: issue.state.to_uppercase(),
+            body: issue.body.unwrap_or_default(),
+            labels: issue.labels,
+            author: author(issue.user),
+            comments: comments
+                .into_iter()
+                .map(|comment| GitHubIssueComment {
+                    author: author(comment.user),
+                    body: comment.body.unwrap_or_default(),
+                    created_at: comment.created_at,
+                })
+                .collect(),
+            updated_at: issue.updated_at,
+        })
+    }
+
+    /// An issue with its description, labels and comments.
+    pub(crate) async fn issue(&self, number: u64) -> Result<GitHubIssueDetail, String> {
+        if let Some(api) = &self.api {
+            if let Some(result) = api_result(self.api_issue(api, number).await) {
+                return result;
+            }
+        }
+        let output = run_gh_command(
+            &self.repo_root,
+            &[
+                "issue",
+                "view",
+                &number.to_string(),
+                "--repo",
+                &self.gh_repo(),
+                "--json",
+                "number,title,url,state,body,labels,author,comments,updatedAt",
+            ],
+        )
+        .await?;
+        serde_json::from_slice(&output).map_err(|e| e.to_string())
+    }
+
+    pub(crate) async fn pull_request(&self, number: u64) -> Result<GitHubPullRequest, String> {
+        if let Some(api) = &self.api {
+            let path = format!("/repos/{}/pulls/{number}", self.remote.repo);
+            let pull_request = api
+                .get_json::<RestPullRequest>(&path)
+                .await
+                .map(GitHubPullRequest::from);
+            if let Some(result) = api_result(pull_request) {
+                return result;
+            }
+        }
+        let output = run_gh_command(
+            &self.repo_root,
+            &[
+                "pr",
+                "view",
+                &number.to_string(),
+                "--repo",
+                &self.gh_repo(),
+                "--json",
+                PULL_REQUEST_FIELDS,
+            ],
+        )
+        .await?;
+        serde_json::from_slice(&output).map_err(|e| e.to_string())
+    }
+
+    /// The commit a pull request's head branch points at.
+    pub(crate) async fn pull_request_head(&self, number: u64) -> Result<String, String> {
+        if let Some(api) = &self.api {
+            let path = format!("/repos/{}/pulls/{number}", self.remote.repo);
+            let head = api
+                .get_json::<RestPullRequest>(&path)
+                .await
+                .map(|pull_request| pull_request.head.sha);
+            if let Some(result) = api_result(head) {
+                return result;
+            }
+        }
+        let output = run_gh_command(
+            &self.repo_root,
+            &[
+                "pr",
+                "view",
+                &number.to_string(),
+                "--repo",
+                &self.gh_repo(),
+                "--json",
+                "headRefOid",
+                "--jq",
+                ".headRefOid",
+            ],
+        )
+        .await?;
+        Ok(String::from_utf8_lossy(&output).trim().to_string())
+    }
+
+    async fn api_checks(
+        &self,
+        api: &GitHubClient,
+        sha: &str,
+    ) -> Result<(Vec<GitHubCheckRun>, Vec<GitHubCommitStatus>), GitHubApiError> {
+        let repo = &self.remote.repo;
+        let path = format!("/repos/{repo}/commits/{sha}/check-runs?per_page=100");
+        let runs: RestCheckRuns = api.get_json(&path).await?;
+        let path = format!("/repos/{repo}/commits/{sha}/status");
+        let status: RestCombinedStatus = api.get_json(&path).await?;
+        Ok((
+            runs.check_runs
+                .into_iter()
+                .map(GitHubCheckRun::from)
+                .collect(),
+            status
+                .statuses
+                .into_iter()
+                .map(GitHubCommitStatus::from)
+                .collect(),
+        ))
+    }
+
+    /// Check runs and commit statuses reported for `sha`.
+    pub(crate) async fn checks(
+        &self,
+        sha: &str,
+    ) -> Result<(Vec<GitHubCheckRun>, Vec<GitHubCommitStatus>), String> {
+        if let Some(api) = &self.api {
+            if let Some(result) = api_result(self.api_checks(api, sha).await) {
+                return result;
+            }
+        }
+        let repo = &self.remote.repo;
+        let endpoint = format!("/repos/{repo}/commits/{sha}/check-runs?per_page=100");
+        let jq_filter = r#"[.check_runs[] | {id, name, status, conclusion, url: .html_url, startedAt: .started_at, completedAt: .completed_at, appSlug: .app.slug}]"#;
+        let output = self.gh_api(&endpoint, jq_filter).await?;
+        let check_runs = serde_json::from_slice(&output).map_err(|e| e.to_string())?;
+        let endpoint = format!("/repos/{repo}/commits/{sha}/status");
+        let jq_filter = r#"[.statuses[] | {context, state, description, url: .target_url, updatedAt: .updated_at}]"#;
+        let output = self.gh_api(&endpoint, jq_filter).await?;
+        let statuses = serde_json::from_slice(&output).map_err(|e| e.to_string())?;
+        Ok((check_runs, statuses))
+    }
+
+    /// The log of a GitHub Actions job, or `None` when it is unavailable.
+    pub(crate) async fn job_log(&self, job_id: u64) -> Option<String> {
+        let path = format!("/repos/{}/actions/jobs/{job_id}/logs", self.remote.repo);
+        if let Some(api) = &self.api {
+            if let Some(result) = api_result(api.get_uncached(&path, JSON_MEDIA_TYPE).await) {
+                return result
+                    .ok()
+                    .map(|log| String::from_utf8_lossy(&log).to_string());
+            }
+        }
+        let output = self.gh_api_with(vec![&path], None).await.ok()?;
+        Some(String::from_utf8_lossy(&output).to_string())
+    }
+
+    async fn request_reviewers(
+        &self,
+        api: &GitHubClient,
+        number: u64,
+        reviewers: &[String],
+    ) -> Result<(), GitHubApiError> {
+        let (users, teams) = split_reviewers(reviewers);
+        let path = format!(
+            "/repos/{}/pulls/{number}/requested_reviewers",
+            self.remote.repo
+        );
+        let body = json!({ "reviewers": users, "team_reviewers": teams });
+        api.post_json::<Value>(&path, &body).await.map(|_| ())
+    }
+
+    /// Opens a pull request from `head` into `base` and requests reviews
+    /// from `reviewers` (logins or `org/team`).
+    pub(crate) async fn create_pull_request(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+        reviewers: &[String],
+    ) -> Result<GitHubPullRequest, String> {
+        if let Some(api) = &self.api {
+            let request = json!({
+                "title": title,
+                "body": body,
+                "head": head,
+                "base": base,
+                "draft": draft,
+            });
+            let path = format!("/repos/{}/pulls", self.remote.repo);
+            let created = api
+                .post_json::<RestPullRequest>(&path, &request)
+                .await
+                .map(GitHubPullRequest::from);
+            if let Some(result) = api_write_result(created) {
+                let pull_request = result?;
+                if !reviewers.is_empty() {
+                    self.request_reviewers(api, pull_request.number, reviewers)
+                        .await
+                        .map_err(|error| {
+                            format!(
+                                "Created {}, but requesting reviewers failed: {error}",
+                                pull_request.url
+                            )
+                        })?;
+                }
+                return Ok(pull_request);
+            }
+        }
+        let repo = self.gh_repo();
+        let mut args = vec![
+            "pr", "create", "--repo", &repo, "--head", head, "--base", base, "--title", title,
+            "--body", body,
+        ];
+        if draft {
+            args.push("--draft");
+        }
+        let reviewers = reviewers.join(",");
+        if !reviewers.is_empty() {
+            args.push("--reviewer");
+            args.push(&reviewers);
+        }
+        let output = run_gh_command(&self.repo_root, &args).await?;
+        // `gh pr create` prints the URL of the new pull request as its last line.
+        let stdout = String::from_utf8_lossy(&output);
+        let url = stdout
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| line.starts_with("http"))
+            .ok_or("GitHub CLI did not return a pull request URL.")?;
+        let output = run_gh_command(
+            &self.repo_root,
+            &[
+                "pr",
+                "view",
+                url,
+                "--repo",
+                &repo,
+                "--json",
+                PULL_REQUEST_FIELDS,
+            ],
+        )
+        .await?;
+        serde_json::from_slice(&output).map_err(|e| e.to_string())
+    }
+
+    /// Posts a review built by `review_request_body`.
+    pub(crate) async fn submit_review(
+        &self,
+        number: u64,
+        review: &Value,
+    ) -> Result<GitHubPullRequestReview, String> {
+        let path = format!("/repos/{}/pulls/{number}/reviews", self.remote.repo);
+        if let Some(api) = &self.api {
+            let posted = api
+                .post_json::<RestReview>(&path, review)
+                .await
+                .map(|review| GitHubPullRequestReview {
+                    id: review.id,
+                    state: review.state,
+                    url: review.html_url,
+                    submitted_at: review.submitted_at,
+                });
+            if let Some(result) = api_write_result(posted) {
+                return result;
+            }
+        }
+        let jq_filter = r#"{id, state, url: .html_url, submittedAt: .submitted_at}"#;
+        let body = review.to_string();
+        let output = self
+            .gh_api_with(
+                vec!["--method", "POST", &path, "--input", "-", "--jq", jq_filter],
+                Some(body.as_bytes()),
+            )
+            .await?;
+        serde_json::from_slice(&output).map_err(|e| e.to_string())
+    }
+}
+
+impl Forge for GitHubForge {
+    fn remote(&self) -> &ForgeRemote {
+        &self.remote
+    }
+
+    fn list_issues<'a>(
+        &'a self,
+        query: &'a ForgeListQuery,
+    ) -> ForgeFuture<'a, GitHubIssuesResponse> {
+        Box::pin(self.issues(query))
+    }
+
+    fn list_merge_requests<'a>(
+        &'a self,
+        query: &'a ForgeListQuery,
+    ) -> ForgeFuture<'a, GitHubPullRequestsResponse> {
+        Box::pin(self.pull_requests(query))
+    }
+
+    fn merge_request_diff(&self, number: u64) -> ForgeFuture<'_, Vec<GitHubPullRequestDiff>> {
+        Box::pin(self.diff(number))
+    }
+
+    fn merge_request_comments(
+        &self,
+        number: u64,
+    ) -> ForgeFuture<'_, Vec<GitHubPullRequestComment>> {
+        Box::pin(self.comments(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use mockito::Server;
+    use tokio::sync::Mutex;
+
+    use super::{
+        build_review_preview, combined_check_state, failing_log_excerpt, github_search_query,
+        index_diff_lines, parse_pull_request_draft, token_sources, GitHubForge,
+    };
+    use crate::forge::github_api::{GitHubApiState, GitHubClient};
+    use crate::forge::test_support::block_on;
+    use crate::forge::{host_trust, parse_forge_remote};
+    use crate::types::{
+        ForgeKind, ForgeListQuery, GitHubCheckRun, GitHubCommitStatus, GitHubPullRequestDiff,
+        GitHubReviewComment, GitHubReviewDraft,
+    };
+
+    fn forge(server: &Server) -> GitHubForge {
+        let url = format!("{}/me/app.git", server.url());
+        let overrides = BTreeMap::from([(server.host_with_port(), ForgeKind::GitHub)]);
+        let mut remote = parse_forge_remote(&url, &overrides).unwrap();
+        remote.api_base = server.url();
+        let state = Arc::new(Mutex::new(GitHubApiState::default()));
+        let api = GitHubClient::new(&remote, "secret".to_string(), state).unwrap();
+        GitHubForge {
+            repo_root: PathBuf::from("."),
+            remote,
+            api: Some(api),
+        }
+    }
+
+    #[test]
+    fn github_search_query_applies_filters() {
+        assert_eq!(
+            github_search_query("me/app", "issue", &ForgeListQuery::default()),
+            "repo:me/app is:issue is:open sort:updated-desc"
+        );
+        let query = ForgeListQuery {
+            state: Some("All".to_string()),
+            labels: vec!["good first issue".to_string(), "bug".to_string()],
+            author: Some("sam".to_string()),
+            assignee: Some(" ".to_string()),
+            search: Some("crash on start".to_string()),
+            ..ForgeListQuery::default()
+        };
+        assert_eq!(
+            github_search_query("me/app", "pr", &query),
+            "repo:me/app is:pr label:\"good first issue\" label:bug author:sam crash on start sort:updated-desc"
+        );
+    }
+
+    #[test]
+    fn github_tokens_are_scoped_to_their_host() {
+        let overrides = BTreeMap::from([
+            ("ghe.corp.example".to_string(), ForgeKind::GitHub),
+            ("ghe.plain.example".to_string(), ForgeKind::GitHub),
+        ]);
+        let sources = |url: &str| {
+            let remote = parse_forge_remote(url, &overrides).unwrap();
+            token_sources(host_trust(&remote, &overrides)).map(|(saved, _)| saved)
+        };
+        assert_eq!(sources("git@github.com:me/app.git"), Some(true));
+        assert_eq!(sources("https://ghe.corp.example/me/app"), Some(false));
+        assert_eq!(sources("http://ghe.plain.example/me/app"), None);
+        assert_eq!(sources("https://github.evil.example/me/app"), None);
+    }
+
+    #[test]
+    fn issue_detail_comes_from_the_rest_api() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/repos/me/app/issues/7")
+            .with_body(
+                r#"{"number": 7, "title": "Crash", "html_url": "https://github.com/me/app/issues/7",
+                "state": "open", "body": null, "labels": [{"name": "bug", "color": "d73a4a"}],
+                "user": {"login": "sam"}, "updated_at": "2024-05-01T00:00:00Z"}"#,
+            )
+            .create();
+        server
+            .mock("GET", "/repos/me/app/issues/7/comments?per_page=100")
+            .with_body(
+                r#"[{"id": 1, "body": "Same here", "created_at": "2024-05-02T00:00:00Z",
+                "html_url": "https://github.com/me/app/issues/7#issuecomment-1", "user": null}]"#,
+            )
+            .create();
+        let forge = forge(&server);
+
+        let issue = block_on(forge.issue(7)).unwrap();
+        assert_eq!(issue.state, "OPEN");
+        assert_eq!(issue.body, "");
+        assert_eq!(issue.labels[0].name, "bug");
+        assert_eq!(issue.author.unwrap().login, "sam");
+        assert_eq!(issue.comments.len(), 1);
+        assert!(issue.comments[0].author.is_none());
+    }
+
+    #[test]
+    fn checks_come_from_the_rest_api() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/repos/me/app/pulls/3")
+            .with_body(
+                r#"{"number": 3, "title": "Fix", "html_url": "https://github.com/me/app/pull/3",
+                "updated_at": "2024-05-01T00:00:00Z", "created_at": "2024-05-01T00:00:00Z",
+                "head": {"ref": "fix", "sha": "abc123"}, "base": {"ref": "main", "sha": "def"}}"#,
+            )
+            .create();
+        server
+            .mock("GET", "/repos/me/app/commits/abc123/check-runs?per_page=100")
+            .with_body(
+                r#"{"total_count": 1, "check_runs": [{"id": 9, "name": "test",
+                "status": "completed", "conclusion": "failure",
+                "html_url": "https://github.com/me/app/runs/9", "app": {"slug": "github-actions"}}]}"#,
+            )
+            .create();
+        server
+            .mock("GET", "/repos/me/app/commits/abc123/status")
+            .with_body(
+                r#"{"state": "pending", "statuses": [{"context": "ci/lint", "state": "pending",
+                "description": null, "target_url": "https://ci.example/1"}]}"#,
+            )
+            .create();
+        let forge = forge(&server);
+
+        let sha = block_on(forge.pull_request_head(3)).unwrap();
+        assert_eq!(sha, "abc123");
+        let (check_runs, statuses) = block_on(forge.checks(&sha)).unwrap();
+        assert_eq!(check_runs[0].conclusion.as_deref(), Some("failure"));
+        assert_eq!(check_runs[0].app_slug.as_deref(), Some("github-actions"));
+        assert_eq!(statuses[0].url.as_deref(), Some("https://ci.example/1"));
+    }
+
+    #[test]
+    fn pull_requests_and_reviews_are_posted_to_the_rest_api() {
+        let mut server = Server::new();
+        let created = server
+            .mock("POST", "/repos/me/app/pulls")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"head": "fix", "base": "main", "title": "Fix", "draft": true}"#.to_string(),
+            ))
+            .with_status(201)
+            .with_body(
+                r#"{"number": 4, "title": "Fix", "html_url": "https://github.com/me/app/pull/4",
+                "updated_at": "2024-05-01T00:00:00Z", "created_at": "2024-05-01T00:00:00Z",
+                "head": {"ref": "fix"}, "base": {"ref": "main"}, "draft": true}"#,
+            )
+            .create();
+        let reviewers = server
+            .mock("POST", "/repos/me/app/pulls/4/requested_reviewers")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "reviewers": ["sam"],
+                "team_reviewers": ["core"],
+            })))
+            .with_status(201)
+            .with_body("{}")
+            .create();
+        let review = server
+            .mock("POST", "/repos/me/app/pulls/4/reviews")
+            .with_body(
+                r#"{"id": 11, "state": "COMMENTED",
+                "html_url": "https://github.com/me/app/pull/4#pullrequestreview-11"}"#,
+            )
+            .create();
+        let forge = forge(&server);
+
+        let pull_request = block_on(forge.create_pull_request(
+            "fix",
+            "main",
+            "Fix",
+            "",
+            true,
+            &["sam".to_string(), "me/core".to_string()],
+        ))
+        .unwrap();
+        assert_eq!(pull_request.number, 4);
+        assert!(pull_request.is_draft);
+        let posted = block_on(forge.submit_review(
+            4,
+            &serde_json::json!({"event": "COMMENT", "body": "Looks good"}),
+        ))
+        .unwrap();
+        assert_eq!(posted.id, 11);
+        assert_eq!(posted.state, "COMMENTED");
+
+        created.assert();
+        reviewers.assert();
+        review.assert();
+    }
+
+    const PR_DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -10,3 +10,4 @@ fn main() {\n     let a = 1;\n-    let b = 2;\n+    let b = 3;\n+    let c = 4;\n     run(a, b);\n";
+
+    #[test]
+    fn index_diff_lines_tracks_both_sides() {
+        let index = index_diff_lines(PR_DIFF);
+        assert_eq!(
+            index.right.get(&10).map(String::as_str),
+            Some("    let a = 1;")
+        );
+        assert_eq!(
+            index.right.get(&12).map(String::as_str),
+            Some("    let c = 4;")
+        );
+        assert_eq!(
+            index.right.get(&13).map(String::as_str),
+            Some("    run(a, b);")
+        );
+        assert_eq!(
+            index.left.get(&11).map(String::as_str),
+            Some("    let b = 2;")
+        );
+        assert!(!index.right.contains_key(&14));
+        assert!(!index.left.contains_key(&13));
+    }
+
+    #[test]
+    fn build_review_preview_validates_inline_positions() {
+        let diffs = vec![GitHubPullRequestDiff {
+            path: "src/lib.rs".to_string(),
+            status: "M".to_string(),
+            diff: PR_DIFF.to_string(),
+        }];
+        let comment = |line: u64, side: &str| GitHubReviewComment {
+            path: "src/lib.rs".to_string(),
+            line,
+            side: side.to_string(),
+            start_line: None,
+            body: "nit".to_string(),
+        };
+        let mut draft = GitHubReviewDraft {
+            event: "comment".to_string(),
+            body: String::new(),
+            comments: vec![comment(12, "right"), comment(11, "LEFT")],
+        };
+        let preview = build_review_preview(&draft, &diffs);
+        assert_eq!(preview.event, "COMMENT");
+        assert!(preview.errors.is_empty());
+        assert_eq!(
+            preview.comments[0].excerpt.as_deref(),
+            Some("    let c = 4;")
+        );
+        assert!(preview.digest.is_some());
+
+        draft.comments.push(comment(40, "RIGHT"));
+        let preview = build_review_preview(&draft, &diffs);
+        assert!(preview.comments[2].error.is_some());
+        assert!(preview.digest.is_none());
+
+        let request_changes = GitHubReviewDraft {
+            event: "REQUEST_CHANGES".to_string(),
+            body: String::new(),
+            comments: vec![comment(12, "RIGHT")],
+        };
+        assert!(!build_review_preview(&request_changes, &diffs)
+            .errors
+            .is_empty());
+    }
+
+    #[test]
+    fn combined_check_state_prefers_failures_then_pending() {
+        let run = |status: &str, conclusion: Option<&str>| GitHubCheckRun {
+            id: 1,
+            name: "build".to_string(),
+            status: status.to_string(),
+            conclusion: conclusion.map(|value| value.to_string()),
+            url: None,
+            started_at: None,
+            completed_at: None,
+            app_slug: Some("github-actions".to_string()),
+            log_excerpt: None,
+        };
+        let status = |state: &str| GitHubCommitStatus {
+            context: "ci/lint".to_string(),
+            state: state.to_string(),
+            description: None,
+            url: None,
+            updated_at: None,
+        };
+        assert_eq!(combined_check_state(&[], &[]), "neutral");
+        assert_eq!(
+            combined_check_state(&[run("completed", Some("success"))], &[status("success")]),
+            "success"
+        );
+        assert_eq!(
+            combined_check_state(&[run("in_progress", None)], &[status("success")]),
+            "pending"
+        );
+        assert_eq!(
+            combined_check_state(&[run("in_progress", None)], &[status("error")]),
+            "failure"
+        );
+        assert_eq!(
+            combined_check_state(&[run("completed", Some("timed_out"))], &[]),
+            "failure"
+        );
+    }
+
+    #[test]
+    fn failing_log_excerpt_keeps_errors_and_tail() {
+        let log = "2024-05-01T10:00:00.0000000Z setup\n\
+2024-05-01T10:00:01.0000000Z ##[error]missing crate\n\
+2024-05-01T10:00:02.0000000Z compiling\n\
+2024-05-01T10:00:03.0000000Z test failed\n\
+2024-05-01T10:00:04.0000000Z ##[error]Process completed with exit code 1.";
+        assert_eq!(
+            failing_log_excerpt(log, 2),
+            "##[error]missing crate\n...\ntest failed\n##[error]Process completed with exit code 1."
+        );
+        assert_eq!(failing_log_excerpt("one\ntwo", 5), "one\ntwo");
+    }
+
+    #[test]
+    fn pull_request_drafts_split_into_title_and_body() {
+        assert_eq!(
+            parse_pull_request_draft("Add retries\n\n## Summary\nRetries failed uploads.\n"),
+            Some((
+                "Add retries".to_string(),
+                "## Summary\nRetries failed uploads.".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_pull_request_draft("```markdown\n# Add retries\n\nBody text\n```"),
+            Some(("Add retries".to_string(), "Body text".to_string()))
+        );
+        assert_eq!(
+            parse_pull_request_draft("**Title:** Add retries\n\n**Body:**\nBody text"),
+            Some(("Add retries".to_string(), "Body text".to_string()))
+        );
+        assert_eq!(
+            parse_pull_request_draft("Add retries"),
+            Some(("Add retries".to_string(), String::new()))
+        );
+        assert_eq!(parse_pull_request_draft("  \n\n"), None);
+        assert_eq!(parse_pull_request_draft("Title:\n\nBody text"), None);
+    }
+}
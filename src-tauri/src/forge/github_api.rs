@@ -0,0 +1,550 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, ACCEPT, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::types::ForgeRemote;
+
+pub(crate) const JSON_MEDIA_TYPE: &str = "application/vnd.github+json";
+pub(crate) const DIFF_MEDIA_TYPE: &str = "application/vnd.github.diff";
+
+const MAX_CACHED_RESPONSES: usize = 256;
+
+#[derive(Debug)]
+pub(crate) enum GitHubApiError {
+    Unauthorized(String),
+    NotFound(String),
+    RateLimited { reset_at: u64 },
+    Http { status: u16, message: String },
+    Network(String),
+    Decode(String),
+}
+
+impl GitHubApiError {
+    /// Whether retrying through `gh` could help: it may hold different
+    /// credentials or reach the host through its own proxy settings.
+    pub(crate) fn allows_cli_fallback(&self) -> bool {
+        matches!(self, Self::Unauthorized(_) | Self::Network(_))
+    }
+}
+
+impl fmt::Display for GitHubApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized(message) => write!(f, "GitHub rejected the token: {message}"),
+            Self::NotFound(message) => write!(f, "Not found on GitHub: {message}"),
+            Self::RateLimited { reset_at } => {
+                let minutes = reset_at.saturating_sub(unix_now()).div_ceil(60);
+                write!(
+                    f,
+                    "GitHub API rate limit exceeded; it resets in {minutes} minute(s)."
+                )
+            }
+            Self::Http { status, message } => write!(f, "GitHub API error {status}: {message}"),
+            Self::Network(message) => write!(f, "Could not reach GitHub: {message}"),
+            Self::Decode(message) => write!(f, "Unexpected GitHub API response: {message}"),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RateLimit {
+    remaining: u64,
+    reset_at: u64,
+}
+
+struct CachedResponse {
+    etag: String,
+    body: Vec<u8>,
+    last_used: u64,
+}
+
+/// State shared by every GitHub client in the app: ETag-validated response
+/// bodies, the last seen rate limit per host and tokens read from `gh`.
+#[derive(Default)]
+pub(crate) struct GitHubApiState {
+    responses: HashMap<String, CachedResponse>,
+    /// Bumped on every cache use; the least recently used entry is evicted.
+    clock: u64,
+    rate_limits: HashMap<String, RateLimit>,
+    cli_tokens: HashMap<String, String>,
+}
+
+impl GitHubApiState {
+    fn cached_body(&mut self, key: &str) -> Option<Vec<u8>> {
+        self.clock += 1;
+        let cached = self.responses.get_mut(key)?;
+        cached.last_used = self.clock;
+        Some(cached.body.clone())
+    }
+
+    fn cache_response(&mut self, key: String, etag: String, body: Vec<u8>) {
+        self.clock += 1;
+        if self.responses.len() >= MAX_CACHED_RESPONSES && !self.responses.contains_key(&key) {
+            let oldest = self
+                .responses
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.responses.remove(&oldest);
+            }
+        }
+        self.responses.insert(
+            key,
+            CachedResponse {
+                etag,
+                body,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    pub(crate) fn cli_token(&self, host: &str) -> Option<String> {
+        self.cli_tokens.get(host).cloned()
+    }
+
+    pub(crate) fn set_cli_token(&mut self, host: &str, token: String) {
+        self.cli_tokens.insert(host.to_string(), token);
+    }
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+fn error_message(body: &[u8]) -> String {
+    serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|value| value.get("message")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| String::from_utf8_lossy(body).trim().to_string())
+}
+
+fn error_for_response(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> GitHubApiError {
+    let message = error_message(body);
+    let exhausted = header_u64(headers, "x-ratelimit-remaining") == Some(0);
+    let retry_after = header_u64(headers, "retry-after");
+    match status.as_u16() {
+        401 => GitHubApiError::Unauthorized(message),
+        403 | 429 if exhausted || retry_after.is_some() => GitHubApiError::RateLimited {
+            reset_at: retry_after
+                .map(|seconds| unix_now() + seconds)
+                .or_else(|| header_u64(headers, "x-ratelimit-reset"))
+                .unwrap_or_else(unix_now),
+        },
+        404 => GitHubApiError::NotFound(message),
+        status => GitHubApiError::Http { status, message },
+    }
+}
+
+/// GitHub REST and GraphQL over HTTP, for a single host.
+pub(crate) struct GitHubClient {
+    http: reqwest::Client,
+    host: String,
+    api_base: String,
+    graphql_url: String,
+    token: String,
+    /// Identifies the token in cache keys, so a response is only reused
+    /// with the credentials that were allowed to see it.
+    token_id: String,
+    state: Arc<Mutex<GitHubApiState>>,
+}
+
+impl GitHubClient {
+    pub(crate) fn new(
+        remote: &ForgeRemote,
+        token: String,
+        state: Arc<Mutex<GitHubApiState>>,
+    ) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .user_agent("codex-monitor")
+            .build()
+            .map_err(|e| e.to_string())?;
+        let api_base = remote.api_base.trim_end_matches('/').to_string();
+        // GitHub Enterprise serves GraphQL next to, not under, the REST prefix.
+        let graphql_url = match api_base.strip_suffix("/api/v3") {
+            Some(root) => format!("{root}/api/graphql"),
+            None => format!("{api_base}/graphql"),
+        };
+        let token_id = Sha256::digest(token.as_bytes())
+            .iter()
+            .take(8)
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        Ok(Self {
+            http,
+            host: remote.host.clone(),
+            api_base,
+            graphql_url,
+            token,
+            token_id,
+            state,
+        })
+    }
+
+    /// Fails fast while the host's rate limit is known to be exhausted.
+    async fn check_rate_limit(&self) -> Result<(), GitHubApiError> {
+        let state = self.state.lock().await;
+        match state.rate_limits.get(&self.host) {
+            Some(limit) if limit.remaining == 0 && limit.reset_at > unix_now() => {
+                Err(GitHubApiError::RateLimited {
+                    reset_at: limit.reset_at,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn record_rate_limit(&self, headers: &HeaderMap) {
+        let remaining = header_u64(headers, "x-ratelimit-remaining");
+        let reset_at = header_u64(headers, "x-ratelimit-reset");
+        if let (Some(remaining), Some(reset_at)) = (remaining, reset_at) {
+            self.state.lock().await.rate_limits.insert(
+                self.host.clone(),
+                RateLimit {
+                    remaining,
+                    reset_at,
+                },
+            );
+        }
+    }
+
+    /// GETs an API path, revalidating earlier responses with their ETag so
+    /// unchanged data costs a 304 and no rate limit.
+    pub(crate) async fn get(&self, path: &str, accept: &str) -> Result<Vec<u8>, GitHubApiError> {
+        self.check_rate_limit().await?;
+        let url = format!("{}{path}", self.api_base);
+        let cache_key = format!("{} {accept} {url}", self.token_id);
+        let mut etag = {
+            let state = self.state.lock().await;
+            state
+                .responses
+                .get(&cache_key)
+                .map(|cached| cached.etag.clone())
+        };
+        loop {
+            let mut request = self
+                .http
+                .get(&url)
+                .header(ACCEPT, accept)
+                .bearer_auth(&self.token);
+            if let Some(etag) = &etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| GitHubApiError::Network(e.to_string()))?;
+            if response.status() == StatusCode::NOT_MODIFIED && etag.is_some() {
+                let headers = response.headers().clone();
+                self.record_rate_limit(&headers).await;
+                if let Some(body) = self.state.lock().await.cached_body(&cache_key) {
+                    return Ok(body);
+                }
+                // Evicted while the request was in flight: ask for the body.
+                etag = None;
+                continue;
+            }
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let body = self.read_response(response).await?;
+            if let Some(etag) = etag {
+                self.state
+                    .lock()
+                    .await
+                    .cache_response(cache_key, etag, body.clone());
+            }
+            return Ok(body);
+        }
+    }
+
+    /// GETs an API path without keeping the body, for large one-off
+    /// downloads such as job logs.
+    pub(crate) async fn get_uncached(
+        &self,
+        path: &str,
+        accept: &str,
+    ) -> Result<Vec<u8>, GitHubApiError> {
+        self.check_rate_limit().await?;
+        let url = format!("{}{path}", self.api_base);
+        let response = self
+            .http
+            .get(&url)
+            .header(ACCEPT, accept)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| GitHubApiError::Network(e.to_string()))?;
+        self.read_response(response).await
+    }
+
+    async fn read_response(&self, response: reqwest::Response) -> Result<Vec<u8>, GitHubApiError> {
+        let status = response.status();
+        let headers = response.headers().clone();
+        self.record_rate_limit(&headers).await;
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| GitHubApiError::Network(e.to_string()))?
+            .to_vec();
+        if !status.is_success() {
+            return Err(error_for_response(status, &headers, &body));
+        }
+        Ok(body)
+    }
+
+    async fn post(&self, url: &str, body: &Value) -> Result<Vec<u8>, GitHubApiError> {
+        self.check_rate_limit().await?;
+        let response = self
+            .http
+            .post(url)
+            .header(ACCEPT, JSON_MEDIA_TYPE)
+            .header(CONTENT_TYPE, "application/json")
+            .bearer_auth(&self.token)
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| GitHubApiError::Network(e.to_string()))?;
+        self.read_response(response).await
+    }
+
+    /// POSTs a JSON body to an API path and decodes the reply.
+    pub(crate) async fn post_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &Value,
+    ) -> Result<T, GitHubApiError> {
+        let body = self.post(&format!("{}{path}", self.api_base), body).await?;
+        serde_json::from_slice(&body).map_err(|e| GitHubApiError::Decode(e.to_string()))
+    }
+
+    pub(crate) async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<T, GitHubApiError> {
+        let body = self.get(path, JSON_MEDIA_TYPE).await?;
+        serde_json::from_slice(&body).map_err(|e| GitHubApiError::Decode(e.to_string()))
+    }
+
+    /// Runs a GraphQL query and returns its `data`.
+    pub(crate) async fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Value,
+    ) -> Result<T, GitHubApiError> {
+        let body = json!({ "query": query, "variables": variables });
+        let body = self.post(&self.graphql_url, &body).await?;
+        let mut value: Value =
+            serde_json::from_slice(&body).map_err(|e| GitHubApiError::Decode(e.to_string()))?;
+        if let Some(error) = value
+            .get("errors")
+            .and_then(|errors| errors.as_array())
+            .and_then(|errors| errors.first())
+        {
+            let message = error
+                .get("message")
+                .and_then(|message| message.as_str())
+                .unwrap_or("GraphQL query failed.")
+                .to_string();
+            return Err(GitHubApiError::Http {
+                status: StatusCode::OK.as_u16(),
+                message,
+            });
+        }
+        serde_json::from_value(value["data"].take())
+            .map_err(|e| GitHubApiError::Decode(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use mockito::Server;
+    use serde_json::{json, Value};
+    use tokio::sync::Mutex;
+
+    use super::{unix_now, GitHubApiError, GitHubApiState, GitHubClient, MAX_CACHED_RESPONSES};
+    use crate::forge::parse_forge_remote;
+    use crate::forge::test_support::block_on;
+    use crate::types::ForgeKind;
+
+    fn client(server: &Server) -> GitHubClient {
+        let state = Arc::new(Mutex::new(GitHubApiState::default()));
+        client_with(server, "secret", state)
+    }
+
+    fn client_with(
+        server: &Server,
+        token: &str,
+        state: Arc<Mutex<GitHubApiState>>,
+    ) -> GitHubClient {
+        let url = format!("{}/me/app.git", server.url());
+        let overrides = BTreeMap::from([(server.host_with_port(), ForgeKind::GitHub)]);
+        let mut remote = parse_forge_remote(&url, &overrides).unwrap();
+        remote.api_base = server.url();
+        GitHubClient::new(&remote, token.to_string(), state).unwrap()
+    }
+
+    #[test]
+    fn revalidates_cached_responses_with_etags() {
+        let mut server = Server::new();
+        let first = server
+            .mock("GET", "/repos/me/app/pulls/1")
+            .match_header("authorization", "Bearer secret")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", "\"abc\"")
+            .with_body(r#"{"number": 1}"#)
+            .expect(1)
+            .create();
+        let revalidated = server
+            .mock("GET", "/repos/me/app/pulls/1")
+            .match_header("if-none-match", "\"abc\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+        let client = client(&server);
+
+        let value: Value = block_on(client.get_json("/repos/me/app/pulls/1")).unwrap();
+        assert_eq!(value, json!({"number": 1}));
+        let value: Value = block_on(client.get_json("/repos/me/app/pulls/1")).unwrap();
+        assert_eq!(value, json!({"number": 1}));
+
+        first.assert();
+        revalidated.assert();
+    }
+
+    #[test]
+    fn cached_responses_are_not_shared_between_tokens() {
+        let mut server = Server::new();
+        let fetched = server
+            .mock("GET", "/repos/me/app/pulls/1")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", "\"abc\"")
+            .with_body(r#"{"number": 1}"#)
+            .expect(2)
+            .create();
+        let state = Arc::new(Mutex::new(GitHubApiState::default()));
+        let first = client_with(&server, "secret", state.clone());
+        let second = client_with(&server, "other", state);
+
+        block_on(first.get_json::<Value>("/repos/me/app/pulls/1")).unwrap();
+        block_on(second.get_json::<Value>("/repos/me/app/pulls/1")).unwrap();
+
+        fetched.assert();
+    }
+
+    #[test]
+    fn refetches_when_a_revalidated_response_was_evicted() {
+        let mut server = Server::new();
+        let fetched = server
+            .mock("GET", "/repos/me/app/pulls/1")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", "\"abc\"")
+            .with_body(r#"{"number": 1}"#)
+            .expect(2)
+            .create();
+        let client = client(&server);
+        let state = client.state.clone();
+        let revalidated = server
+            .mock("GET", "/repos/me/app/pulls/1")
+            .match_header("if-none-match", "\"abc\"")
+            .with_status(304)
+            .with_body_from_request(move |_| {
+                state.try_lock().unwrap().responses.clear();
+                Vec::new()
+            })
+            .expect(1)
+            .create();
+
+        block_on(client.get_json::<Value>("/repos/me/app/pulls/1")).unwrap();
+        let value: Value = block_on(client.get_json("/repos/me/app/pulls/1")).unwrap();
+        assert_eq!(value, json!({"number": 1}));
+
+        fetched.assert();
+        revalidated.assert();
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_response() {
+        let mut state = GitHubApiState::default();
+        for index in 0..MAX_CACHED_RESPONSES {
+            state.cache_response(format!("key-{index}"), "etag".to_string(), Vec::new());
+        }
+        assert!(state.cached_body("key-0").is_some());
+
+        state.cache_response("new".to_string(), "etag".to_string(), Vec::new());
+        assert_eq!(state.responses.len(), MAX_CACHED_RESPONSES);
+        assert!(state.cached_body("key-0").is_some());
+        assert!(state.cached_body("key-1").is_none());
+        assert!(state.cached_body("new").is_some());
+    }
+
+    #[test]
+    fn stops_calling_once_the_rate_limit_is_exhausted() {
+        let mut server = Server::new();
+        let reset_at = (unix_now() + 600).to_string();
+        let limited = server
+            .mock("GET", "/repos/me/app/issues/1")
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &reset_at)
+            .with_body(r#"{"message": "API rate limit exceeded"}"#)
+            .expect(1)
+            .create();
+        let client = client(&server);
+
+        for _ in 0..2 {
+            let error = block_on(client.get_json::<Value>("/repos/me/app/issues/1")).unwrap_err();
+            assert!(
+                matches!(error, GitHubApiError::RateLimited { .. }),
+                "{error}"
+            );
+        }
+        limited.assert();
+    }
+
+    #[test]
+    fn maps_auth_failures_and_graphql_errors() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/user")
+            .with_status(401)
+            .with_body(r#"{"message": "Bad credentials"}"#)
+            .create();
+        server
+            .mock("POST", "/graphql")
+            .with_body(r#"{"data": null, "errors": [{"message": "Field 'nope' doesn't exist"}]}"#)
+            .create();
+        let client = client(&server);
+
+        let error = block_on(client.get_json::<Value>("/user")).unwrap_err();
+        assert!(matches!(error, GitHubApiError::Unauthorized(_)));
+        assert!(error.allows_cli_fallback());
+
+        let error = block_on(client.graphql::<Value>("{ nope }", json!({}))).unwrap_err();
+        assert!(
+            error.to_string().contains("Field 'nope' doesn't exist"),
+            "{error}"
+        );
+        assert!(!error.allows_cli_fallback());
+    }
+}
@@ -0,0 +1,404 @@
+use serde::Deserialize;
+
+use super::{
+    encode_path_segment, filter_value, header_count, list_state, page_size, Forge, ForgeFuture,
+    ForgeHttp,
+};
+use crate::types::{
+    ForgeListQuery, ForgeRemote, GitHubIssue, GitHubIssuesResponse, GitHubPullRequest,
+    GitHubPullRequestAuthor, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestsResponse,
+};
+use crate::utils::normalize_git_path;
+
+/// GitLab (gitlab.com or self-hosted) through the v4 REST API.
+pub(crate) struct GitLabForge {
+    remote: ForgeRemote,
+    http: ForgeHttp,
+}
+
+#[derive(Deserialize)]
+struct GitLabUser {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabIssue {
+    iid: u64,
+    title: String,
+    web_url: String,
+    updated_at: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabMergeRequest {
+    iid: u64,
+    title: String,
+    web_url: String,
+    updated_at: String,
+    created_at: String,
+    #[serde(default)]
+    description: Option<String>,
+    source_branch: String,
+    target_branch: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    author: Option<GitLabUser>,
+}
+
+#[derive(Deserialize)]
+struct GitLabDiff {
+    old_path: String,
+    new_path: String,
+    #[serde(default)]
+    diff: String,
+    #[serde(default)]
+    new_file: bool,
+    #[serde(default)]
+    renamed_file: bool,
+    #[serde(default)]
+    deleted_file: bool,
+}
+
+#[derive(Deserialize)]
+struct GitLabNote {
+    id: u64,
+    #[serde(default)]
+    body: String,
+    created_at: String,
+    #[serde(default)]
+    system: bool,
+    #[serde(default)]
+    author: Option<GitLabUser>,
+}
+
+fn author(user: Option<GitLabUser>) -> Option<GitHubPullRequestAuthor> {
+    user.map(|user| GitHubPullRequestAuthor {
+        login: user.username,
+    })
+}
+
+/// GitLab returns bare hunks; rebuild the git headers the diff viewer and
+/// review tooling expect.
+fn diff_entry(diff: GitLabDiff) -> GitHubPullRequestDiff {
+    let (status, path) = if diff.new_file {
+        ("A", &diff.new_path)
+    } else if diff.deleted_file {
+        ("D", &diff.old_path)
+    } else if diff.renamed_file {
+        ("R", &diff.new_path)
+    } else {
+        ("M", &diff.new_path)
+    };
+    let mut text = format!("diff --git a/{} b/{}\n", diff.old_path, diff.new_path);
+    match status {
+        "A" => text.push_str(&format!("--- /dev/null\n+++ b/{}\n", diff.new_path)),
+        "D" => text.push_str(&format!("--- a/{}\n+++ /dev/null\n", diff.old_path)),
+        _ => text.push_str(&format!(
+            "--- a/{}\n+++ b/{}\n",
+            diff.old_path, diff.new_path
+        )),
+    }
+    text.push_str(diff.diff.trim_end_matches('\n'));
+    GitHubPullRequestDiff {
+        path: normalize_git_path(path),
+        status: status.to_string(),
+        diff: text,
+    }
+}
+
+/// Query string for GitLab's offset-paginated list endpoints. The cursor is
+/// the page number.
+fn list_params(query: &ForgeListQuery) -> String {
+    let state = match list_state(query).as_str() {
+        "open" => "opened".to_string(),
+        state => state.to_string(),
+    };
+    let page = filter_value(&query.cursor).unwrap_or("1");
+    let mut params = vec![
+        format!("state={}", encode_path_segment(&state)),
+        format!("per_page={}", page_size(query)),
+        format!("page={}", encode_path_segment(page)),
+        "order_by=updated_at".to_string(),
+    ];
+    let labels: Vec<&str> = query
+        .labels
+        .iter()
+        .map(|label| label.trim())
+        .filter(|label| !label.is_empty())
+        .collect();
+    if !labels.is_empty() {
+        params.push(format!("labels={}", encode_path_segment(&labels.join(","))));
+    }
+    if let Some(author) = filter_value(&query.author) {
+        params.push(format!("author_username={}", encode_path_segment(author)));
+    }
+    if let Some(assignee) = filter_value(&query.assignee) {
+        params.push(format!(
+            "assignee_username={}",
+            encode_path_segment(assignee)
+        ));
+    }
+    if let Some(search) = filter_value(&query.search) {
+        params.push(format!("search={}", encode_path_segment(search)));
+    }
+    params.join("&")
+}
+
+fn next_page(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    header_count(headers, "x-next-page").map(|page| page.to_string())
+}
+
+impl GitLabForge {
+    pub(crate) fn new(remote: ForgeRemote, token: Option<String>) -> Result<Self, String> {
+        let http = ForgeHttp::new(token.map(|token| ("private-token", token)))?;
+        Ok(Self { remote, http })
+    }
+
+    fn project_url(&self, path: &str) -> String {
+        format!(
+            "{}/projects/{}{path}",
+            self.remote.api_base,
+            encode_path_segment(&self.remote.repo)
+        )
+    }
+
+    async fn issues(&self, query: &ForgeListQuery) -> Result<GitHubIssuesResponse, String> {
+        let url = self.project_url(&format!("/issues?{}", list_params(query)));
+        let (issues, headers) = self.http.get_json::<Vec<GitLabIssue>>(&url).await?;
+        let issues: Vec<GitHubIssue> = issues
+            .into_iter()
+            .map(|issue| GitHubIssue {
+                number: issue.iid,
+                title: issue.title,
+                url: issue.web_url,
+                updated_at: issue.updated_at,
+            })
+            .collect();
+        let total = header_count(&headers, "x-total").unwrap_or(issues.len());
+        Ok(GitHubIssuesResponse {
+            total,
+            issues,
+            next_cursor: next_page(&headers),
+        })
+    }
+
+    async fn merge_requests(
+        &self,
+        query: &ForgeListQuery,
+    ) -> Result<GitHubPullRequestsResponse, String> {
+        let url = self.project_url(&format!("/merge_requests?{}", list_params(query)));
+        let (merge_requests, headers) = self.http.get_json::<Vec<GitLabMergeRequest>>(&url).await?;
+        let pull_requests: Vec<GitHubPullRequest> = merge_requests
+            .into_iter()
+            .map(|merge_request| GitHubPullRequest {
+                number: merge_request.iid,
+                title: merge_request.title,
+                url: merge_request.web_url,
+                updated_at: merge_request.updated_at,
+                created_at: merge_request.created_at,
+                body: merge_request.description.unwrap_or_default(),
+                head_ref_name: merge_request.source_branch,
+                base_ref_name: merge_request.target_branch,
+                is_draft: merge_request.draft,
+                author: author(merge_request.author),
+            })
+            .collect();
+        let total = header_count(&headers, "x-total").unwrap_or(pull_requests.len());
+        Ok(GitHubPullRequestsResponse {
+            total,
+            pull_requests,
+            next_cursor: next_page(&headers),
+        })
+    }
+
+    async fn diff(&self, number: u64) -> Result<Vec<GitHubPullRequestDiff>, String> {
+        let url = self.project_url(&format!("/merge_requests/{number}/diffs?per_page=100"));
+        let (diffs, _) = self.http.get_json::<Vec<GitLabDiff>>(&url).await?;
+        Ok(diffs.into_iter().map(diff_entry).collect())
+    }
+
+    async fn comments(&self, number: u64) -> Result<Vec<GitHubPullRequestComment>, String> {
+        let url = self.project_url(&format!(
+            "/merge_requests/{number}/notes?sort=asc&order_by=created_at&per_page=30"
+        ));
+        let (notes, _) = self.http.get_json::<Vec<GitLabNote>>(&url).await?;
+        let merge_request_url = format!("{}/-/merge_requests/{number}", self.remote.web_url);
+        Ok(notes
+            .into_iter()
+            .filter(|note| !note.system)
+            .map(|note| GitHubPullRequestComment {
+                id: note.id,
+                body: note.body,
+                created_at: note.created_at,
+                url: format!("{merge_request_url}#note_{}", note.id),
+                author: author(note.author),
+            })
+            .collect())
+    }
+}
+
+impl Forge for GitLabForge {
+    fn remote(&self) -> &ForgeRemote {
+        &self.remote
+    }
+
+    fn list_issues<'a>(
+        &'a self,
+        query: &'a ForgeListQuery,
+    ) -> ForgeFuture<'a, GitHubIssuesResponse> {
+        Box::pin(self.issues(query))
+    }
+
+    fn list_merge_requests<'a>(
+        &'a self,
+        query: &'a ForgeListQuery,
+    ) -> ForgeFuture<'a, GitHubPullRequestsResponse> {
+        Box::pin(self.merge_requests(query))
+    }
+
+    fn merge_request_diff(&self, number: u64) -> ForgeFuture<'_, Vec<GitHubPullRequestDiff>> {
+        Box::pin(self.diff(number))
+    }
+
+    fn merge_request_comments(
+        &self,
+        number: u64,
+    ) -> ForgeFuture<'_, Vec<GitHubPullRequestComment>> {
+        Box::pin(self.comments(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use mockito::{Matcher, Server};
+
+    use super::GitLabForge;
+    use crate::forge::test_support::block_on;
+    use crate::forge::{parse_forge_remote, Forge};
+    use crate::types::{ForgeKind, ForgeListQuery};
+
+    fn forge(server: &Server, token: Option<&str>) -> GitLabForge {
+        let url = format!("{}/team/infra/tools.git", server.url());
+        let overrides = BTreeMap::from([(server.host_with_port(), ForgeKind::GitLab)]);
+        let remote = parse_forge_remote(&url, &overrides).unwrap();
+        GitLabForge::new(remote, token.map(|token| token.to_string())).unwrap()
+    }
+
+    #[test]
+    fn lists_issues_and_merge_requests() {
+        let mut server = Server::new();
+        let issues = server
+            .mock("GET", "/api/v4/projects/team%2Finfra%2Ftools/issues")
+            .match_query(Matcher::UrlEncoded("state".into(), "opened".into()))
+            .match_header("private-token", "secret")
+            .with_header("x-total", "73")
+            .with_header("x-next-page", "2")
+            .with_body(
+                r#"[{"iid": 7, "title": "Crash on start", "web_url": "https://gl/issues/7", "updated_at": "2024-05-01T10:00:00Z"}]"#,
+            )
+            .create();
+        let merge_requests = server
+            .mock("GET", "/api/v4/projects/team%2Finfra%2Ftools/merge_requests")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("state".into(), "merged".into()),
+                Matcher::UrlEncoded("labels".into(), "bug,ui".into()),
+                Matcher::UrlEncoded("author_username".into(), "sam".into()),
+                Matcher::UrlEncoded("search".into(), "fix crash".into()),
+                Matcher::UrlEncoded("page".into(), "3".into()),
+            ]))
+            .with_body(
+                r#"[{"iid": 3, "title": "Fix crash", "web_url": "https://gl/mr/3", "updated_at": "2024-05-02T10:00:00Z", "created_at": "2024-05-01T09:00:00Z", "description": null, "source_branch": "fix-crash", "target_branch": "main", "draft": true, "author": {"username": "sam"}}]"#,
+            )
+            .create();
+        let forge = forge(&server, Some("secret"));
+
+        let response = block_on(forge.list_issues(&ForgeListQuery::default())).unwrap();
+        assert_eq!(response.total, 73);
+        assert_eq!(response.next_cursor.as_deref(), Some("2"));
+        assert_eq!(response.issues[0].number, 7);
+        assert_eq!(response.issues[0].title, "Crash on start");
+
+        let query = ForgeListQuery {
+            state: Some("merged".to_string()),
+            labels: vec!["bug".to_string(), "ui".to_string()],
+            author: Some("sam".to_string()),
+            search: Some("fix crash".to_string()),
+            cursor: Some("3".to_string()),
+            ..ForgeListQuery::default()
+        };
+        let response = block_on(forge.list_merge_requests(&query)).unwrap();
+        assert_eq!(response.total, 1);
+        assert!(response.next_cursor.is_none());
+        let merge_request = &response.pull_requests[0];
+        assert_eq!(merge_request.head_ref_name, "fix-crash");
+        assert_eq!(merge_request.base_ref_name, "main");
+        assert!(merge_request.is_draft);
+        assert_eq!(merge_request.body, "");
+        assert_eq!(merge_request.author.as_ref().unwrap().login, "sam");
+
+        issues.assert();
+        merge_requests.assert();
+    }
+
+    #[test]
+    fn rebuilds_merge_request_diffs_and_skips_system_notes() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/api/v4/projects/team%2Finfra%2Ftools/merge_requests/3/diffs")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"[
+                    {"old_path": "src/a.rs", "new_path": "src/a.rs", "diff": "@@ -1 +1 @@\n-a\n+b\n", "new_file": false, "renamed_file": false, "deleted_file": false},
+                    {"old_path": "src/new.rs", "new_path": "src/new.rs", "diff": "@@ -0,0 +1 @@\n+x\n", "new_file": true, "renamed_file": false, "deleted_file": false}
+                ]"#,
+            )
+            .create();
+        server
+            .mock("GET", "/api/v4/projects/team%2Finfra%2Ftools/merge_requests/3/notes")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"[
+                    {"id": 1, "body": "added 1 commit", "created_at": "2024-05-01T10:00:00Z", "system": true, "author": {"username": "sam"}},
+                    {"id": 2, "body": "Looks good", "created_at": "2024-05-01T11:00:00Z", "system": false, "author": {"username": "alex"}}
+                ]"#,
+            )
+            .create();
+        let forge = forge(&server, None);
+
+        let diffs = block_on(forge.merge_request_diff(3)).unwrap();
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].status, "M");
+        assert_eq!(
+            diffs[0].diff,
+            "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1 +1 @@\n-a\n+b"
+        );
+        assert_eq!(diffs[1].status, "A");
+        assert!(diffs[1].diff.contains("--- /dev/null\n+++ b/src/new.rs"));
+
+        let comments = block_on(forge.merge_request_comments(3)).unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].body, "Looks good");
+        assert!(comments[0]
+            .url
+            .ends_with("/team/infra/tools/-/merge_requests/3#note_2"));
+    }
+
+    #[test]
+    fn surfaces_api_error_messages() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/api/v4/projects/team%2Finfra%2Ftools/issues")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .with_body(r#"{"message": "404 Project Not Found"}"#)
+            .create();
+        let forge = forge(&server, None);
+
+        let error = block_on(forge.list_issues(&ForgeListQuery::default())).unwrap_err();
+        assert!(error.contains("404 Project Not Found"), "{error}");
+    }
+}
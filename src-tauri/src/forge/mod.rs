@@ -0,0 +1,637 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use git2::Repository;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+use crate::git_utils::{default_remote_name, resolve_git_root};
+use crate::settings::read_secret;
+use crate::state::AppState;
+use crate::types::{
+    ForgeKind, ForgeListQuery, ForgeRemote, GitHubIssuesResponse, GitHubPullRequestComment,
+    GitHubPullRequestDiff, GitHubPullRequestsResponse, SecretKind,
+};
+
+mod gitea;
+mod github;
+mod github_api;
+mod gitlab;
+
+pub(crate) use gitea::GiteaForge;
+pub(crate) use github::{
+    build_review_preview, check_run_failed, combined_check_state, commit_status_failed,
+    failing_log_excerpt, parse_pull_request_draft, review_request_body, GitHubForge,
+};
+pub(crate) use github_api::GitHubApiState;
+pub(crate) use gitlab::GitLabForge;
+
+pub(crate) type ForgeFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// The parts of a code host the app reads from. Issues and merge requests
+/// are returned in the GitHub shapes the UI already renders; GitLab merge
+/// request IIDs are used as the numbers.
+pub(crate) trait Forge: Send + Sync {
+    fn remote(&self) -> &ForgeRemote;
+
+    fn list_issues<'a>(
+        &'a self,
+        query: &'a ForgeListQuery,
+    ) -> ForgeFuture<'a, GitHubIssuesResponse>;
+
+    fn list_merge_requests<'a>(
+        &'a self,
+        query: &'a ForgeListQuery,
+    ) -> ForgeFuture<'a, GitHubPullRequestsResponse>;
+
+    fn merge_request_diff(&self, number: u64) -> ForgeFuture<'_, Vec<GitHubPullRequestDiff>>;
+
+    fn merge_request_comments(&self, number: u64)
+        -> ForgeFuture<'_, Vec<GitHubPullRequestComment>>;
+}
+
+/// Splits a remote URL into host and repository path. Handles scp-style
+/// (`git@host:group/repo.git`), `ssh://`, `git://` and `http(s)://` remotes.
+/// The returned scheme is the one to use for web and API requests.
+fn split_remote_url(remote_url: &str) -> Option<(String, String, String)> {
+    let trimmed = remote_url.trim();
+    let (scheme, host, path) = if let Some((scheme, rest)) = trimmed.split_once("://") {
+        let rest = rest.rsplit_once('@').map(|(_, rest)| rest).unwrap_or(rest);
+        let (authority, path) = rest.split_once('/')?;
+        let web_scheme = if scheme == "http" { "http" } else { "https" };
+        let host = if matches!(scheme, "http" | "https") {
+            authority
+        } else {
+            // SSH ports say nothing about where the web UI lives.
+            authority.split(':').next().unwrap_or(authority)
+        };
+        (web_scheme, host, path)
+    } else {
+        let rest = trimmed
+            .rsplit_once('@')
+            .map(|(_, rest)| rest)
+            .unwrap_or(trimmed);
+        let (host, path) = rest.split_once(':')?;
+        ("https", host, path)
+    };
+    let path = path
+        .trim_matches('/')
+        .trim_end_matches(".git")
+        .trim_end_matches('/');
+    if host.is_empty() || !path.contains('/') {
+        return None;
+    }
+    Some((scheme.to_string(), host.to_lowercase(), path.to_string()))
+}
+
+fn detect_forge_kind(host: &str, overrides: &BTreeMap<String, ForgeKind>) -> Option<ForgeKind> {
+    if let Some(kind) = overrides.get(host) {
+        return Some(*kind);
+    }
+    let name = host.split(':').next().unwrap_or(host);
+    if name == "github.com" || name.ends_with(".github.com") || name.starts_with("github.") {
+        Some(ForgeKind::GitHub)
+    } else if name.contains("gitlab") {
+        Some(ForgeKind::GitLab)
+    } else if name.contains("gitea") || name.contains("forgejo") || name == "codeberg.org" {
+        Some(ForgeKind::Gitea)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn parse_forge_remote(
+    remote_url: &str,
+    overrides: &BTreeMap<String, ForgeKind>,
+) -> Option<ForgeRemote> {
+    let (scheme, host, repo) = split_remote_url(remote_url)?;
+    let kind = detect_forge_kind(&host, overrides)?;
+    let web_url = format!("{scheme}://{host}/{repo}");
+    let api_base = match kind {
+        ForgeKind::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+        ForgeKind::GitHub => format!("{scheme}://{host}/api/v3"),
+        ForgeKind::GitLab => format!("{scheme}://{host}/api/v4"),
+        ForgeKind::Gitea => format!("{scheme}://{host}/api/v1"),
+    };
+    Some(ForgeRemote {
+        kind,
+        host,
+        repo,
+        web_url,
+        api_base,
+    })
+}
+
+pub(crate) fn forge_remote_from_path(
+    repo_root: &Path,
+    overrides: &BTreeMap<String, ForgeKind>,
+) -> Result<ForgeRemote, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let name = default_remote_name(&repo).ok_or("No git remote configured.")?;
+    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
+    let remote_url = remote.url().ok_or("Remote has no URL configured.")?;
+    parse_forge_remote(remote_url, overrides).ok_or_else(|| {
+        format!("Could not tell which forge hosts {remote_url}. Add its host to forgeHosts in settings.")
+    })
+}
+
+/// How far a remote's host is trusted with the user's credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HostTrust {
+    /// The forge's public instance: github.com, gitlab.com or codeberg.org.
+    Public,
+    /// A self-hosted instance the user listed in `forgeHosts`.
+    Configured,
+    /// A host whose forge was only guessed from its name, or one not served
+    /// over https. It gets no token.
+    Untrusted,
+}
+
+pub(crate) fn host_trust(
+    remote: &ForgeRemote,
+    overrides: &BTreeMap<String, ForgeKind>,
+) -> HostTrust {
+    if !remote.api_base.starts_with("https://") {
+        return HostTrust::Untrusted;
+    }
+    let public_host = match remote.kind {
+        ForgeKind::GitHub => "github.com",
+        ForgeKind::GitLab => "gitlab.com",
+        ForgeKind::Gitea => "codeberg.org",
+    };
+    if remote.host == public_host {
+        HostTrust::Public
+    } else if overrides.get(&remote.host) == Some(&remote.kind) {
+        HostTrust::Configured
+    } else {
+        HostTrust::Untrusted
+    }
+}
+
+/// Reads a GitLab or Gitea token from the environment, then from the
+/// forge's CLI. GitHub tokens are resolved by [`GitHubForge`]; a saved
+/// GitLab token takes precedence over both.
+async fn forge_token(remote: &ForgeRemote) -> Option<String> {
+    let (env_var, cli_args): (&str, Option<Vec<&str>>) = match remote.kind {
+        ForgeKind::GitHub => return None,
+        ForgeKind::GitLab => (
+            "GITLAB_TOKEN",
+            Some(vec![
+                "glab",
+                "config",
+                "get",
+                "token",
+                "--host",
+                &remote.host,
+            ]),
+        ),
+        ForgeKind::Gitea => ("GITEA_TOKEN", None),
+    };
+    if let Some(token) = std::env::var(env_var)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+    {
+        return Some(token.trim().to_string());
+    }
+    let args = cli_args?;
+    let output = Command::new(args[0]).args(&args[1..]).output().await.ok()?;
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !token.is_empty()).then_some(token)
+}
+
+pub(crate) async fn forge_for_remote(
+    repo_root: &Path,
+    remote: ForgeRemote,
+    state: &AppState,
+) -> Result<Box<dyn Forge>, String> {
+    let overrides = state.app_settings.lock().await.forge_hosts.clone();
+    let trusted = host_trust(&remote, &overrides) != HostTrust::Untrusted;
+    let forge: Box<dyn Forge> = match remote.kind {
+        ForgeKind::GitHub => {
+            Box::new(GitHubForge::with_remote(repo_root.to_path_buf(), remote, state).await)
+        }
+        ForgeKind::GitLab => {
+            let token = if !trusted {
+                None
+            } else {
+                match read_secret(state, SecretKind::GitLab).await {
+                    Some(token) => Some(token),
+                    None => forge_token(&remote).await,
+                }
+            };
+            Box::new(GitLabForge::new(remote, token)?)
+        }
+        ForgeKind::Gitea => {
+            let token = if trusted {
+                forge_token(&remote).await
+            } else {
+                None
+            };
+            Box::new(GiteaForge::new(remote, token)?)
+        }
+    };
+    Ok(forge)
+}
+
+/// Normalized list state, defaulting to `open`.
+pub(crate) fn list_state(query: &ForgeListQuery) -> String {
+    query
+        .state
+        .as_deref()
+        .map(|state| state.trim().to_lowercase())
+        .filter(|state| !state.is_empty())
+        .unwrap_or_else(|| "open".to_string())
+}
+
+pub(crate) fn page_size(query: &ForgeListQuery) -> usize {
+    query.page_size.unwrap_or(50).clamp(1, 100)
+}
+
+/// Non-empty, trimmed filter value.
+pub(crate) fn filter_value(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+const FORGE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Short-lived cache for list results so sidebar refreshes don't hit the
+/// forge (or spawn `gh`) every time. Keyed by repository, list kind and query.
+#[derive(Default)]
+pub(crate) struct ForgeCache {
+    entries: HashMap<String, (Instant, Value)>,
+}
+
+impl ForgeCache {
+    fn key(remote: &ForgeRemote, kind: &str, query: &ForgeListQuery) -> String {
+        let query = serde_json::to_string(query).unwrap_or_default();
+        format!("{}\n{kind}\n{query}", remote.web_url)
+    }
+
+    fn get<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        self.entries
+            .retain(|_, (stored_at, _)| stored_at.elapsed() < FORGE_CACHE_TTL);
+        let (_, value) = self.entries.get(key)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    fn insert<T: Serialize>(&mut self, key: String, value: &T) {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.entries.insert(key, (Instant::now(), value));
+        }
+    }
+
+    /// Drops every cached list for a repository, e.g. after opening a pull request.
+    pub(crate) fn invalidate(&mut self, remote: &ForgeRemote) {
+        let prefix = format!("{}\n", remote.web_url);
+        self.entries.retain(|key, _| !key.starts_with(&prefix));
+    }
+}
+
+/// Returns a fresh cached list or runs `fetch` and caches its result.
+pub(crate) async fn cached_list<T, F, Fut>(
+    cache: &Mutex<ForgeCache>,
+    remote: &ForgeRemote,
+    kind: &str,
+    query: &ForgeListQuery,
+    fetch: F,
+) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let key = ForgeCache::key(remote, kind, query);
+    if let Some(value) = cache.lock().await.get(&key) {
+        return Ok(value);
+    }
+    let value = fetch().await?;
+    cache.lock().await.insert(key, &value);
+    Ok(value)
+}
+
+/// Minimal JSON-over-HTTP client shared by the REST-based forges.
+pub(crate) struct ForgeHttp {
+    client: reqwest::Client,
+    headers: HeaderMap,
+}
+
+impl ForgeHttp {
+    pub(crate) fn new(auth: Option<(&'static str, String)>) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .user_agent("codex-monitor")
+            .build()
+            .map_err(|e| e.to_string())?;
+        let mut headers = HeaderMap::new();
+        if let Some((name, value)) = auth {
+            let value = HeaderValue::from_str(&value).map_err(|e| e.to_string())?;
+            headers.insert(HeaderName::from_static(name), value);
+        }
+        Ok(Self { client, headers })
+    }
+
+    pub(crate) async fn get(&self, url: &str) -> Result<(Vec<u8>, HeaderMap), String> {
+        let response = self
+            .client
+            .get(url)
+            .headers(self.headers.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Request to {url} failed: {e}"))?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await.map_err(|e| e.to_string())?.to_vec();
+        if status.is_success() {
+            return Ok((body, headers));
+        }
+        let message = serde_json::from_slice::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|value| {
+                let message = value.get("message").or_else(|| value.get("error"))?;
+                Some(match message.as_str() {
+                    Some(text) => text.to_string(),
+                    None => message.to_string(),
+                })
+            })
+            .unwrap_or_else(|| String::from_utf8_lossy(&body).trim().to_string());
+        if message.is_empty() {
+            return Err(format!("Request failed with HTTP {status}."));
+        }
+        Err(format!("Request failed with HTTP {status}: {message}"))
+    }
+
+    pub(crate) async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<(T, HeaderMap), String> {
+        let (body, headers) = self.get(url).await?;
+        let value = serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+        Ok((value, headers))
+    }
+}
+
+pub(crate) fn header_count(headers: &HeaderMap, name: &str) -> Option<usize> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Percent-encodes a value for use as a single URL path segment.
+pub(crate) fn encode_path_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+async fn workspace_remote(
+    workspace_id: &str,
+    state: &State<'_, AppState>,
+) -> Result<(PathBuf, ForgeRemote), String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let overrides = state.app_settings.lock().await.forge_hosts.clone();
+    let remote = forge_remote_from_path(&repo_root, &overrides)?;
+    Ok((repo_root, remote))
+}
+
+async fn workspace_forge(
+    workspace_id: &str,
+    state: &State<'_, AppState>,
+) -> Result<Box<dyn Forge>, String> {
+    let (repo_root, remote) = workspace_remote(workspace_id, state).await?;
+    forge_for_remote(&repo_root, remote, state).await
+}
+
+#[tauri::command]
+pub(crate) async fn get_forge_remote(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<ForgeRemote, String> {
+    let (_, remote) = workspace_remote(&workspace_id, &state).await?;
+    Ok(remote)
+}
+
+#[tauri::command]
+pub(crate) async fn get_forge_issues(
+    workspace_id: String,
+    query: Option<ForgeListQuery>,
+    state: State<'_, AppState>,
+) -> Result<GitHubIssuesResponse, String> {
+    let (repo_root, remote) = workspace_remote(&workspace_id, &state).await?;
+    let query = query.unwrap_or_default();
+    cached_list(&state.forge_cache, &remote, "issues", &query, || async {
+        let forge = forge_for_remote(&repo_root, remote.clone(), &state).await?;
+        forge.list_issues(&query).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn get_forge_merge_requests(
+    workspace_id: String,
+    query: Option<ForgeListQuery>,
+    state: State<'_, AppState>,
+) -> Result<GitHubPullRequestsResponse, String> {
+    let (repo_root, remote) = workspace_remote(&workspace_id, &state).await?;
+    let query = query.unwrap_or_default();
+    cached_list(
+        &state.forge_cache,
+        &remote,
+        "merge_requests",
+        &query,
+        || async {
+            let forge = forge_for_remote(&repo_root, remote.clone(), &state).await?;
+            forge.list_merge_requests(&query).await
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn get_forge_merge_request_diff(
+    workspace_id: String,
+    number: u64,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitHubPullRequestDiff>, String> {
+    let forge = workspace_forge(&workspace_id, &state).await?;
+    forge.merge_request_diff(number).await
+}
+
+#[tauri::command]
+pub(crate) async fn get_forge_merge_request_comments(
+    workspace_id: String,
+    number: u64,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitHubPullRequestComment>, String> {
+    let forge = workspace_forge(&workspace_id, &state).await?;
+    forge.merge_request_comments(number).await
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    /// Runs `future` on a fresh current-thread runtime, for the forge tests
+    /// that talk to a mock server.
+    pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use std::time::{Duration, Instant};
+
+    use super::{encode_path_segment, host_trust, parse_forge_remote, ForgeCache, HostTrust};
+    use crate::types::{ForgeKind, ForgeListQuery};
+
+    #[test]
+    fn parse_forge_remote_detects_hosts_and_paths() {
+        let none = BTreeMap::new();
+        let github = parse_forge_remote("git@github.com:openai/codex.git", &none).unwrap();
+        assert_eq!(github.kind, ForgeKind::GitHub);
+        assert_eq!(github.repo, "openai/codex");
+        assert_eq!(github.api_base, "https://api.github.com");
+
+        let gitlab = parse_forge_remote(
+            "ssh://git@gitlab.example.com:2222/team/infra/tools.git",
+            &none,
+        )
+        .unwrap();
+        assert_eq!(gitlab.kind, ForgeKind::GitLab);
+        assert_eq!(gitlab.host, "gitlab.example.com");
+        assert_eq!(gitlab.repo, "team/infra/tools");
+        assert_eq!(
+            gitlab.web_url,
+            "https://gitlab.example.com/team/infra/tools"
+        );
+        assert_eq!(gitlab.api_base, "https://gitlab.example.com/api/v4");
+
+        let gitea = parse_forge_remote("http://localhost:3000/me/app", &none);
+        assert!(gitea.is_none());
+
+        let overrides = BTreeMap::from([("localhost:3000".to_string(), ForgeKind::Gitea)]);
+        let gitea = parse_forge_remote("http://localhost:3000/me/app", &overrides).unwrap();
+        assert_eq!(gitea.kind, ForgeKind::Gitea);
+        assert_eq!(gitea.api_base, "http://localhost:3000/api/v1");
+
+        let codeberg = parse_forge_remote("https://user@codeberg.org/me/app.git", &none).unwrap();
+        assert_eq!(codeberg.kind, ForgeKind::Gitea);
+        assert_eq!(codeberg.web_url, "https://codeberg.org/me/app");
+
+        assert!(parse_forge_remote("/srv/git/app.git", &none).is_none());
+    }
+
+    #[test]
+    fn tokens_are_only_trusted_to_known_or_configured_hosts() {
+        let none = BTreeMap::new();
+        let trust = |url: &str, overrides: &BTreeMap<String, ForgeKind>| {
+            host_trust(&parse_forge_remote(url, overrides).unwrap(), overrides)
+        };
+        assert_eq!(trust("git@gitlab.com:me/app.git", &none), HostTrust::Public);
+        assert_eq!(
+            trust("https://codeberg.org/me/app", &none),
+            HostTrust::Public
+        );
+        assert_eq!(
+            trust("https://gitlab.evil.example/me/app", &none),
+            HostTrust::Untrusted
+        );
+
+        let overrides = BTreeMap::from([
+            ("git.corp.example".to_string(), ForgeKind::GitLab),
+            ("localhost:3000".to_string(), ForgeKind::Gitea),
+        ]);
+        assert_eq!(
+            trust("git@git.corp.example:team/app.git", &overrides),
+            HostTrust::Configured
+        );
+        assert_eq!(
+            trust("https://gitlab.evil.example/me/app", &overrides),
+            HostTrust::Untrusted
+        );
+        assert_eq!(
+            trust("http://localhost:3000/me/app", &overrides),
+            HostTrust::Untrusted
+        );
+        assert_eq!(
+            trust("http://gitlab.com/me/app", &none),
+            HostTrust::Untrusted
+        );
+    }
+
+    #[test]
+    fn encode_path_segment_escapes_slashes() {
+        assert_eq!(
+            encode_path_segment("team/infra/tools"),
+            "team%2Finfra%2Ftools"
+        );
+        assert_eq!(encode_path_segment("a b.c"), "a%20b.c");
+    }
+
+    #[test]
+    fn forge_cache_expires_and_invalidates_per_repo() {
+        let none = BTreeMap::new();
+        let app = parse_forge_remote("git@github.com:me/app.git", &none).unwrap();
+        let lib = parse_forge_remote("git@github.com:me/lib.git", &none).unwrap();
+        let open = ForgeListQuery::default();
+        let closed = ForgeListQuery {
+            state: Some("closed".to_string()),
+            ..ForgeListQuery::default()
+        };
+        let mut cache = ForgeCache::default();
+        cache.insert(ForgeCache::key(&app, "issues", &open), &1);
+        cache.insert(ForgeCache::key(&app, "issues", &closed), &2);
+        cache.insert(ForgeCache::key(&lib, "issues", &open), &3);
+
+        assert_eq!(
+            cache.get::<u32>(&ForgeCache::key(&app, "issues", &closed)),
+            Some(2)
+        );
+        assert_eq!(
+            cache.get::<u32>(&ForgeCache::key(&app, "merge_requests", &open)),
+            None
+        );
+
+        cache.invalidate(&app);
+        assert_eq!(
+            cache.get::<u32>(&ForgeCache::key(&app, "issues", &open)),
+            None
+        );
+        assert_eq!(
+            cache.get::<u32>(&ForgeCache::key(&lib, "issues", &open)),
+            Some(3)
+        );
+
+        let key = ForgeCache::key(&lib, "issues", &open);
+        if let Some(stale) = Instant::now().checked_sub(Duration::from_secs(60)) {
+            cache.entries.get_mut(&key).unwrap().0 = stale;
+            assert_eq!(cache.get::<u32>(&key), None);
+        }
+    }
+}
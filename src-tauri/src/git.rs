@@ -1,21 +1,45 @@
 use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
 
-use git2::{BranchType, DiffOptions, Repository, Sort, Status, StatusOptions};
+use git2::{BranchType, Repository, Sort};
 use serde_json::json;
-use tauri::State;
+use tauri::{AppHandle, State};
+use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
+use crate::codex::{run_read_only_agent_turn, start_agent_turn};
+use crate::event_sink::TauriEventSink;
+use crate::forge::{
+    build_review_preview, cached_list, check_run_failed, combined_check_state,
+    commit_status_failed, failing_log_excerpt, parse_pull_request_draft, review_request_body,
+    Forge, GitHubForge,
+};
 use crate::git_utils::{
-    checkout_branch, commit_to_entry, diff_patch_to_string, diff_stats_for_path,
-    list_git_roots as scan_git_roots, parse_github_repo, resolve_git_root,
+    apply_stash, blame_file, check_repo_relative_path, checkout_branch, collect_status_entries,
+    commit_to_entry, commits_since_base, conflict_versions, default_base_branch,
+    default_remote_name, diff_to_file_diffs, file_history, has_conflict_markers, list_conflicts,
+    list_git_roots as scan_git_roots, list_stashes, parse_pr_diff, push_stash, rebase_in_progress,
+    resolve_git_root, stash_diffs, status_payload, workdir_diff, DiffLimits,
 };
+use crate::git_watcher::GitStatusWatcher;
 use crate::state::AppState;
 use crate::types::{
-    BranchInfo, GitFileDiff, GitFileStatus, GitHubIssue, GitHubIssuesResponse,
-    GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestDiff,
-    GitHubPullRequestsResponse, GitLogResponse,
+    BranchInfo, ForgeListQuery, GitBlameHunk, GitConflictFile, GitConflictVersions, GitFileDiff,
+    GitFileLogEntry, GitHubChecksResponse, GitHubIssueDetail, GitHubIssuesResponse,
+    GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestDiff, GitHubPullRequestDraft,
+    GitHubPullRequestReview, GitHubPullRequestsResponse, GitHubReviewDraft, GitHubReviewPreview,
+    GitLogEntry, GitLogResponse, GitStashEntry, WorkspaceEntry,
 };
-use crate::utils::normalize_git_path;
+
+const MAX_ISSUE_PROMPT_COMMENTS: usize = 20;
+
+const MAX_CHECK_LOGS: usize = 5;
+
+const CHECK_LOG_TAIL_LINES: usize = 60;
+
+pub(crate) const PULL_REQUEST_FIELDS: &str =
+    "number,title,url,updatedAt,createdAt,body,headRefName,baseRefName,isDraft,author";
 
 async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String> {
     let output = Command::new("git")
@@ -42,272 +66,390 @@ async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String>
     Err(detail.to_string())
 }
 
-fn status_for_index(status: Status) -> Option<&'static str> {
-    if status.contains(Status::INDEX_NEW) {
-        Some("A")
-    } else if status.contains(Status::INDEX_MODIFIED) {
-        Some("M")
-    } else if status.contains(Status::INDEX_DELETED) {
-        Some("D")
-    } else if status.contains(Status::INDEX_RENAMED) {
-        Some("R")
-    } else if status.contains(Status::INDEX_TYPECHANGE) {
-        Some("T")
+pub(crate) async fn run_gh_command(repo_root: &Path, args: &[&str]) -> Result<Vec<u8>, String> {
+    run_gh_command_with_input(repo_root, args, None).await
+}
+
+pub(crate) async fn run_gh_command_with_input(
+    repo_root: &Path,
+    args: &[&str],
+    input: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("gh")
+        .args(args)
+        .current_dir(repo_root)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input)
+            .await
+            .map_err(|e| format!("Failed to write gh input: {e}"))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+
+    if output.status.success() {
+        return Ok(output.stdout);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
     } else {
-        None
+        stderr.trim()
+    };
+    if detail.is_empty() {
+        return Err("GitHub CLI command failed.".to_string());
     }
+    Err(detail.to_string())
 }
 
-fn status_for_workdir(status: Status) -> Option<&'static str> {
-    if status.contains(Status::WT_NEW) {
-        Some("A")
-    } else if status.contains(Status::WT_MODIFIED) {
-        Some("M")
-    } else if status.contains(Status::WT_DELETED) {
-        Some("D")
-    } else if status.contains(Status::WT_RENAMED) {
-        Some("R")
-    } else if status.contains(Status::WT_TYPECHANGE) {
-        Some("T")
-    } else {
-        None
+pub(crate) async fn fetch_pull_request_diff(
+    repo_root: &Path,
+    repo_name: &str,
+    pr_number: u64,
+) -> Result<Vec<GitHubPullRequestDiff>, String> {
+    let output = run_gh_command(
+        repo_root,
+        &[
+            "pr",
+            "diff",
+            &pr_number.to_string(),
+            "--repo",
+            repo_name,
+            "--color",
+            "never",
+        ],
+    )
+    .await?;
+    Ok(parse_pr_diff(&String::from_utf8_lossy(&output)))
+}
+
+pub(crate) async fn diff_limits(state: &AppState) -> DiffLimits {
+    let settings = state.app_settings.lock().await;
+    DiffLimits {
+        max_file_bytes: settings.diff_max_file_bytes,
+        max_total_bytes: settings.diff_max_total_bytes,
+        collapse_generated: true,
     }
 }
 
-fn github_repo_from_path(path: &Path) -> Result<String, String> {
-    let repo = Repository::open(path).map_err(|e| e.to_string())?;
-    let remotes = repo.remotes().map_err(|e| e.to_string())?;
-    let name = if remotes.iter().any(|remote| remote == Some("origin")) {
-        "origin".to_string()
+async fn take_conflict_side(repo_root: &Path, path: &str, ours: bool) -> Result<(), String> {
+    check_repo_relative_path(path)?;
+    let (rebasing, side_exists) = {
+        let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+        let conflict = list_conflicts(&repo)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|conflict| conflict.path == path)
+            .ok_or("Path is not conflicted.")?;
+        let side_exists = if ours {
+            conflict.ours_sha.is_some()
+        } else {
+            conflict.theirs_sha.is_some()
+        };
+        (rebase_in_progress(&repo), side_exists)
+    };
+    if !side_exists {
+        return run_git_command(repo_root, &["rm", "-f", "--", path]).await;
+    }
+    // Mid-rebase git's `--ours` is the branch being rebased onto.
+    let side_flag = if ours != rebasing {
+        "--ours"
     } else {
-        remotes
-            .iter()
-            .flatten()
-            .next()
-            .unwrap_or("")
-            .to_string()
+        "--theirs"
     };
-    if name.is_empty() {
-        return Err("No git remote configured.".to_string());
+    run_git_command(repo_root, &["checkout", side_flag, "--", path]).await?;
+    run_git_command(repo_root, &["add", "--", path]).await
+}
+
+fn conflict_resolution_prompt(
+    repo_root: &Path,
+    workspace_path: &str,
+    conflicts: &[GitConflictFile],
+) -> String {
+    let mut prompt = String::from(
+        "Resolve the merge conflicts in this repository. Only edit the conflicted files listed below.\n",
+    );
+    if repo_root != Path::new(workspace_path) {
+        prompt.push_str(&format!(
+            "Paths are relative to the git root at {}.\n",
+            repo_root.display()
+        ));
     }
-    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
-    let remote_url = remote
-        .url()
-        .ok_or("Remote has no URL configured.")?;
-    parse_github_repo(remote_url).ok_or("Remote is not a GitHub repository.".to_string())
+    prompt.push_str("\nConflicted files:\n");
+    for conflict in conflicts {
+        prompt.push_str(&format!("- {} ({})\n", conflict.path, conflict.kind));
+    }
+    prompt.push_str(
+        "\nFor each file, combine the intent of both sides, remove every conflict marker, and stage the result with `git add <path>`. Do not commit. Summarize how each conflict was resolved.",
+    );
+    prompt
 }
 
-fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
-    let mut entries = Vec::new();
-    let mut current_lines: Vec<&str> = Vec::new();
-    let mut current_old_path: Option<String> = None;
-    let mut current_new_path: Option<String> = None;
-    let mut current_status: Option<String> = None;
-
-    let finalize = |lines: &Vec<&str>,
-                        old_path: &Option<String>,
-                        new_path: &Option<String>,
-                        status: &Option<String>,
-                        results: &mut Vec<GitHubPullRequestDiff>| {
-        if lines.is_empty() {
-            return;
+fn pull_request_draft_prompt(branch: &str, base: &str, commits: &[GitLogEntry]) -> String {
+    let mut prompt = format!(
+        "Draft a GitHub pull request for the branch `{branch}` targeting `{base}`. Use what we did in this thread and the changes on the branch (`git diff {base}...HEAD`).\n"
+    );
+    if !commits.is_empty() {
+        prompt.push_str("\nCommits on the branch:\n");
+        for commit in commits {
+            prompt.push_str(&format!(
+                "- {} {}\n",
+                &commit.sha[..7.min(commit.sha.len())],
+                commit.summary
+            ));
         }
-        let diff_text = lines.join("\n");
-        if diff_text.trim().is_empty() {
-            return;
+    }
+    prompt.push_str(
+        "\nReply with the title on the first line, a blank line, then the body in Markdown: a short summary of the change, the notable implementation details, and how it was tested. Do not modify any files and do not create the pull request yourself.",
+    );
+    prompt
+}
+
+/// The first message of a thread started from an issue.
+pub(crate) fn github_issue_prompt(issue: &GitHubIssueDetail) -> String {
+    let mut prompt = format!(
+        "Work on GitHub issue #{}: {}\n{}\n",
+        issue.number, issue.title, issue.url
+    );
+    if !issue.labels.is_empty() {
+        let labels = issue
+            .labels
+            .iter()
+            .map(|label| label.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        prompt.push_str(&format!("Labels: {labels}\n"));
+    }
+    let body = issue.body.trim();
+    prompt.push('\n');
+    prompt.push_str(if body.is_empty() {
+        "(The issue has no description.)"
+    } else {
+        body
+    });
+    prompt.push('\n');
+    // Keep the most recent discussion; long threads would crowd out the issue itself.
+    let skipped = issue.comments.len().saturating_sub(MAX_ISSUE_PROMPT_COMMENTS);
+    if !issue.comments.is_empty() {
+        prompt.push_str("\nComments:\n");
+        if skipped > 0 {
+            prompt.push_str(&format!("({skipped} earlier comments omitted)\n"));
         }
-        let status_value = status.clone().unwrap_or_else(|| "M".to_string());
-        let path = if status_value == "D" {
-            old_path.clone().unwrap_or_default()
-        } else {
-            new_path.clone().or_else(|| old_path.clone()).unwrap_or_default()
-        };
-        if path.is_empty() {
-            return;
+        for comment in issue.comments.iter().skip(skipped) {
+            let author = comment
+                .author
+                .as_ref()
+                .map(|author| author.login.as_str())
+                .unwrap_or("unknown");
+            prompt.push_str(&format!(
+                "\n@{author} ({}):\n{}\n",
+                comment.created_at,
+                comment.body.trim()
+            ));
         }
-        results.push(GitHubPullRequestDiff {
-            path: normalize_git_path(&path),
-            status: status_value,
-            diff: diff_text,
-        });
-    };
+    }
+    prompt.push_str(
+        "\nInvestigate the codebase, propose a plan, and then implement a fix for this issue in this worktree.",
+    );
+    prompt
+}
 
-    for line in diff.lines() {
-        if line.starts_with("diff --git ") {
-            finalize(
-                &current_lines,
-                &current_old_path,
-                &current_new_path,
-                &current_status,
-                &mut entries,
-            );
-            current_lines = vec![line];
-            current_old_path = None;
-            current_new_path = None;
-            current_status = None;
-
-            let rest = line.trim_start_matches("diff --git ").trim();
-            let mut parts = rest.split_whitespace();
-            let old_part = parts.next().unwrap_or("").trim_start_matches("a/");
-            let new_part = parts.next().unwrap_or("").trim_start_matches("b/");
-            if !old_part.is_empty() {
-                current_old_path = Some(old_part.to_string());
-            }
-            if !new_part.is_empty() {
-                current_new_path = Some(new_part.to_string());
-            }
-            continue;
+pub(crate) async fn fetch_github_issue(
+    state: &AppState,
+    repo_root: &Path,
+    issue_number: u64,
+) -> Result<GitHubIssueDetail, String> {
+    GitHubForge::connect(repo_root, state)
+        .await?
+        .issue(issue_number)
+        .await
+}
+
+pub(crate) async fn fetch_github_pull_request(
+    state: &AppState,
+    repo_root: &Path,
+    pr_number: u64,
+) -> Result<GitHubPullRequest, String> {
+    GitHubForge::connect(repo_root, state)
+        .await?
+        .pull_request(pr_number)
+        .await
+}
+
+/// Fetches check runs and commit statuses for a pull request head or a ref
+/// (HEAD by default), with log excerpts for failing GitHub Actions jobs.
+async fn fetch_github_checks(
+    state: &AppState,
+    repo_root: &Path,
+    git_ref: Option<String>,
+    pr_number: Option<u64>,
+) -> Result<GitHubChecksResponse, String> {
+    let forge = GitHubForge::connect(repo_root, state).await?;
+    let sha = match pr_number {
+        Some(pr_number) => forge.pull_request_head(pr_number).await?,
+        None => {
+            let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+            let spec = git_ref.as_deref().unwrap_or("HEAD");
+            let object = repo.revparse_single(spec).map_err(|e| e.to_string())?;
+            let commit = object.peel_to_commit().map_err(|e| e.to_string())?;
+            commit.id().to_string()
         }
-        if line.starts_with("new file mode ") {
-            current_status = Some("A".to_string());
-        } else if line.starts_with("deleted file mode ") {
-            current_status = Some("D".to_string());
-        } else if line.starts_with("rename from ") {
-            current_status = Some("R".to_string());
-            let path = line.trim_start_matches("rename from ").trim();
-            if !path.is_empty() {
-                current_old_path = Some(path.to_string());
-            }
-        } else if line.starts_with("rename to ") {
-            current_status = Some("R".to_string());
-            let path = line.trim_start_matches("rename to ").trim();
-            if !path.is_empty() {
-                current_new_path = Some(path.to_string());
-            }
+    };
+    let (mut check_runs, statuses) = forge.checks(&sha).await?;
+
+    // Only GitHub Actions exposes job logs; a check run id there is the job id.
+    for run in check_runs
+        .iter_mut()
+        .filter(|run| check_run_failed(run) && run.app_slug.as_deref() == Some("github-actions"))
+        .take(MAX_CHECK_LOGS)
+    {
+        if let Some(log) = forge.job_log(run.id).await {
+            run.log_excerpt = Some(failing_log_excerpt(&log, CHECK_LOG_TAIL_LINES));
         }
-        current_lines.push(line);
     }
 
-    finalize(
-        &current_lines,
-        &current_old_path,
-        &current_new_path,
-        &current_status,
-        &mut entries,
-    );
+    let state = combined_check_state(&check_runs, &statuses).to_string();
+    Ok(GitHubChecksResponse {
+        sha,
+        state,
+        check_runs,
+        statuses,
+    })
+}
 
-    entries
+fn check_failures_prompt(checks: &GitHubChecksResponse) -> Option<String> {
+    let failed_runs = checks
+        .check_runs
+        .iter()
+        .filter(|run| check_run_failed(run))
+        .collect::<Vec<_>>();
+    let failed_statuses = checks
+        .statuses
+        .iter()
+        .filter(|status| commit_status_failed(status))
+        .collect::<Vec<_>>();
+    if failed_runs.is_empty() && failed_statuses.is_empty() {
+        return None;
+    }
+    let mut prompt = format!(
+        "CI is failing for commit {}. Find the cause and fix it.\n",
+        checks.sha
+    );
+    for run in failed_runs {
+        prompt.push_str(&format!(
+            "\n## {} ({})\n",
+            run.name,
+            run.conclusion.as_deref().unwrap_or("failed")
+        ));
+        if let Some(url) = &run.url {
+            prompt.push_str(&format!("{url}\n"));
+        }
+        if let Some(excerpt) = &run.log_excerpt {
+            prompt.push_str(&format!("```\n{excerpt}\n```\n"));
+        }
+    }
+    for status in failed_statuses {
+        prompt.push_str(&format!("\n## {} ({})\n", status.context, status.state));
+        if let Some(description) = &status.description {
+            prompt.push_str(&format!("{description}\n"));
+        }
+        if let Some(url) = &status.url {
+            prompt.push_str(&format!("{url}\n"));
+        }
+    }
+    prompt.push_str(
+        "\nReproduce the failure locally where possible, fix the underlying problem rather than the test, and summarize what was wrong.",
+    );
+    Some(prompt)
 }
+
 #[tauri::command]
 pub(crate) async fn get_git_status(
     workspace_id: String,
     state: State<'_, AppState>,
 ) -> Result<serde_json::Value, String> {
-    let workspaces = state.workspaces.lock().await;
-    let entry = workspaces
-        .get(&workspace_id)
-        .ok_or("workspace not found")?
-        .clone();
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
 
     let repo_root = resolve_git_root(&entry)?;
+    let watcher = state
+        .git_status_watchers
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .filter(|watcher| watcher.repo_root() == repo_root.as_path());
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let entries = match watcher.and_then(|watcher| watcher.cached_entries()) {
+        Some(entries) => entries,
+        None => collect_status_entries(&repo, None).map_err(|e| e.to_string())?,
+    };
+    status_payload(&repo, &entries).map_err(|e| e.to_string())
+}
 
-    let branch_name = repo
-        .head()
-        .ok()
-        .and_then(|head| head.shorthand().map(|s| s.to_string()))
-        .unwrap_or_else(|| "unknown".to_string());
-
-    let mut status_options = StatusOptions::new();
-    status_options
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
-        .renames_head_to_index(true)
-        .renames_index_to_workdir(true)
-        .include_ignored(false);
-
-    let statuses = repo
-        .statuses(Some(&mut status_options))
-        .map_err(|e| e.to_string())?;
-
-    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
-
-    let mut files = Vec::new();
-    let mut staged_files = Vec::new();
-    let mut unstaged_files = Vec::new();
-    let mut total_additions = 0i64;
-    let mut total_deletions = 0i64;
-    for entry in statuses.iter() {
-        let path = entry.path().unwrap_or("");
-        if path.is_empty() {
-            continue;
-        }
-        let status = entry.status();
-        let normalized_path = normalize_git_path(path);
-        let include_index = status.intersects(
-            Status::INDEX_NEW
-                | Status::INDEX_MODIFIED
-                | Status::INDEX_DELETED
-                | Status::INDEX_RENAMED
-                | Status::INDEX_TYPECHANGE,
-        );
-        let include_workdir = status.intersects(
-            Status::WT_NEW
-                | Status::WT_MODIFIED
-                | Status::WT_DELETED
-                | Status::WT_RENAMED
-                | Status::WT_TYPECHANGE,
-        );
-        let mut combined_additions = 0i64;
-        let mut combined_deletions = 0i64;
-
-        if include_index {
-            let (additions, deletions) =
-                diff_stats_for_path(&repo, head_tree.as_ref(), path, true, false)
-                    .map_err(|e| e.to_string())?;
-            if let Some(status_str) = status_for_index(status) {
-                staged_files.push(GitFileStatus {
-                    path: normalized_path.clone(),
-                    status: status_str.to_string(),
-                    additions,
-                    deletions,
-                });
-            }
-            combined_additions += additions;
-            combined_deletions += deletions;
-            total_additions += additions;
-            total_deletions += deletions;
-        }
-
-        if include_workdir {
-            let (additions, deletions) =
-                diff_stats_for_path(&repo, head_tree.as_ref(), path, false, true)
-                    .map_err(|e| e.to_string())?;
-            if let Some(status_str) = status_for_workdir(status) {
-                unstaged_files.push(GitFileStatus {
-                    path: normalized_path.clone(),
-                    status: status_str.to_string(),
-                    additions,
-                    deletions,
-                });
-            }
-            combined_additions += additions;
-            combined_deletions += deletions;
-            total_additions += additions;
-            total_deletions += deletions;
-        }
-
-        if include_index || include_workdir {
-            let status_str = status_for_workdir(status)
-                .or_else(|| status_for_index(status))
-                .unwrap_or("--");
-            files.push(GitFileStatus {
-                path: normalized_path,
-                status: status_str.to_string(),
-                additions: combined_additions,
-                deletions: combined_deletions,
-            });
-        }
+/// Starts watching the workspace's repository unless a watcher for the same
+/// root is already running.
+pub(crate) async fn start_git_status_watcher(
+    state: &AppState,
+    entry: &WorkspaceEntry,
+    app: AppHandle,
+) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    let mut watchers = state.git_status_watchers.lock().await;
+    if watchers
+        .get(&entry.id)
+        .is_some_and(|watcher| watcher.repo_root() == repo_root.as_path())
+    {
+        return Ok(());
     }
+    let watcher = GitStatusWatcher::start(entry.id.clone(), repo_root, TauriEventSink::new(app))?;
+    watchers.insert(entry.id.clone(), Arc::new(watcher));
+    Ok(())
+}
 
-    Ok(json!({
-        "branchName": branch_name,
-        "files": files,
-        "stagedFiles": staged_files,
-        "unstagedFiles": unstaged_files,
-        "totalAdditions": total_additions,
-        "totalDeletions": total_deletions,
-    }))
+#[tauri::command]
+pub(crate) async fn watch_git_status(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    start_git_status_watcher(&state, &entry, app).await
+}
+
+#[tauri::command]
+pub(crate) async fn unwatch_git_status(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.git_status_watchers.lock().await.remove(&workspace_id);
+    Ok(())
 }
 
 #[tauri::command]
@@ -400,6 +542,37 @@ pub(crate) async fn get_git_diffs(
     workspace_id: String,
     state: State<'_, AppState>,
 ) -> Result<Vec<GitFileDiff>, String> {
+    let limits = diff_limits(&state).await;
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+
+    let repo_root = resolve_git_root(&entry)?;
+    let watcher = state
+        .git_status_watchers
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .filter(|watcher| watcher.repo_root() == repo_root.as_path());
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    if let Some(watcher) = watcher {
+        return watcher.file_diffs(&repo, &limits);
+    }
+    let diff = workdir_diff(&repo, None).map_err(|e| e.to_string())?;
+    Ok(diff_to_file_diffs(&repo, &diff, &limits))
+}
+
+#[tauri::command]
+pub(crate) async fn get_git_file_diff(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<GitFileDiff, String> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
@@ -408,56 +581,11 @@ pub(crate) async fn get_git_diffs(
 
     let repo_root = resolve_git_root(&entry)?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-    let head_tree = repo
-        .head()
-        .ok()
-        .and_then(|head| head.peel_to_tree().ok());
-
-    let mut options = DiffOptions::new();
-    options
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
-        .show_untracked_content(true);
-
-    let diff = match head_tree.as_ref() {
-        Some(tree) => repo
-            .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
-            .map_err(|e| e.to_string())?,
-        None => repo
-            .diff_tree_to_workdir_with_index(None, Some(&mut options))
-            .map_err(|e| e.to_string())?,
-    };
-
-    let mut results = Vec::new();
-    for (index, delta) in diff.deltas().enumerate() {
-        let path = delta
-            .new_file()
-            .path()
-            .or_else(|| delta.old_file().path());
-        let Some(path) = path else {
-            continue;
-        };
-        let patch = match git2::Patch::from_diff(&diff, index) {
-            Ok(patch) => patch,
-            Err(_) => continue,
-        };
-        let Some(mut patch) = patch else {
-            continue;
-        };
-        let content = match diff_patch_to_string(&mut patch) {
-            Ok(content) => content,
-            Err(_) => continue,
-        };
-        if content.trim().is_empty() {
-            continue;
-        }
-        results.push(GitFileDiff {
-            path: normalize_git_path(path.to_string_lossy().as_ref()),
-            diff: content,
-        });
-    }
-
-    Ok(results)
+    let diff = workdir_diff(&repo, Some(std::slice::from_ref(&path))).map_err(|e| e.to_string())?;
+    diff_to_file_diffs(&repo, &diff, &DiffLimits::unlimited())
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No changes for {path}."))
 }
 
 #[tauri::command]
@@ -569,10 +697,13 @@ pub(crate) async fn get_git_log(
 }
 
 #[tauri::command]
-pub(crate) async fn get_git_remote(
+pub(crate) async fn get_git_blame(
     workspace_id: String,
+    path: String,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
     state: State<'_, AppState>,
-) -> Result<Option<String>, String> {
+) -> Result<Vec<GitBlameHunk>, String> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
@@ -581,29 +712,20 @@ pub(crate) async fn get_git_remote(
 
     let repo_root = resolve_git_root(&entry)?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-    let remotes = repo.remotes().map_err(|e| e.to_string())?;
-    let name = if remotes.iter().any(|remote| remote == Some("origin")) {
-        "origin".to_string()
-    } else {
-        remotes
-            .iter()
-            .flatten()
-            .next()
-            .unwrap_or("")
-            .to_string()
+    let line_range = match (start_line, end_line) {
+        (None, None) => None,
+        (start, end) => Some((start.unwrap_or(1), end.unwrap_or(usize::MAX))),
     };
-    if name.is_empty() {
-        return Ok(None);
-    }
-    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
-    Ok(remote.url().map(|url| url.to_string()))
+    blame_file(&repo, &repo_root, &path, line_range).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub(crate) async fn get_github_issues(
+pub(crate) async fn get_git_file_log(
     workspace_id: String,
+    path: String,
+    limit: Option<usize>,
     state: State<'_, AppState>,
-) -> Result<GitHubIssuesResponse, String> {
+) -> Result<Vec<GitFileLogEntry>, String> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
@@ -611,69 +733,15 @@ pub(crate) async fn get_github_issues(
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
-    let repo_name = github_repo_from_path(&repo_root)?;
-
-    let output = Command::new("gh")
-        .args([
-            "issue",
-            "list",
-            "--repo",
-            &repo_name,
-            "--limit",
-            "50",
-            "--json",
-            "number,title,url,updatedAt",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run gh: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
-        }
-        return Err(detail.to_string());
-    }
-
-    let issues: Vec<GitHubIssue> =
-        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
-
-    let search_query = format!("repo:{repo_name} is:issue is:open");
-    let search_query = search_query.replace(' ', "+");
-    let total = match Command::new("gh")
-        .args([
-            "api",
-            &format!("/search/issues?q={search_query}"),
-            "--jq",
-            ".total_count",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-    {
-        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
-            .trim()
-            .parse::<usize>()
-            .unwrap_or(issues.len()),
-        _ => issues.len(),
-    };
-
-    Ok(GitHubIssuesResponse { total, issues })
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    file_history(&repo, &path, limit.unwrap_or(40)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub(crate) async fn get_github_pull_requests(
+pub(crate) async fn get_git_remote(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<GitHubPullRequestsResponse, String> {
+) -> Result<Option<String>, String> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
@@ -681,67 +749,73 @@ pub(crate) async fn get_github_pull_requests(
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
-    let repo_name = github_repo_from_path(&repo_root)?;
-
-    let output = Command::new("gh")
-        .args([
-            "pr",
-            "list",
-            "--repo",
-            &repo_name,
-            "--state",
-            "open",
-            "--limit",
-            "50",
-            "--json",
-            "number,title,url,updatedAt,createdAt,body,headRefName,baseRefName,isDraft,author",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run gh: {e}"))?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let Some(name) = default_remote_name(&repo) else {
+        return Ok(None);
+    };
+    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
+    Ok(remote.url().map(|url| url.to_string()))
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
-        }
-        return Err(detail.to_string());
-    }
+#[tauri::command]
+pub(crate) async fn get_github_issues(
+    workspace_id: String,
+    query: Option<ForgeListQuery>,
+    state: State<'_, AppState>,
+) -> Result<GitHubIssuesResponse, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let forge = GitHubForge::connect(&repo_root, &state).await?;
+    let query = query.unwrap_or_default();
+    cached_list(&state.forge_cache, forge.remote(), "issues", &query, || {
+        forge.list_issues(&query)
+    })
+    .await
+}
 
-    let pull_requests: Vec<GitHubPullRequest> =
-        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
-
-    let search_query = format!("repo:{repo_name} is:pr is:open");
-    let search_query = search_query.replace(' ', "+");
-    let total = match Command::new("gh")
-        .args([
-            "api",
-            &format!("/search/issues?q={search_query}"),
-            "--jq",
-            ".total_count",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-    {
-        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
-            .trim()
-            .parse::<usize>()
-            .unwrap_or(pull_requests.len()),
-        _ => pull_requests.len(),
+#[tauri::command]
+pub(crate) async fn get_github_issue(
+    workspace_id: String,
+    issue_number: u64,
+    state: State<'_, AppState>,
+) -> Result<GitHubIssueDetail, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
     };
+    let repo_root = resolve_git_root(&entry)?;
+    fetch_github_issue(&state, &repo_root, issue_number).await
+}
 
-    Ok(GitHubPullRequestsResponse {
-        total,
-        pull_requests,
+#[tauri::command]
+pub(crate) async fn get_github_pull_requests(
+    workspace_id: String,
+    query: Option<ForgeListQuery>,
+    state: State<'_, AppState>,
+) -> Result<GitHubPullRequestsResponse, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let forge = GitHubForge::connect(&repo_root, &state).await?;
+    let query = query.unwrap_or_default();
+    cached_list(&state.forge_cache, forge.remote(), "merge_requests", &query, || {
+        forge.list_merge_requests(&query)
     })
+    .await
 }
 
 #[tauri::command]
@@ -750,46 +824,72 @@ pub(crate) async fn get_github_pull_request_diff(
     pr_number: u64,
     state: State<'_, AppState>,
 ) -> Result<Vec<GitHubPullRequestDiff>, String> {
-    let workspaces = state.workspaces.lock().await;
-    let entry = workspaces
-        .get(&workspace_id)
-        .ok_or("workspace not found")?
-        .clone();
-
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
     let repo_root = resolve_git_root(&entry)?;
-    let repo_name = github_repo_from_path(&repo_root)?;
-
-    let output = Command::new("gh")
-        .args([
-            "pr",
-            "diff",
-            &pr_number.to_string(),
-            "--repo",
-            &repo_name,
-            "--color",
-            "never",
-        ])
-        .current_dir(&repo_root)
-        .output()
+    GitHubForge::connect(&repo_root, &state)
+        .await?
+        .merge_request_diff(pr_number)
         .await
-        .map_err(|e| format!("Failed to run gh: {e}"))?;
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
-        }
-        return Err(detail.to_string());
-    }
+#[tauri::command]
+pub(crate) async fn preview_github_pull_request_review(
+    workspace_id: String,
+    pr_number: u64,
+    review: GitHubReviewDraft,
+    state: State<'_, AppState>,
+) -> Result<GitHubReviewPreview, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let diffs = GitHubForge::connect(&repo_root, &state)
+        .await?
+        .merge_request_diff(pr_number)
+        .await?;
+    Ok(build_review_preview(&review, &diffs))
+}
 
-    let diff_text = String::from_utf8_lossy(&output.stdout);
-    Ok(parse_pr_diff(&diff_text))
+#[tauri::command]
+pub(crate) async fn submit_github_pull_request_review(
+    workspace_id: String,
+    pr_number: u64,
+    review: GitHubReviewDraft,
+    digest: String,
+    state: State<'_, AppState>,
+) -> Result<GitHubPullRequestReview, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let forge = GitHubForge::connect(&repo_root, &state).await?;
+    // Rebuild the preview so we only send exactly what was shown, against the
+    // current diff.
+    let diffs = forge.merge_request_diff(pr_number).await?;
+    let preview = build_review_preview(&review, &diffs);
+    if preview.digest.as_deref() != Some(digest.as_str()) {
+        return Err(
+            "The review changed since it was previewed. Preview it again before posting."
+                .to_string(),
+        );
+    }
+    forge
+        .submit_review(pr_number, &review_request_body(&preview))
+        .await
 }
 
 #[tauri::command]
@@ -798,44 +898,174 @@ pub(crate) async fn get_github_pull_request_comments(
     pr_number: u64,
     state: State<'_, AppState>,
 ) -> Result<Vec<GitHubPullRequestComment>, String> {
-    let workspaces = state.workspaces.lock().await;
-    let entry = workspaces
-        .get(&workspace_id)
-        .ok_or("workspace not found")?
-        .clone();
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    GitHubForge::connect(&repo_root, &state)
+        .await?
+        .merge_request_comments(pr_number)
+        .await
+}
+
+#[tauri::command]
+pub(crate) async fn create_github_pull_request(
+    workspace_id: String,
+    title: String,
+    body: Option<String>,
+    base: Option<String>,
+    draft: Option<bool>,
+    reviewers: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<GitHubPullRequest, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err("Pull request title is required.".to_string());
+    }
 
     let repo_root = resolve_git_root(&entry)?;
-    let repo_name = github_repo_from_path(&repo_root)?;
+    let forge = GitHubForge::connect(&repo_root, &state).await?;
+    let (remote, branch, base) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let remote = default_remote_name(&repo).ok_or("No git remote configured.")?;
+        let head = repo.head().map_err(|e| e.to_string())?;
+        if !head.is_branch() {
+            return Err("Check out a branch before creating a pull request.".to_string());
+        }
+        let branch = head.shorthand().unwrap_or("").to_string();
+        let base = base
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| default_base_branch(&repo, &remote));
+        (remote, branch, base)
+    };
+    if branch == base {
+        return Err(format!(
+            "Branch {branch} is the base branch; create the pull request from a feature branch."
+        ));
+    }
 
-    let comments_endpoint =
-        format!("/repos/{repo_name}/issues/{pr_number}/comments?per_page=30");
-    let jq_filter = r#"[.[] | {id, body, createdAt: .created_at, url: .html_url, author: (if .user then {login: .user.login} else null end)}]"#;
+    run_git_command(&repo_root, &["push", "--set-upstream", &remote, &branch]).await?;
+
+    let reviewers = reviewers
+        .unwrap_or_default()
+        .into_iter()
+        .map(|reviewer| reviewer.trim().trim_start_matches('@').to_string())
+        .filter(|reviewer| !reviewer.is_empty())
+        .collect::<Vec<_>>();
+    let pull_request = forge
+        .create_pull_request(
+            &branch,
+            &base,
+            &title,
+            &body.unwrap_or_default(),
+            draft.unwrap_or(false),
+            &reviewers,
+        )
+        .await;
+    state.forge_cache.lock().await.invalidate(forge.remote());
+    pull_request
+}
 
-    let output = Command::new("gh")
-        .args(["api", &comments_endpoint, "--jq", jq_filter])
-        .current_dir(&repo_root)
-        .output()
+#[tauri::command]
+pub(crate) async fn draft_github_pull_request_with_agent(
+    workspace_id: String,
+    thread_id: Option<String>,
+    base: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<GitHubPullRequestDraft, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let prompt = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let remote = default_remote_name(&repo);
+        let head = repo.head().map_err(|e| e.to_string())?;
+        let branch = head.shorthand().unwrap_or("HEAD").to_string();
+        let base = base
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| default_base_branch(&repo, remote.as_deref().unwrap_or("origin")));
+        let commits =
+            commits_since_base(&repo, remote.as_deref(), &base, 50).map_err(|e| e.to_string())?;
+        pull_request_draft_prompt(&branch, &base, &commits)
+    };
+    let session = state
+        .sessions
+        .lock()
         .await
-        .map_err(|e| format!("Failed to run gh: {e}"))?;
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not connected")?;
+    let (thread_id, reply) = run_read_only_agent_turn(&state, &session, thread_id, prompt).await?;
+    let (title, body) = parse_pull_request_draft(&reply)
+        .ok_or("The agent's reply did not contain a pull request title.")?;
+    Ok(GitHubPullRequestDraft {
+        thread_id,
+        title,
+        body,
+    })
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
-        }
-        return Err(detail.to_string());
-    }
+#[tauri::command]
+pub(crate) async fn get_github_checks(
+    workspace_id: String,
+    git_ref: Option<String>,
+    pr_number: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<GitHubChecksResponse, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
 
-    let comments: Vec<GitHubPullRequestComment> =
-        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+    let repo_root = resolve_git_root(&entry)?;
+    fetch_github_checks(&state, &repo_root, git_ref, pr_number).await
+}
 
-    Ok(comments)
+#[tauri::command]
+pub(crate) async fn send_github_check_failures_to_agent(
+    workspace_id: String,
+    thread_id: Option<String>,
+    git_ref: Option<String>,
+    pr_number: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let checks = fetch_github_checks(&state, &repo_root, git_ref, pr_number).await?;
+    let prompt = check_failures_prompt(&checks).ok_or("No failing checks to send.")?;
+    let session = state
+        .sessions
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not connected")?;
+    start_agent_turn(&state, &session, thread_id, prompt).await
 }
 
 #[tauri::command]
@@ -907,3 +1137,229 @@ pub(crate) async fn create_git_branch(
         .map_err(|e| e.to_string())?;
     checkout_branch(&repo, &name).map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub(crate) async fn list_git_stashes(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitStashEntry>, String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    let mut repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    list_stashes(&mut repo).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn stash_git_changes(
+    workspace_id: String,
+    message: Option<String>,
+    include_untracked: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<GitStashEntry, String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    let mut repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    push_stash(
+        &mut repo,
+        message.as_deref(),
+        include_untracked.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+pub(crate) async fn apply_git_stash(
+    workspace_id: String,
+    index: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    let mut repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    apply_stash(&mut repo, index, false)
+}
+
+#[tauri::command]
+pub(crate) async fn pop_git_stash(
+    workspace_id: String,
+    index: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    let mut repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    apply_stash(&mut repo, index, true)
+}
+
+#[tauri::command]
+pub(crate) async fn drop_git_stash(
+    workspace_id: String,
+    index: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    let mut repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    repo.stash_drop(index).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn get_git_stash_diff(
+    workspace_id: String,
+    index: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitFileDiff>, String> {
+    let limits = diff_limits(&state).await;
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    let mut repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let stash = list_stashes(&mut repo)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|stash| stash.index == index)
+        .ok_or("stash not found")?;
+    let oid = git2::Oid::from_str(&stash.sha).map_err(|e| e.to_string())?;
+    stash_diffs(&repo, oid, &limits).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn get_git_conflicts(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitConflictFile>, String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    list_conflicts(&repo).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn get_git_conflict_versions(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<GitConflictVersions, String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let conflict = list_conflicts(&repo)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|conflict| conflict.path == path)
+        .ok_or("Path is not conflicted.")?;
+    conflict_versions(&repo, &conflict).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn take_git_conflict_ours(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    take_conflict_side(&repo_root, &path, true).await
+}
+
+#[tauri::command]
+pub(crate) async fn take_git_conflict_theirs(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    take_conflict_side(&repo_root, &path, false).await
+}
+
+#[tauri::command]
+pub(crate) async fn mark_git_conflict_resolved(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(&workspace_id)
+        .ok_or("workspace not found")?
+        .clone();
+    let repo_root = resolve_git_root(&entry)?;
+    check_repo_relative_path(&path)?;
+    if let Ok(content) = std::fs::read_to_string(repo_root.join(&path)) {
+        if has_conflict_markers(&content) {
+            return Err(format!("{path} still contains conflict markers."));
+        }
+    }
+    run_git_command(&repo_root, &["add", "-A", "--", &path]).await
+}
+
+#[tauri::command]
+pub(crate) async fn resolve_git_conflicts_with_agent(
+    workspace_id: String,
+    thread_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let conflicts = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        list_conflicts(&repo).map_err(|e| e.to_string())?
+    };
+    if conflicts.is_empty() {
+        return Err("No conflicted files to resolve.".to_string());
+    }
+    let prompt = conflict_resolution_prompt(&repo_root, &entry.path, &conflicts);
+    let session = state
+        .sessions
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not connected")?;
+    start_agent_turn(&state, &session, thread_id, prompt).await
+}
@@ -1,10 +1,18 @@
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 
-use git2::{DiffOptions, Repository, Tree};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use git2::{
+    BlameOptions, BranchType, Diff, DiffFindOptions, DiffOptions, ErrorCode, Index, IndexEntry,
+    Oid, Repository, RepositoryState, Signature, Sort, StashFlags, Status, StatusOptions, Tree,
+};
 use ignore::WalkBuilder;
+use serde_json::{json, Value};
 
-use crate::types::{GitLogEntry, WorkspaceEntry};
+use crate::types::{
+    GitBlameHunk, GitConflictFile, GitConflictVersions, GitDiffKind, GitFileDiff, GitFileLogEntry,
+    GitFileStatus, GitHubPullRequestDiff, GitImageDiff, GitLogEntry, GitStashEntry, WorkspaceEntry,
+};
 use crate::utils::normalize_git_path;
 
 pub(crate) fn commit_to_entry(commit: git2::Commit) -> GitLogEntry {
@@ -19,6 +27,162 @@ pub(crate) fn commit_to_entry(commit: git2::Commit) -> GitLogEntry {
     }
 }
 
+pub(crate) fn blame_file(
+    repo: &Repository,
+    repo_root: &Path,
+    path: &str,
+    line_range: Option<(usize, usize)>,
+) -> Result<Vec<GitBlameHunk>, git2::Error> {
+    let mut options = BlameOptions::new();
+    options.track_copies_same_file(true);
+    let committed = repo.blame_file(Path::new(path), Some(&mut options))?;
+    // Blame the working copy on top so uncommitted edits show up as their own hunks.
+    let with_workdir = std::fs::read(repo_root.join(path))
+        .ok()
+        .map(|contents| committed.blame_buffer(&contents))
+        .transpose()?;
+    let blame = with_workdir.as_ref().unwrap_or(&committed);
+
+    let mut summaries: HashMap<Oid, String> = HashMap::new();
+    let mut results = Vec::new();
+    for hunk in blame.iter() {
+        let start_line = hunk.final_start_line();
+        let line_count = hunk.lines_in_hunk();
+        if let Some((first, last)) = line_range {
+            let hunk_end = start_line + line_count.saturating_sub(1);
+            if hunk_end < first || start_line > last {
+                continue;
+            }
+        }
+        let oid = hunk.final_commit_id();
+        let committed = !oid.is_zero();
+        let summary = if committed {
+            summaries
+                .entry(oid)
+                .or_insert_with(|| {
+                    repo.find_commit(oid)
+                        .ok()
+                        .and_then(|commit| commit.summary().map(|value| value.to_string()))
+                        .unwrap_or_default()
+                })
+                .clone()
+        } else {
+            String::new()
+        };
+        // Uncommitted hunks from the working copy carry no signature.
+        let (author, email, timestamp) = if committed {
+            let signature = hunk.final_signature();
+            (
+                signature.name().unwrap_or("").to_string(),
+                signature.email().unwrap_or("").to_string(),
+                signature.when().seconds(),
+            )
+        } else {
+            ("Not Committed Yet".to_string(), String::new(), 0)
+        };
+        results.push(GitBlameHunk {
+            start_line,
+            line_count,
+            sha: oid.to_string(),
+            author,
+            email,
+            timestamp,
+            summary,
+            orig_path: hunk
+                .path()
+                .map(|value| normalize_git_path(&value.to_string_lossy())),
+            committed,
+        });
+    }
+    Ok(results)
+}
+
+fn rename_source(
+    repo: &Repository,
+    parent_tree: &Tree,
+    tree: &Tree,
+    path: &str,
+) -> Result<Option<String>, git2::Error> {
+    let mut diff = repo.diff_tree_to_tree(Some(parent_tree), Some(tree), None)?;
+    let mut find_options = DiffFindOptions::new();
+    find_options.renames(true);
+    diff.find_similar(Some(&mut find_options))?;
+    for delta in diff.deltas() {
+        if delta.status() != git2::Delta::Renamed {
+            continue;
+        }
+        let new_path = delta.new_file().path().map(|value| value.to_string_lossy());
+        if new_path.as_deref() != Some(path) {
+            continue;
+        }
+        return Ok(delta
+            .old_file()
+            .path()
+            .map(|value| value.to_string_lossy().to_string()));
+    }
+    Ok(None)
+}
+
+/// Commits `file_history` looks through before giving up, so a file with
+/// only a few commits doesn't cost a walk of the whole repository history.
+const FILE_HISTORY_SCAN_LIMIT: usize = 5000;
+
+pub(crate) fn file_history(
+    repo: &Repository,
+    path: &str,
+    limit: usize,
+) -> Result<Vec<GitFileLogEntry>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+    let mut current_path = path.to_string();
+    let mut results = Vec::new();
+    for oid_result in revwalk.take(FILE_HISTORY_SCAN_LIMIT) {
+        let commit = repo.find_commit(oid_result?)?;
+        let tree = commit.tree()?;
+        let entry_id = tree
+            .get_path(Path::new(&current_path))
+            .ok()
+            .map(|entry| entry.id());
+        let parent_tree = if commit.parent_count() > 0 {
+            Some(commit.parent(0)?.tree()?)
+        } else {
+            None
+        };
+        let parent_entry_id = parent_tree
+            .as_ref()
+            .and_then(|parent| parent.get_path(Path::new(&current_path)).ok())
+            .map(|entry| entry.id());
+        if parent_entry_id == entry_id {
+            continue;
+        }
+
+        let touched_path = normalize_git_path(&current_path);
+        if entry_id.is_some() && parent_entry_id.is_none() {
+            // The file appeared in this commit; follow it if it was renamed.
+            // Otherwise keep walking, since it may have been deleted and
+            // re-added at the same path.
+            if let Some(old_path) = parent_tree
+                .as_ref()
+                .map(|parent| rename_source(repo, parent, &tree, &current_path))
+                .transpose()?
+                .flatten()
+            {
+                current_path = old_path;
+            }
+        }
+        results.push(GitFileLogEntry {
+            commit: commit_to_entry(commit),
+            path: touched_path,
+        });
+        if results.len() >= limit {
+            break;
+        }
+    }
+    Ok(results)
+}
+
 pub(crate) fn checkout_branch(repo: &Repository, name: &str) -> Result<(), git2::Error> {
     let refname = format!("refs/heads/{name}");
     repo.set_head(&refname)?;
@@ -28,6 +192,71 @@ pub(crate) fn checkout_branch(repo: &Repository, name: &str) -> Result<(), git2:
     Ok(())
 }
 
+/// The worktree (main checkout included) that has `branch` checked out.
+fn branch_checkout(repo: &Repository, branch: &str) -> Option<PathBuf> {
+    let refname = format!("refs/heads/{branch}");
+    let checked_out = |repo: &Repository| {
+        repo.head()
+            .ok()
+            .is_some_and(|head| head.name() == Some(refname.as_str()))
+    };
+    if checked_out(repo) {
+        return repo.workdir().map(Path::to_path_buf);
+    }
+    let names = repo.worktrees().ok()?;
+    names.iter().flatten().find_map(|name| {
+        let worktree = repo.find_worktree(name).ok()?;
+        let opened = Repository::open_from_worktree(&worktree).ok()?;
+        checked_out(&opened).then(|| worktree.path().to_path_buf())
+    })
+}
+
+/// Points the local `branch` at `target`, creating it or fast-forwarding it.
+/// Refuses to drop commits that only the local branch has, or to move a
+/// branch under a worktree that has it checked out.
+pub(crate) fn create_or_fast_forward_branch(
+    repo: &Repository,
+    branch: &str,
+    target: Oid,
+) -> Result<(), String> {
+    let existing = match repo.find_branch(branch, BranchType::Local) {
+        Ok(existing) => existing,
+        Err(err) if err.code() == ErrorCode::NotFound => {
+            let commit = repo.find_commit(target).map_err(|e| e.to_string())?;
+            repo.branch(branch, &commit, false)
+                .map_err(|e| e.to_string())?;
+            return Ok(());
+        }
+        Err(err) => return Err(err.to_string()),
+    };
+    let current = existing
+        .get()
+        .target()
+        .ok_or_else(|| format!("Branch {branch} is not a direct reference."))?;
+    if current == target {
+        return Ok(());
+    }
+    if !repo
+        .graph_descendant_of(target, current)
+        .map_err(|e| e.to_string())?
+    {
+        return Err(format!(
+            "Local branch {branch} has commits that are not in the update; rename or delete it first."
+        ));
+    }
+    if let Some(path) = branch_checkout(repo, branch) {
+        return Err(format!(
+            "Branch {branch} is checked out at {}; update it there instead.",
+            path.display()
+        ));
+    }
+    existing
+        .into_reference()
+        .set_target(target, "fast-forward")
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 pub(crate) fn diff_stats_for_path(
     repo: &Repository,
     head_tree: Option<&Tree>,
@@ -63,6 +292,188 @@ pub(crate) fn diff_stats_for_path(
     Ok((additions, deletions))
 }
 
+fn status_for_index(status: Status) -> Option<&'static str> {
+    if status.contains(Status::INDEX_NEW) {
+        Some("A")
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        Some("M")
+    } else if status.contains(Status::INDEX_DELETED) {
+        Some("D")
+    } else if status.contains(Status::INDEX_RENAMED) {
+        Some("R")
+    } else if status.contains(Status::INDEX_TYPECHANGE) {
+        Some("T")
+    } else {
+        None
+    }
+}
+
+fn status_for_workdir(status: Status) -> Option<&'static str> {
+    if status.contains(Status::WT_NEW) {
+        Some("A")
+    } else if status.contains(Status::WT_MODIFIED) {
+        Some("M")
+    } else if status.contains(Status::WT_DELETED) {
+        Some("D")
+    } else if status.contains(Status::WT_RENAMED) {
+        Some("R")
+    } else if status.contains(Status::WT_TYPECHANGE) {
+        Some("T")
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct StatusEntry {
+    pub(crate) path: String,
+    pub(crate) status: Status,
+    pub(crate) index_stats: Option<(i64, i64)>,
+    pub(crate) workdir_stats: Option<(i64, i64)>,
+}
+
+/// Collects status entries with line stats, optionally limited to `paths`
+/// (matched literally, so directories include everything beneath them).
+pub(crate) fn collect_status_entries(
+    repo: &Repository,
+    paths: Option<&[String]>,
+) -> Result<Vec<StatusEntry>, git2::Error> {
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true)
+        .include_ignored(false);
+    if let Some(paths) = paths {
+        for path in paths {
+            status_options.pathspec(path);
+        }
+        status_options.disable_pathspec_match(true);
+    }
+
+    let statuses = repo.statuses(Some(&mut status_options))?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+    let mut entries = Vec::new();
+    for entry in statuses.iter() {
+        let path = entry.path().unwrap_or("");
+        if path.is_empty() {
+            continue;
+        }
+        let status = entry.status();
+        let include_index = status.intersects(
+            Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        );
+        let include_workdir = status.intersects(
+            Status::WT_NEW
+                | Status::WT_MODIFIED
+                | Status::WT_DELETED
+                | Status::WT_RENAMED
+                | Status::WT_TYPECHANGE,
+        );
+        if !include_index && !include_workdir {
+            continue;
+        }
+        let index_stats = if include_index {
+            Some(diff_stats_for_path(repo, head_tree.as_ref(), path, true, false)?)
+        } else {
+            None
+        };
+        let workdir_stats = if include_workdir {
+            Some(diff_stats_for_path(repo, head_tree.as_ref(), path, false, true)?)
+        } else {
+            None
+        };
+        entries.push(StatusEntry {
+            path: normalize_git_path(path),
+            status,
+            index_stats,
+            workdir_stats,
+        });
+    }
+    Ok(entries)
+}
+
+pub(crate) fn status_payload(
+    repo: &Repository,
+    entries: &[StatusEntry],
+) -> Result<Value, git2::Error> {
+    let branch_name = repo
+        .head()
+        .ok()
+        .and_then(|head| head.shorthand().map(|s| s.to_string()))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut files = Vec::new();
+    let mut staged_files = Vec::new();
+    let mut unstaged_files = Vec::new();
+    let mut total_additions = 0i64;
+    let mut total_deletions = 0i64;
+    for entry in entries {
+        let mut combined_additions = 0i64;
+        let mut combined_deletions = 0i64;
+
+        if let Some((additions, deletions)) = entry.index_stats {
+            if let Some(status_str) = status_for_index(entry.status) {
+                staged_files.push(GitFileStatus {
+                    path: entry.path.clone(),
+                    status: status_str.to_string(),
+                    additions,
+                    deletions,
+                });
+            }
+            combined_additions += additions;
+            combined_deletions += deletions;
+            total_additions += additions;
+            total_deletions += deletions;
+        }
+
+        if let Some((additions, deletions)) = entry.workdir_stats {
+            if let Some(status_str) = status_for_workdir(entry.status) {
+                unstaged_files.push(GitFileStatus {
+                    path: entry.path.clone(),
+                    status: status_str.to_string(),
+                    additions,
+                    deletions,
+                });
+            }
+            combined_additions += additions;
+            combined_deletions += deletions;
+            total_additions += additions;
+            total_deletions += deletions;
+        }
+
+        let status_str = status_for_workdir(entry.status)
+            .or_else(|| status_for_index(entry.status))
+            .unwrap_or("--");
+        files.push(GitFileStatus {
+            path: entry.path.clone(),
+            status: status_str.to_string(),
+            additions: combined_additions,
+            deletions: combined_deletions,
+        });
+    }
+
+    let conflicted_files = list_conflicts(repo)?;
+    let repo_state = repository_state_label(repo);
+
+    Ok(json!({
+        "branchName": branch_name,
+        "files": files,
+        "stagedFiles": staged_files,
+        "unstagedFiles": unstaged_files,
+        "conflictedFiles": conflicted_files,
+        "repoState": repo_state,
+        "totalAdditions": total_additions,
+        "totalDeletions": total_deletions,
+    }))
+}
+
 pub(crate) fn diff_patch_to_string(patch: &mut git2::Patch) -> Result<String, git2::Error> {
     let buf = patch.to_buf()?;
     Ok(buf
@@ -71,26 +482,717 @@ pub(crate) fn diff_patch_to_string(patch: &mut git2::Patch) -> Result<String, gi
         .unwrap_or_else(|| String::from_utf8_lossy(&buf).to_string()))
 }
 
-pub(crate) fn parse_github_repo(remote_url: &str) -> Option<String> {
-    let trimmed = remote_url.trim();
-    if trimmed.is_empty() {
-        return None;
+const LOCKFILE_NAMES: &[&str] = &[
+    "Cargo.lock",
+    "Gemfile.lock",
+    "Package.resolved",
+    "Pipfile.lock",
+    "Podfile.lock",
+    "bun.lock",
+    "bun.lockb",
+    "composer.lock",
+    "flake.lock",
+    "go.sum",
+    "mix.lock",
+    "npm-shrinkwrap.json",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "pubspec.lock",
+    "uv.lock",
+    "yarn.lock",
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "avif", "bmp", "gif", "ico", "jpeg", "jpg", "png", "tif", "tiff", "webp",
+];
+
+const MINIFIED_LINE_LENGTH: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DiffLimits {
+    pub(crate) max_file_bytes: usize,
+    pub(crate) max_total_bytes: usize,
+    /// Reduce lockfiles and minified files to their line stats.
+    pub(crate) collapse_generated: bool,
+}
+
+impl DiffLimits {
+    pub(crate) fn unlimited() -> Self {
+        Self {
+            max_file_bytes: usize::MAX,
+            max_total_bytes: usize::MAX,
+            collapse_generated: false,
+        }
     }
-    let mut path = if trimmed.starts_with("git@github.com:") {
-        trimmed.trim_start_matches("git@github.com:").to_string()
-    } else if trimmed.starts_with("ssh://git@github.com/") {
-        trimmed.trim_start_matches("ssh://git@github.com/").to_string()
-    } else if let Some(index) = trimmed.find("github.com/") {
-        trimmed[index + "github.com/".len()..].to_string()
+}
+
+fn diff_kind_for_path(path: &Path) -> GitDiffKind {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        GitDiffKind::Image
+    } else if LOCKFILE_NAMES.contains(&name.as_str()) {
+        GitDiffKind::Lockfile
+    } else if name.contains(".min.") {
+        GitDiffKind::Minified
     } else {
+        GitDiffKind::Text
+    }
+}
+
+fn looks_minified(patch: &str) -> bool {
+    patch
+        .lines()
+        .filter(|line| line.starts_with('+') || line.starts_with('-'))
+        .any(|line| line.len() > MINIFIED_LINE_LENGTH)
+}
+
+/// Cuts a patch at the last full line that fits in `max_bytes`.
+fn truncate_patch(patch: &str, max_bytes: usize) -> String {
+    if patch.len() <= max_bytes {
+        return patch.to_string();
+    }
+    let mut end = max_bytes;
+    while !patch.is_char_boundary(end) {
+        end -= 1;
+    }
+    let end = patch[..end].rfind('\n').map(|index| index + 1).unwrap_or(0);
+    patch[..end].to_string()
+}
+
+fn image_mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "avif" => "image/avif",
+        "bmp" => "image/bmp",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "jpeg" | "jpg" => "image/jpeg",
+        "png" => "image/png",
+        "tif" | "tiff" => "image/tiff",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Reads one side of an image diff, from the object database or, for the
+/// working copy, from disk.
+fn image_side_bytes(repo: &Repository, file: &git2::DiffFile) -> Option<Vec<u8>> {
+    if !file.exists() {
         return None;
+    }
+    if let Ok(blob) = repo.find_blob(file.id()) {
+        return Some(blob.content().to_vec());
+    }
+    std::fs::read(repo.workdir()?.join(file.path()?)).ok()
+}
+
+/// Diffs HEAD against the working copy (staged and unstaged, untracked
+/// included), optionally limited to a single path.
+pub(crate) fn workdir_diff<'repo>(
+    repo: &'repo Repository,
+    paths: Option<&[String]>,
+) -> Result<Diff<'repo>, git2::Error> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut options = DiffOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    if let Some(paths) = paths {
+        for path in paths {
+            options.pathspec(path);
+        }
+        options.disable_pathspec_match(true);
+    }
+    repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut options))
+}
+
+pub(crate) fn diff_to_file_diffs(
+    repo: &Repository,
+    diff: &Diff,
+    limits: &DiffLimits,
+) -> Vec<GitFileDiff> {
+    let mut results = Vec::new();
+    let mut total_bytes = 0usize;
+    for (index, delta) in diff.deltas().enumerate() {
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path());
+        let Some(path) = path else {
+            continue;
+        };
+        let patch = match git2::Patch::from_diff(diff, index) {
+            Ok(patch) => patch,
+            Err(_) => continue,
+        };
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        let (_, additions, deletions) = patch.line_stats().unwrap_or((0, 0, 0));
+        let mut kind = diff_kind_for_path(path);
+        if kind != GitDiffKind::Image && patch.delta().flags().is_binary() {
+            kind = GitDiffKind::Binary;
+        }
+        let mut file_diff = GitFileDiff {
+            path: normalize_git_path(path.to_string_lossy().as_ref()),
+            diff: String::new(),
+            kind,
+            additions,
+            deletions,
+            truncated: false,
+            image: None,
+        };
+        match kind {
+            GitDiffKind::Image => {
+                let budget = limits
+                    .max_file_bytes
+                    .min(limits.max_total_bytes.saturating_sub(total_bytes));
+                let mut used = 0;
+                let mut encode = |file: git2::DiffFile| {
+                    let bytes = image_side_bytes(repo, &file)?;
+                    let encoded = BASE64_STANDARD.encode(bytes);
+                    if used + encoded.len() > budget {
+                        file_diff.truncated = true;
+                        return None;
+                    }
+                    used += encoded.len();
+                    Some(encoded)
+                };
+                let before = encode(delta.old_file());
+                let after = encode(delta.new_file());
+                total_bytes += used;
+                file_diff.image = Some(GitImageDiff {
+                    mime_type: image_mime_type(path).to_string(),
+                    before,
+                    after,
+                });
+                results.push(file_diff);
+                continue;
+            }
+            GitDiffKind::Binary => {
+                results.push(file_diff);
+                continue;
+            }
+            GitDiffKind::Lockfile | GitDiffKind::Minified if limits.collapse_generated => {
+                results.push(file_diff);
+                continue;
+            }
+            _ => {}
+        }
+        let content = match diff_patch_to_string(&mut patch) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        if limits.collapse_generated && looks_minified(&content) {
+            file_diff.kind = GitDiffKind::Minified;
+            results.push(file_diff);
+            continue;
+        }
+        let budget = limits
+            .max_file_bytes
+            .min(limits.max_total_bytes.saturating_sub(total_bytes));
+        file_diff.truncated = content.len() > budget;
+        file_diff.diff = if file_diff.truncated {
+            truncate_patch(&content, budget)
+        } else {
+            content
+        };
+        total_bytes += file_diff.diff.len();
+        results.push(file_diff);
+    }
+    results
+}
+
+/// Caps diffs produced without a total budget at `max_total_bytes`, as
+/// `diff_to_file_diffs` would have.
+pub(crate) fn apply_total_diff_budget(
+    diffs: impl IntoIterator<Item = GitFileDiff>,
+    max_total_bytes: usize,
+) -> Vec<GitFileDiff> {
+    let mut total_bytes = 0usize;
+    diffs
+        .into_iter()
+        .map(|mut file_diff| {
+            let budget = max_total_bytes.saturating_sub(total_bytes);
+            if file_diff.diff.len() > budget {
+                file_diff.diff = truncate_patch(&file_diff.diff, budget);
+                file_diff.truncated = true;
+            }
+            total_bytes += file_diff.diff.len();
+            file_diff
+        })
+        .collect()
+}
+
+pub(crate) fn list_stashes(repo: &mut Repository) -> Result<Vec<GitStashEntry>, git2::Error> {
+    let mut stashes = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        stashes.push((index, message.to_string(), *oid));
+        true
+    })?;
+    Ok(stashes
+        .into_iter()
+        .map(|(index, message, oid)| {
+            let timestamp = repo
+                .find_commit(oid)
+                .map(|commit| commit.time().seconds())
+                .unwrap_or(0);
+            GitStashEntry {
+                index,
+                message,
+                sha: oid.to_string(),
+                timestamp,
+            }
+        })
+        .collect())
+}
+
+/// Stashes local changes, untracked files too when asked, and returns the
+/// new stash.
+pub(crate) fn push_stash(
+    repo: &mut Repository,
+    message: Option<&str>,
+    include_untracked: bool,
+) -> Result<GitStashEntry, String> {
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("CodexMonitor", "codex-monitor@localhost"))
+        .map_err(|e| e.to_string())?;
+    let message = message
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty());
+    let mut flags = StashFlags::DEFAULT;
+    if include_untracked {
+        flags |= StashFlags::INCLUDE_UNTRACKED;
+    }
+    repo.stash_save2(&signature, message, Some(flags))
+        .map_err(|e| {
+            if e.code() == ErrorCode::NotFound {
+                "No local changes to stash.".to_string()
+            } else {
+                e.to_string()
+            }
+        })?;
+    list_stashes(repo)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next()
+        .ok_or("Stash was not created.".to_string())
+}
+
+/// Applies the stash at `index`, dropping it afterwards when `pop` is set.
+pub(crate) fn apply_stash(repo: &mut Repository, index: usize, pop: bool) -> Result<(), String> {
+    let applied = if pop {
+        repo.stash_pop(index, None)
+    } else {
+        repo.stash_apply(index, None)
     };
-    path = path.trim_end_matches(".git").trim_end_matches('/').to_string();
-    if path.is_empty() {
-        None
+    applied.map_err(|err| {
+        if err.code() == ErrorCode::Conflict {
+            return "Stash conflicts with local changes. Commit, stash, or discard them before applying."
+                .to_string();
+        }
+        err.to_string()
+    })
+}
+
+pub(crate) fn stash_diffs(
+    repo: &Repository,
+    oid: Oid,
+    limits: &DiffLimits,
+) -> Result<Vec<GitFileDiff>, git2::Error> {
+    let commit = repo.find_commit(oid)?;
+    let stash_tree = commit.tree()?;
+    let base_tree = commit.parent(0)?.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&stash_tree), None)?;
+    let mut results = diff_to_file_diffs(repo, &diff, limits);
+    // Stashes saved with untracked files keep them in a third parent commit.
+    if commit.parent_count() > 2 {
+        let untracked_tree = commit.parent(2)?.tree()?;
+        let diff = repo.diff_tree_to_tree(None, Some(&untracked_tree), None)?;
+        results.extend(diff_to_file_diffs(repo, &diff, limits));
+    }
+    Ok(results)
+}
+
+fn conflict_kind(base: bool, ours: bool, theirs: bool) -> &'static str {
+    match (base, ours, theirs) {
+        (true, true, true) => "both-modified",
+        (false, true, true) => "both-added",
+        (true, false, true) => "deleted-by-us",
+        (true, true, false) => "deleted-by-them",
+        (false, true, false) => "added-by-us",
+        (false, false, true) => "added-by-them",
+        _ => "both-deleted",
+    }
+}
+
+fn conflict_entry_path(entry: &IndexEntry) -> String {
+    normalize_git_path(&String::from_utf8_lossy(&entry.path))
+}
+
+/// Lists the conflicted paths in the index. Mid-rebase the sides are
+/// reported from the rebased branch's point of view, so "ours" is always
+/// the work being kept rather than git's rebase-onto commit.
+pub(crate) fn list_conflicts(repo: &Repository) -> Result<Vec<GitConflictFile>, git2::Error> {
+    let mut conflicts = index_conflicts(&repo.index()?)?;
+    if rebase_in_progress(repo) {
+        for conflict in &mut conflicts {
+            std::mem::swap(&mut conflict.ours_sha, &mut conflict.theirs_sha);
+            conflict.kind = conflict_kind(
+                conflict.base_sha.is_some(),
+                conflict.ours_sha.is_some(),
+                conflict.theirs_sha.is_some(),
+            )
+            .to_string();
+        }
+    }
+    Ok(conflicts)
+}
+
+/// Rejects paths from the UI that are absolute or climb out of the
+/// repository.
+pub(crate) fn check_repo_relative_path(path: &str) -> Result<(), String> {
+    let inside = !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if inside {
+        Ok(())
     } else {
-        Some(path)
+        Err(format!("Path must stay inside the repository: {path}"))
+    }
+}
+
+fn index_conflicts(index: &Index) -> Result<Vec<GitConflictFile>, git2::Error> {
+    if !index.has_conflicts() {
+        return Ok(Vec::new());
     }
+    let mut results = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let path = conflict
+            .our
+            .as_ref()
+            .or(conflict.their.as_ref())
+            .or(conflict.ancestor.as_ref())
+            .map(conflict_entry_path);
+        let Some(path) = path else {
+            continue;
+        };
+        let kind = conflict_kind(
+            conflict.ancestor.is_some(),
+            conflict.our.is_some(),
+            conflict.their.is_some(),
+        );
+        results.push(GitConflictFile {
+            path,
+            kind: kind.to_string(),
+            base_sha: conflict.ancestor.map(|entry| entry.id.to_string()),
+            ours_sha: conflict.our.map(|entry| entry.id.to_string()),
+            theirs_sha: conflict.their.map(|entry| entry.id.to_string()),
+        });
+    }
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(results)
+}
+
+pub(crate) fn conflict_versions(
+    repo: &Repository,
+    conflict: &GitConflictFile,
+) -> Result<GitConflictVersions, git2::Error> {
+    let read_blob = |sha: &Option<String>| -> Result<Option<String>, git2::Error> {
+        let Some(sha) = sha else {
+            return Ok(None);
+        };
+        let blob = repo.find_blob(Oid::from_str(sha)?)?;
+        Ok(Some(String::from_utf8_lossy(blob.content()).to_string()))
+    };
+    Ok(GitConflictVersions {
+        path: conflict.path.clone(),
+        base: read_blob(&conflict.base_sha)?,
+        ours: read_blob(&conflict.ours_sha)?,
+        theirs: read_blob(&conflict.theirs_sha)?,
+    })
+}
+
+/// During a rebase git's "ours" is the branch being rebased onto and "theirs"
+/// is the commit being replayed, the reverse of a merge.
+pub(crate) fn rebase_in_progress(repo: &Repository) -> bool {
+    matches!(
+        repo.state(),
+        RepositoryState::Rebase | RepositoryState::RebaseInteractive | RepositoryState::RebaseMerge
+    )
+}
+
+/// Whether `content` still holds an unresolved `<<<<<<<` ... `>>>>>>>` block.
+pub(crate) fn has_conflict_markers(content: &str) -> bool {
+    let mut open = false;
+    for line in content.lines() {
+        if line.starts_with("<<<<<<<") {
+            open = true;
+        } else if open && line.starts_with(">>>>>>>") {
+            return true;
+        }
+    }
+    false
+}
+
+pub(crate) fn repository_state_label(repo: &Repository) -> Option<&'static str> {
+    match repo.state() {
+        RepositoryState::Clean => None,
+        RepositoryState::Merge => Some("merge"),
+        RepositoryState::Revert | RepositoryState::RevertSequence => Some("revert"),
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+            Some("cherry-pick")
+        }
+        RepositoryState::Bisect => Some("bisect"),
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge => Some("rebase"),
+        RepositoryState::ApplyMailbox | RepositoryState::ApplyMailboxOrRebase => Some("am"),
+    }
+}
+
+/// Prefers `origin`, falling back to the first configured remote.
+pub(crate) fn default_remote_name(repo: &Repository) -> Option<String> {
+    let remotes = repo.remotes().ok()?;
+    if remotes.iter().any(|remote| remote == Some("origin")) {
+        return Some("origin".to_string());
+    }
+    remotes.iter().flatten().next().map(|name| name.to_string())
+}
+
+/// Reads the remote's default branch from `refs/remotes/<remote>/HEAD`,
+/// guessing `main`/`master` when the symbolic ref was never fetched.
+pub(crate) fn default_base_branch(repo: &Repository, remote: &str) -> String {
+    let remote_head = format!("refs/remotes/{remote}/HEAD");
+    let prefix = format!("refs/remotes/{remote}/");
+    if let Some(target) = repo
+        .find_reference(&remote_head)
+        .ok()
+        .and_then(|reference| reference.symbolic_target().map(|value| value.to_string()))
+    {
+        if let Some(branch) = target.strip_prefix(&prefix) {
+            return branch.to_string();
+        }
+    }
+    for candidate in ["main", "master"] {
+        let local = repo.find_branch(candidate, git2::BranchType::Local).is_ok();
+        let tracked = repo
+            .find_reference(&format!("{prefix}{candidate}"))
+            .is_ok();
+        if local || tracked {
+            return candidate.to_string();
+        }
+    }
+    "main".to_string()
+}
+
+/// Lists commits reachable from `source` but not from `target`, newest first.
+pub(crate) fn commits_between(
+    repo: &Repository,
+    target: Oid,
+    source: Oid,
+) -> Result<Vec<GitLogEntry>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(source)?;
+    revwalk.hide(target)?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    let mut entries = Vec::new();
+    for oid in revwalk {
+        entries.push(commit_to_entry(repo.find_commit(oid?)?));
+    }
+    Ok(entries)
+}
+
+/// Merges `source` into `target` in memory, without touching any checkout.
+/// Returns the resulting changes relative to `target`, or the conflicting
+/// paths when the merge would not be clean.
+pub(crate) fn preview_merge(
+    repo: &Repository,
+    target: Oid,
+    source: Oid,
+    limits: &DiffLimits,
+) -> Result<(Vec<GitFileDiff>, Vec<GitConflictFile>), git2::Error> {
+    let target_commit = repo.find_commit(target)?;
+    let source_commit = repo.find_commit(source)?;
+    let mut index = repo.merge_commits(&target_commit, &source_commit, None)?;
+    if index.has_conflicts() {
+        return Ok((Vec::new(), index_conflicts(&index)?));
+    }
+    let merged_tree = repo.find_tree(index.write_tree_to(repo)?)?;
+    let diff = repo.diff_tree_to_tree(Some(&target_commit.tree()?), Some(&merged_tree), None)?;
+    Ok((diff_to_file_diffs(repo, &diff, limits), Vec::new()))
+}
+
+/// Replays the non-merge commits of `source` that `target` lacks onto
+/// `target` in memory, oldest first, the way a rebase or cherry-pick would.
+/// Returns the resulting changes relative to `target`, or the conflicts of
+/// the first commit that does not apply cleanly.
+pub(crate) fn preview_replay(
+    repo: &Repository,
+    target: Oid,
+    source: Oid,
+    limits: &DiffLimits,
+) -> Result<(Vec<GitFileDiff>, Vec<GitConflictFile>), git2::Error> {
+    let target_tree = repo.find_commit(target)?.tree()?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(source)?;
+    revwalk.hide(target)?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME | Sort::REVERSE)?;
+    let mut tree = target_tree.clone();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() != 1 {
+            continue;
+        }
+        let base = commit.parent(0)?.tree()?;
+        let mut index = repo.merge_trees(&base, &tree, &commit.tree()?, None)?;
+        if index.has_conflicts() {
+            return Ok((Vec::new(), index_conflicts(&index)?));
+        }
+        tree = repo.find_tree(index.write_tree_to(repo)?)?;
+    }
+    let diff = repo.diff_tree_to_tree(Some(&target_tree), Some(&tree), None)?;
+    Ok((diff_to_file_diffs(repo, &diff, limits), Vec::new()))
+}
+
+/// Lists commits on HEAD that are not on `base` (checked as the remote
+/// tracking branch first, then as a local branch), newest first.
+pub(crate) fn commits_since_base(
+    repo: &Repository,
+    remote: Option<&str>,
+    base: &str,
+    limit: usize,
+) -> Result<Vec<GitLogEntry>, git2::Error> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let base_oid = remote
+        .and_then(|remote| {
+            repo.refname_to_id(&format!("refs/remotes/{remote}/{base}"))
+                .ok()
+        })
+        .or_else(|| repo.refname_to_id(&format!("refs/heads/{base}")).ok());
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head)?;
+    if let Some(base_oid) = base_oid {
+        if let Ok(merge_base) = repo.merge_base(head, base_oid) {
+            revwalk.hide(merge_base)?;
+        }
+    }
+    revwalk.set_sorting(Sort::TIME)?;
+    let mut entries = Vec::new();
+    for oid in revwalk.take(limit) {
+        entries.push(commit_to_entry(repo.find_commit(oid?)?));
+    }
+    Ok(entries)
+}
+
+pub(crate) fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
+    let mut entries = Vec::new();
+    let mut current_lines: Vec<&str> = Vec::new();
+    let mut current_old_path: Option<String> = None;
+    let mut current_new_path: Option<String> = None;
+    let mut current_status: Option<String> = None;
+
+    let finalize = |lines: &Vec<&str>,
+                        old_path: &Option<String>,
+                        new_path: &Option<String>,
+                        status: &Option<String>,
+                        results: &mut Vec<GitHubPullRequestDiff>| {
+        if lines.is_empty() {
+            return;
+        }
+        let diff_text = lines.join("\n");
+        if diff_text.trim().is_empty() {
+            return;
+        }
+        let status_value = status.clone().unwrap_or_else(|| "M".to_string());
+        let path = if status_value == "D" {
+            old_path.clone().unwrap_or_default()
+        } else {
+            new_path.clone().or_else(|| old_path.clone()).unwrap_or_default()
+        };
+        if path.is_empty() {
+            return;
+        }
+        results.push(GitHubPullRequestDiff {
+            path: normalize_git_path(&path),
+            status: status_value,
+            diff: diff_text,
+        });
+    };
+
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            finalize(
+                &current_lines,
+                &current_old_path,
+                &current_new_path,
+                &current_status,
+                &mut entries,
+            );
+            current_lines = vec![line];
+            current_old_path = None;
+            current_new_path = None;
+            current_status = None;
+
+            let rest = line.trim_start_matches("diff --git ").trim();
+            let mut parts = rest.split_whitespace();
+            let old_part = parts.next().unwrap_or("").trim_start_matches("a/");
+            let new_part = parts.next().unwrap_or("").trim_start_matches("b/");
+            if !old_part.is_empty() {
+                current_old_path = Some(old_part.to_string());
+            }
+            if !new_part.is_empty() {
+                current_new_path = Some(new_part.to_string());
+            }
+            continue;
+        }
+        if line.starts_with("new file mode ") {
+            current_status = Some("A".to_string());
+        } else if line.starts_with("deleted file mode ") {
+            current_status = Some("D".to_string());
+        } else if line.starts_with("rename from ") {
+            current_status = Some("R".to_string());
+            let path = line.trim_start_matches("rename from ").trim();
+            if !path.is_empty() {
+                current_old_path = Some(path.to_string());
+            }
+        } else if line.starts_with("rename to ") {
+            current_status = Some("R".to_string());
+            let path = line.trim_start_matches("rename to ").trim();
+            if !path.is_empty() {
+                current_new_path = Some(path.to_string());
+            }
+        }
+        current_lines.push(line);
+    }
+
+    finalize(
+        &current_lines,
+        &current_old_path,
+        &current_new_path,
+        &current_status,
+        &mut entries,
+    );
+
+    entries
 }
 
 pub(crate) fn resolve_git_root(entry: &WorkspaceEntry) -> Result<PathBuf, String> {
@@ -186,3 +1288,422 @@ pub(crate) fn list_git_roots(
     results.sort();
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use git2::{BranchType, Oid, Repository, Signature, TreeBuilder, WorktreeAddOptions};
+    use uuid::Uuid;
+
+    use super::{
+        apply_stash, check_repo_relative_path, commits_between, conflict_kind,
+        create_or_fast_forward_branch, diff_kind_for_path, diff_to_file_diffs, file_history,
+        has_conflict_markers, list_conflicts, list_stashes, looks_minified, preview_merge,
+        preview_replay, push_stash, stash_diffs, truncate_patch, workdir_diff, DiffLimits,
+    };
+    use crate::types::GitDiffKind;
+
+    fn repo_with_commit() -> (PathBuf, Repository) {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        std::fs::write(dir.join("a.txt"), "one\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        drop(tree);
+        (dir, repo)
+    }
+
+    fn commit_file(repo: &Repository, parent: Option<Oid>, path: &str, content: &str) -> Oid {
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        commit_tree(repo, parent, path, |builder| {
+            builder.insert(path, blob, 0o100644).unwrap();
+        })
+    }
+
+    fn commit_tree(
+        repo: &Repository,
+        parent: Option<Oid>,
+        message: &str,
+        edit: impl FnOnce(&mut TreeBuilder),
+    ) -> Oid {
+        let mut builder = repo.treebuilder(None).unwrap();
+        if let Some(parent) = parent {
+            let tree = repo.find_commit(parent).unwrap().tree().unwrap();
+            builder = repo.treebuilder(Some(&tree)).unwrap();
+        }
+        edit(&mut builder);
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parents = parent
+            .map(|parent| vec![repo.find_commit(parent).unwrap()])
+            .unwrap_or_default();
+        let parents = parents.iter().collect::<Vec<_>>();
+        repo.commit(None, &signature, &signature, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn rename_file(repo: &Repository, parent: Oid, from: &str, to: &str) -> Oid {
+        let tree = repo.find_commit(parent).unwrap().tree().unwrap();
+        let blob = tree.get_name(from).unwrap().id();
+        commit_tree(repo, Some(parent), &format!("rename {from}"), |builder| {
+            builder.remove(from).unwrap();
+            builder.insert(to, blob, 0o100644).unwrap();
+        })
+    }
+
+    fn history_summaries(repo: &Repository, head: Oid, path: &str) -> Vec<(String, String)> {
+        repo.set_head_detached(head).unwrap();
+        file_history(repo, path, 50)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.commit.summary, entry.path))
+            .collect()
+    }
+
+    #[test]
+    fn image_diffs_carry_both_sides_as_base64() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let head = commit_file(&repo, None, "logo.png", "before");
+        repo.set_head_detached(head).unwrap();
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        repo.checkout_head(Some(checkout.force())).unwrap();
+        std::fs::write(dir.join("logo.png"), "after").unwrap();
+
+        let diff = workdir_diff(&repo, None).unwrap();
+        let diffs = diff_to_file_diffs(&repo, &diff, &DiffLimits::unlimited());
+        let image = diffs[0].image.as_ref().unwrap();
+        assert_eq!(image.mime_type, "image/png");
+        assert_eq!(image.before.as_deref(), Some("YmVmb3Jl"));
+        assert_eq!(image.after.as_deref(), Some("YWZ0ZXI="));
+        assert!(!diffs[0].truncated);
+
+        let limits = DiffLimits {
+            max_file_bytes: 10,
+            ..DiffLimits::unlimited()
+        };
+        let diffs = diff_to_file_diffs(&repo, &diff, &limits);
+        let image = diffs[0].image.as_ref().unwrap();
+        assert!(image.before.is_some());
+        assert!(image.after.is_none());
+        assert!(diffs[0].truncated);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_history_follows_a_rename_chain() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let content = "line one\nline two\nline three\nline four\n";
+        let created = commit_file(&repo, None, "a.txt", content);
+        let unrelated = commit_file(&repo, Some(created), "other.txt", "other\n");
+        let first = rename_file(&repo, unrelated, "a.txt", "b.txt");
+        let second = rename_file(&repo, first, "b.txt", "c.txt");
+        let edited = commit_file(&repo, Some(second), "c.txt", &format!("{content}line five\n"));
+
+        let history = history_summaries(&repo, edited, "c.txt");
+        assert_eq!(
+            history,
+            vec![
+                ("c.txt".to_string(), "c.txt".to_string()),
+                ("rename b.txt".to_string(), "c.txt".to_string()),
+                ("rename a.txt".to_string(), "b.txt".to_string()),
+                ("a.txt".to_string(), "a.txt".to_string()),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_history_continues_past_a_delete_and_re_add() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let created = commit_file(&repo, None, "a.txt", "first\n");
+        let kept = commit_file(&repo, Some(created), "keep.txt", "keep\n");
+        let deleted = commit_tree(&repo, Some(kept), "delete a.txt", |builder| {
+            builder.remove("a.txt").unwrap();
+        });
+        let readded = commit_file(&repo, Some(deleted), "a.txt", "second\n");
+
+        let history = history_summaries(&repo, readded, "a.txt");
+        let summaries = history
+            .iter()
+            .map(|(summary, _)| summary.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(summaries, vec!["a.txt", "delete a.txt", "a.txt"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stashes_are_pushed_listed_shown_applied_and_dropped() {
+        let (dir, mut repo) = repo_with_commit();
+        assert_eq!(
+            push_stash(&mut repo, None, false).unwrap_err(),
+            "No local changes to stash."
+        );
+
+        std::fs::write(dir.join("a.txt"), "two\n").unwrap();
+        std::fs::write(dir.join("b.txt"), "new\n").unwrap();
+        let tracked = push_stash(&mut repo, Some("  tracked only  "), false).unwrap();
+        assert!(tracked.message.ends_with(": tracked only"));
+        assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "one\n");
+        assert!(dir.join("b.txt").exists());
+
+        let untracked = push_stash(&mut repo, Some("untracked"), true).unwrap();
+        assert!(!dir.join("b.txt").exists());
+        let stashes = list_stashes(&mut repo).unwrap();
+        assert_eq!(stashes.len(), 2);
+        assert_eq!((stashes[0].index, &stashes[0].sha), (0, &untracked.sha));
+        assert_eq!((stashes[1].index, &stashes[1].sha), (1, &tracked.sha));
+
+        let limits = DiffLimits::unlimited();
+        let shown = |sha: &str| {
+            stash_diffs(&repo, Oid::from_str(sha).unwrap(), &limits)
+                .unwrap()
+                .into_iter()
+                .map(|diff| diff.path)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shown(&untracked.sha), vec!["b.txt"]);
+        assert_eq!(shown(&tracked.sha), vec!["a.txt"]);
+
+        apply_stash(&mut repo, 0, false).unwrap();
+        assert!(dir.join("b.txt").exists());
+        assert_eq!(list_stashes(&mut repo).unwrap().len(), 2);
+        apply_stash(&mut repo, 1, true).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "two\n");
+        assert_eq!(list_stashes(&mut repo).unwrap().len(), 1);
+        repo.stash_drop(0).unwrap();
+        assert!(list_stashes(&mut repo).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn applying_a_stash_over_conflicting_changes_is_refused() {
+        let (dir, mut repo) = repo_with_commit();
+        std::fs::write(dir.join("a.txt"), "stashed\n").unwrap();
+        push_stash(&mut repo, None, false).unwrap();
+        std::fs::write(dir.join("a.txt"), "local\n").unwrap();
+
+        let error = apply_stash(&mut repo, 0, true).unwrap_err();
+        assert!(error.starts_with("Stash conflicts with local changes."), "{error}");
+        assert_eq!(std::fs::read_to_string(dir.join("a.txt")).unwrap(), "local\n");
+        assert_eq!(list_stashes(&mut repo).unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conflict_markers_need_an_opening_and_closing_line() {
+        assert!(has_conflict_markers(
+            "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n"
+        ));
+        assert!(!has_conflict_markers("Title\n=======\nbody\n"));
+        assert!(!has_conflict_markers(">>>>>>> stray\n<<<<<<< open\n"));
+    }
+
+    #[test]
+    fn conflict_sides_follow_the_rebased_branch() {
+        let (dir, repo) = repo_with_commit();
+        let base = repo.head().unwrap().target().unwrap();
+        let ours = commit_tree(&repo, Some(base), "delete", |builder| {
+            builder.remove("a.txt").unwrap();
+        });
+        let theirs = commit_file(&repo, Some(base), "a.txt", "two\n");
+        let merged = repo
+            .merge_commits(
+                &repo.find_commit(ours).unwrap(),
+                &repo.find_commit(theirs).unwrap(),
+                None,
+            )
+            .unwrap();
+        let mut index = merged;
+        repo.set_index(&mut index).unwrap();
+
+        let conflict = &list_conflicts(&repo).unwrap()[0];
+        assert_eq!(conflict.kind, "deleted-by-us");
+        assert!(conflict.ours_sha.is_none());
+
+        std::fs::create_dir(dir.join(".git/rebase-merge")).unwrap();
+        let conflict = &list_conflicts(&repo).unwrap()[0];
+        assert_eq!(conflict.kind, "deleted-by-them");
+        assert!(conflict.theirs_sha.is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn repo_relative_paths_cannot_escape_the_repository() {
+        assert!(check_repo_relative_path("src/main.rs").is_ok());
+        assert!(check_repo_relative_path("./a.txt").is_ok());
+        assert!(check_repo_relative_path("../outside").is_err());
+        assert!(check_repo_relative_path("src/../../outside").is_err());
+        assert!(check_repo_relative_path("/etc/passwd").is_err());
+        assert!(check_repo_relative_path("").is_err());
+    }
+
+    #[test]
+    fn conflict_kind_labels_each_side_combination() {
+        assert_eq!(conflict_kind(true, true, true), "both-modified");
+        assert_eq!(conflict_kind(false, true, true), "both-added");
+        assert_eq!(conflict_kind(true, false, true), "deleted-by-us");
+        assert_eq!(conflict_kind(true, true, false), "deleted-by-them");
+        assert_eq!(conflict_kind(false, true, false), "added-by-us");
+        assert_eq!(conflict_kind(false, false, true), "added-by-them");
+        assert_eq!(conflict_kind(true, false, false), "both-deleted");
+    }
+
+    #[test]
+    fn diff_kind_for_path_detects_generated_files() {
+        assert_eq!(diff_kind_for_path(Path::new("ui/logo.PNG")), GitDiffKind::Image);
+        assert_eq!(diff_kind_for_path(Path::new("web/yarn.lock")), GitDiffKind::Lockfile);
+        assert_eq!(diff_kind_for_path(Path::new("dist/app.min.js")), GitDiffKind::Minified);
+        assert_eq!(diff_kind_for_path(Path::new("src/icon.svg")), GitDiffKind::Text);
+    }
+
+    #[test]
+    fn looks_minified_checks_changed_lines_only() {
+        let long = "x".repeat(2000);
+        assert!(looks_minified(&format!("@@ -1 +1 @@\n+{long}\n")));
+        assert!(!looks_minified(&format!("@@ -1 +1 @@\n {long}\n+short\n")));
+    }
+
+    #[test]
+    fn truncate_patch_keeps_whole_lines() {
+        let patch = "line one\nline two\nline three\n";
+        assert_eq!(truncate_patch(patch, 100), patch);
+        assert_eq!(truncate_patch(patch, 14), "line one\n");
+        assert_eq!(truncate_patch("héllo\nworld\n", 2), "");
+    }
+
+    #[test]
+    fn pull_request_branches_only_fast_forward() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let base = commit_file(&repo, None, "a.txt", "one\n");
+        let update = commit_file(&repo, Some(base), "a.txt", "two\n");
+        let local = commit_file(&repo, Some(base), "b.txt", "local\n");
+        let branch_target = |name: &str| {
+            repo.find_branch(name, BranchType::Local)
+                .unwrap()
+                .get()
+                .target()
+                .unwrap()
+        };
+
+        create_or_fast_forward_branch(&repo, "pr-1", base).unwrap();
+        assert_eq!(branch_target("pr-1"), base);
+        create_or_fast_forward_branch(&repo, "pr-1", update).unwrap();
+        assert_eq!(branch_target("pr-1"), update);
+        create_or_fast_forward_branch(&repo, "pr-1", update).unwrap();
+
+        // Local commits on the branch are never dropped.
+        repo.branch("pr-2", &repo.find_commit(local).unwrap(), false)
+            .unwrap();
+        let diverged = create_or_fast_forward_branch(&repo, "pr-2", update).unwrap_err();
+        assert!(diverged.contains("not in the update"));
+        assert_eq!(branch_target("pr-2"), local);
+
+        // Nor is a branch moved under the checkout that has it.
+        repo.branch("pr-3", &repo.find_commit(base).unwrap(), false)
+            .unwrap();
+        repo.set_head("refs/heads/pr-3").unwrap();
+        let checked_out = create_or_fast_forward_branch(&repo, "pr-3", update).unwrap_err();
+        assert!(checked_out.contains("checked out"));
+        assert_eq!(branch_target("pr-3"), base);
+
+        let linked = dir.with_extension("pr-4");
+        let pr4 = repo
+            .branch("pr-4", &repo.find_commit(base).unwrap(), false)
+            .unwrap();
+        let mut options = WorktreeAddOptions::new();
+        options.reference(Some(pr4.get()));
+        repo.worktree("pr-4", &linked, Some(&options)).unwrap();
+        let checked_out = create_or_fast_forward_branch(&repo, "pr-4", update).unwrap_err();
+        assert!(checked_out.contains("checked out"));
+        assert_eq!(branch_target("pr-4"), base);
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&linked);
+    }
+
+    #[test]
+    fn preview_merge_reports_changes_or_conflicts() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let base = commit_file(&repo, None, "a.txt", "one\n");
+        let target = commit_file(&repo, Some(base), "b.txt", "target\n");
+        let source = commit_file(&repo, Some(base), "c.txt", "source\n");
+        let clashing = commit_file(&repo, Some(base), "b.txt", "source\n");
+
+        let commits = commits_between(&repo, target, source).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].summary, "c.txt");
+
+        let (diffs, conflicts) =
+            preview_merge(&repo, target, source, &DiffLimits::unlimited()).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path, "c.txt");
+
+        let (diffs, conflicts) =
+            preview_merge(&repo, target, clashing, &DiffLimits::unlimited()).unwrap();
+        assert!(diffs.is_empty());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "b.txt");
+        assert_eq!(conflicts[0].kind, "both-added");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn preview_replay_skips_merge_commits_and_stops_at_conflicts() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let base = commit_file(&repo, None, "a.txt", "one\n");
+        let target = commit_file(&repo, Some(base), "b.txt", "target\n");
+        let side = commit_file(&repo, Some(base), "c.txt", "side\n");
+        let feature = commit_file(&repo, Some(base), "d.txt", "feature\n");
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let merged = repo
+            .merge_commits(
+                &repo.find_commit(feature).unwrap(),
+                &repo.find_commit(side).unwrap(),
+                None,
+            )
+            .unwrap()
+            .write_tree_to(&repo)
+            .unwrap();
+        let merge = repo
+            .commit(
+                None,
+                &signature,
+                &signature,
+                "merge side",
+                &repo.find_tree(merged).unwrap(),
+                &[&repo.find_commit(feature).unwrap(), &repo.find_commit(side).unwrap()],
+            )
+            .unwrap();
+        let source = commit_file(&repo, Some(merge), "a.txt", "two\n");
+
+        let (diffs, conflicts) =
+            preview_replay(&repo, target, source, &DiffLimits::unlimited()).unwrap();
+        assert!(conflicts.is_empty());
+        let paths = diffs.iter().map(|diff| diff.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["a.txt", "c.txt", "d.txt"]);
+
+        let clashing = commit_file(&repo, Some(source), "b.txt", "source\n");
+        let (diffs, conflicts) =
+            preview_replay(&repo, target, clashing, &DiffLimits::unlimited()).unwrap();
+        assert!(diffs.is_empty());
+        assert_eq!(conflicts[0].path, "b.txt");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
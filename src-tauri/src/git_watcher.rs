@@ -0,0 +1,775 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use git2::Repository;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use notify::{Event, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Instant};
+
+use crate::backend::events::{EventSink, GitStatusChanged};
+use crate::git_utils::{
+    apply_total_diff_budget, collect_status_entries, diff_to_file_diffs, workdir_diff, DiffLimits,
+    StatusEntry,
+};
+use crate::types::GitFileDiff;
+
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(250);
+const DEBOUNCE_MAX: Duration = Duration::from_secs(2);
+const MAX_INCREMENTAL_PATHS: usize = 256;
+
+type StatusCache = Arc<StdMutex<Option<BTreeMap<String, StatusEntry>>>>;
+type SharedDiffCache = Arc<StdMutex<DiffCache>>;
+type SharedWatcher = Arc<StdMutex<notify::RecommendedWatcher>>;
+
+/// File diffs computed without the total budget, kept until the watcher sees
+/// their paths change.
+#[derive(Default)]
+struct DiffCache {
+    /// Bumped whenever `files` is dropped, so a scan that raced with that
+    /// isn't stored.
+    generation: u64,
+    limits: Option<DiffLimits>,
+    files: Option<BTreeMap<String, GitFileDiff>>,
+    stale: BTreeSet<String>,
+}
+
+impl DiffCache {
+    fn invalidate(&mut self) {
+        self.generation += 1;
+        self.files = None;
+        self.stale.clear();
+    }
+
+    fn record(&mut self, change: &Change) {
+        match change {
+            Change::Skip => {}
+            Change::Full | Change::Ignores => self.invalidate(),
+            Change::Path(path) => {
+                if self.files.is_some() {
+                    self.stale.insert(path.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Watches a repository and keeps its status entries up to date, refreshing
+/// only the paths that changed unless the index, HEAD or refs moved.
+pub(crate) struct GitStatusWatcher {
+    repo_root: PathBuf,
+    cache: StatusCache,
+    diffs: SharedDiffCache,
+    _watcher: SharedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl GitStatusWatcher {
+    pub(crate) fn start(
+        workspace_id: String,
+        repo_root: PathBuf,
+        event_sink: impl EventSink,
+    ) -> Result<Self, String> {
+        let root = repo_root.canonicalize().map_err(|e| e.to_string())?;
+        let git_dir = Repository::open(&root)
+            .map_err(|e| e.to_string())?
+            .path()
+            .canonicalize()
+            .map_err(|e| e.to_string())?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            // Reads (including our own status scans) would otherwise retrigger refreshes.
+            match event {
+                Ok(event) if !event.kind.is_access() => {
+                    let _ = tx.send(event.paths);
+                }
+                _ => {}
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        watch_tree(&mut watcher, &root)?;
+        watcher
+            .watch(&git_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+        // Branch moves land in refs/, and info/exclude changes the ignores.
+        // Linked worktrees keep these in the common dir, which isn't watched.
+        for (dir, mode) in [
+            ("refs", RecursiveMode::Recursive),
+            ("info", RecursiveMode::NonRecursive),
+        ] {
+            let _ = watcher.watch(&git_dir.join(dir), mode);
+        }
+        let watcher = Arc::new(StdMutex::new(watcher));
+
+        let cache: StatusCache = Arc::new(StdMutex::new(None));
+        let diffs = SharedDiffCache::default();
+        let task = tokio::spawn(run_watcher(
+            workspace_id,
+            root,
+            git_dir,
+            cache.clone(),
+            diffs.clone(),
+            Some(watcher.clone()),
+            event_sink,
+            rx,
+        ));
+        Ok(Self {
+            repo_root,
+            cache,
+            diffs,
+            _watcher: watcher,
+            task,
+        })
+    }
+
+    pub(crate) fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// Returns the cached status entries, or `None` until the first scan finishes.
+    pub(crate) fn cached_entries(&self) -> Option<Vec<StatusEntry>> {
+        let cache = self.cache.lock().ok()?;
+        cache
+            .as_ref()
+            .map(|entries| entries.values().cloned().collect())
+    }
+
+    /// The working tree diffs, recomputing only the files that changed since
+    /// the last call.
+    pub(crate) fn file_diffs(
+        &self,
+        repo: &Repository,
+        limits: &DiffLimits,
+    ) -> Result<Vec<GitFileDiff>, String> {
+        cached_file_diffs(&self.diffs, repo, limits)
+    }
+}
+
+impl Drop for GitStatusWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Change {
+    Skip,
+    Full,
+    Ignores,
+    Path(String),
+}
+
+#[derive(Default)]
+struct PendingChanges {
+    full: bool,
+    rebuild_ignores: bool,
+    paths: BTreeSet<String>,
+}
+
+impl PendingChanges {
+    fn is_empty(&self) -> bool {
+        !self.full && !self.rebuild_ignores && self.paths.is_empty()
+    }
+
+    fn add(&mut self, change: Change) {
+        match change {
+            Change::Skip => {}
+            Change::Full => self.full = true,
+            Change::Ignores => {
+                self.rebuild_ignores = true;
+                self.full = true;
+            }
+            Change::Path(path) => {
+                self.paths.insert(path);
+            }
+        }
+    }
+}
+
+/// Gitignore rules per directory, checked deepest first so nested files can
+/// override their parents the same way git does.
+struct IgnoreMatcher {
+    matchers: Vec<(PathBuf, Gitignore)>,
+}
+
+impl IgnoreMatcher {
+    fn build(root: &Path, git_dir: &Path) -> Self {
+        let mut matchers = Vec::new();
+        let mut root_builder = GitignoreBuilder::new(root);
+        root_builder.add(root.join(".gitignore"));
+        root_builder.add(git_dir.join("info").join("exclude"));
+        if let Ok(matcher) = root_builder.build() {
+            matchers.push((root.to_path_buf(), matcher));
+        }
+
+        let walker = WalkBuilder::new(root)
+            .hidden(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+        for entry in walker.flatten() {
+            if entry.file_name() != ".gitignore" {
+                continue;
+            }
+            let Some(dir) = entry.path().parent() else {
+                continue;
+            };
+            if dir == root {
+                continue;
+            }
+            let mut builder = GitignoreBuilder::new(dir);
+            builder.add(entry.path());
+            if let Ok(matcher) = builder.build() {
+                matchers.push((dir.to_path_buf(), matcher));
+            }
+        }
+        matchers.sort_by_key(|(dir, _)| std::cmp::Reverse(dir.components().count()));
+        Self { matchers }
+    }
+
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for (dir, matcher) in &self.matchers {
+            // `matched_path_or_any_parents` panics on paths outside its root.
+            if !path.starts_with(dir) {
+                continue;
+            }
+            let matched = matcher.matched_path_or_any_parents(path, is_dir);
+            if matched.is_whitelist() {
+                return false;
+            }
+            if matched.is_ignore() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// The directories under `dir` that aren't gitignored, `dir` included.
+/// Watching these one by one keeps build output and dependency trees such as
+/// `node_modules` or `target` from using up the system's watch limit.
+fn watched_dirs(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkBuilder::new(dir)
+        .hidden(false)
+        .ignore(false)
+        .filter_entry(|entry| {
+            entry.file_name() != ".git" && entry.file_type().is_some_and(|kind| kind.is_dir())
+        })
+        .build()
+        .flatten()
+        .map(|entry| entry.into_path())
+}
+
+fn watch_tree(watcher: &mut impl Watcher, dir: &Path) -> Result<(), String> {
+    for dir in watched_dirs(dir) {
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn classify_path(path: &Path, root: &Path, git_dir: &Path, ignores: &IgnoreMatcher) -> Change {
+    if let Ok(relative) = path.strip_prefix(git_dir) {
+        let first = relative
+            .components()
+            .next()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .unwrap_or_default();
+        let is_lock = relative
+            .extension()
+            .is_some_and(|extension| extension == "lock");
+        return match first.as_str() {
+            _ if is_lock => Change::Skip,
+            "" | "objects" | "logs" | "hooks" | "worktrees" | "FETCH_HEAD" => Change::Skip,
+            "info" => Change::Ignores,
+            _ => Change::Full,
+        };
+    }
+    let Ok(relative) = path.strip_prefix(root) else {
+        return Change::Skip;
+    };
+    if relative.as_os_str().is_empty() {
+        return Change::Skip;
+    }
+    if relative
+        .components()
+        .any(|component| component.as_os_str() == ".git")
+    {
+        // Nested repositories and submodule git dirs.
+        return Change::Skip;
+    }
+    if path.file_name().is_some_and(|name| name == ".gitignore") {
+        return Change::Ignores;
+    }
+    if ignores.is_ignored(path, path.is_dir()) {
+        return Change::Skip;
+    }
+    Change::Path(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn refresh_cache(
+    root: &Path,
+    cache: &StatusCache,
+    paths: Option<&[String]>,
+) -> Result<(), String> {
+    let primed = cache.lock().map(|cache| cache.is_some()).unwrap_or(false);
+    // Without a full scan to patch, an incremental refresh would drop entries.
+    let paths = paths.filter(|_| primed);
+    let repo = Repository::open(root).map_err(|e| e.to_string())?;
+    let entries = collect_status_entries(&repo, paths).map_err(|e| e.to_string())?;
+    let mut cache = cache.lock().map_err(|_| "status cache poisoned".to_string())?;
+    match (paths, cache.as_mut()) {
+        (Some(paths), Some(existing)) => {
+            existing.retain(|key, _| !paths.iter().any(|path| path_contains(path, key)));
+            existing.extend(entries.into_iter().map(|entry| (entry.path.clone(), entry)));
+        }
+        _ => {
+            *cache = Some(
+                entries
+                    .into_iter()
+                    .map(|entry| (entry.path.clone(), entry))
+                    .collect(),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn cached_file_diffs(
+    diffs: &SharedDiffCache,
+    repo: &Repository,
+    limits: &DiffLimits,
+) -> Result<Vec<GitFileDiff>, String> {
+    let poisoned = |_| "diff cache poisoned".to_string();
+    // The total budget depends on every file, so it is applied afterwards.
+    let file_limits = DiffLimits {
+        max_total_bytes: usize::MAX,
+        ..*limits
+    };
+    let (generation, stale) = {
+        let mut cache = diffs.lock().map_err(poisoned)?;
+        if cache.limits != Some(file_limits) {
+            cache.invalidate();
+            cache.limits = Some(file_limits);
+        }
+        let stale = cache.files.is_some().then(|| {
+            std::mem::take(&mut cache.stale)
+                .into_iter()
+                .collect::<Vec<_>>()
+        });
+        (cache.generation, stale)
+    };
+    let scanned = match stale.as_deref() {
+        Some([]) => Ok(Vec::new()),
+        paths => workdir_diff(repo, paths)
+            .map(|diff| diff_to_file_diffs(repo, &diff, &file_limits))
+            .map_err(|e| e.to_string()),
+    };
+    let mut cache = diffs.lock().map_err(poisoned)?;
+    if cache.generation != generation {
+        // Dropped while scanning, so there is nothing to patch.
+        drop(cache);
+        let diff = workdir_diff(repo, None).map_err(|e| e.to_string())?;
+        return Ok(diff_to_file_diffs(repo, &diff, limits));
+    }
+    let scanned = match scanned {
+        Ok(scanned) => scanned,
+        Err(err) => {
+            cache.invalidate();
+            return Err(err);
+        }
+    };
+    let files = match (stale, cache.files.as_mut()) {
+        (Some(paths), Some(files)) => {
+            files.retain(|key, _| !paths.iter().any(|path| path_contains(path, key)));
+            files.extend(scanned.into_iter().map(|diff| (diff.path.clone(), diff)));
+            files
+        }
+        _ => cache.files.insert(
+            scanned
+                .into_iter()
+                .map(|diff| (diff.path.clone(), diff))
+                .collect(),
+        ),
+    };
+    Ok(apply_total_diff_budget(
+        files.values().cloned(),
+        limits.max_total_bytes,
+    ))
+}
+
+/// Whether `key` is `path` or lies inside it.
+fn path_contains(path: &str, key: &str) -> bool {
+    key == path
+        || key
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+async fn run_watcher(
+    workspace_id: String,
+    root: PathBuf,
+    git_dir: PathBuf,
+    cache: StatusCache,
+    diffs: SharedDiffCache,
+    watcher: Option<SharedWatcher>,
+    event_sink: impl EventSink,
+    mut rx: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+) {
+    let primed = {
+        let root = root.clone();
+        let git_dir = git_dir.clone();
+        let cache = cache.clone();
+        tokio::task::spawn_blocking(move || {
+            let ignores = IgnoreMatcher::build(&root, &git_dir);
+            let _ = refresh_cache(&root, &cache, None);
+            ignores
+        })
+        .await
+    };
+    let Ok(mut ignores) = primed else {
+        return;
+    };
+
+    let collect = |pending: &mut PendingChanges, paths: &[PathBuf], ignores: &IgnoreMatcher| {
+        for path in paths {
+            let change = classify_path(path, &root, &git_dir, ignores);
+            if let (Change::Path(_), Some(watcher)) = (&change, &watcher) {
+                if path.is_dir() {
+                    // New directories aren't covered by the per-directory watches.
+                    if let Ok(mut watcher) = watcher.lock() {
+                        let _ = watch_tree(&mut *watcher, path);
+                    }
+                }
+            }
+            // Drop cached diffs right away rather than after the debounce.
+            if let Ok(mut diffs) = diffs.lock() {
+                diffs.record(&change);
+            }
+            pending.add(change);
+        }
+    };
+    while let Some(paths) = rx.recv().await {
+        let mut pending = PendingChanges::default();
+        collect(&mut pending, &paths, &ignores);
+        let started = Instant::now();
+        while started.elapsed() < DEBOUNCE_MAX {
+            match timeout(DEBOUNCE_WINDOW, rx.recv()).await {
+                Ok(Some(paths)) => collect(&mut pending, &paths, &ignores),
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+        if pending.is_empty() {
+            continue;
+        }
+        let full = pending.full || pending.paths.len() > MAX_INCREMENTAL_PATHS;
+        let changed: Vec<String> = pending.paths.into_iter().collect();
+
+        let result = {
+            let root = root.clone();
+            let git_dir = git_dir.clone();
+            let cache = cache.clone();
+            let changed = changed.clone();
+            let rebuild_ignores = pending.rebuild_ignores;
+            tokio::task::spawn_blocking(move || {
+                let ignores = rebuild_ignores.then(|| IgnoreMatcher::build(&root, &git_dir));
+                let paths = (!full).then_some(changed.as_slice());
+                refresh_cache(&root, &cache, paths).map(|_| ignores)
+            })
+            .await
+        };
+        match result {
+            Ok(Ok(rebuilt)) => {
+                if let Some(rebuilt) = rebuilt {
+                    ignores = rebuilt;
+                }
+            }
+            _ => continue,
+        }
+        event_sink.emit_git_status_changed(GitStatusChanged {
+            workspace_id: workspace_id.clone(),
+            paths: if full { Vec::new() } else { changed },
+            full,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex as StdMutex};
+    use std::time::Duration;
+
+    use git2::{Repository, Signature};
+    use tokio::sync::mpsc;
+    use tokio::time::timeout;
+    use uuid::Uuid;
+
+    use super::{
+        cached_file_diffs, classify_path, refresh_cache, run_watcher, watched_dirs, Change,
+        IgnoreMatcher, SharedDiffCache, StatusCache,
+    };
+    use crate::backend::events::{
+        AppServerEvent, ApprovalAutoDecision, CloneProgress, EventSink, GitStatusChanged,
+        StorageChanged, TerminalOutput, WorktreeSetupOutput,
+    };
+    use crate::git_utils::DiffLimits;
+    use crate::types::WorktreeSetupStatus;
+
+    #[derive(Clone)]
+    struct RecordingSink {
+        tx: mpsc::UnboundedSender<GitStatusChanged>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_git_status_changed(&self, event: GitStatusChanged) {
+            let _ = self.tx.send(event);
+        }
+        fn emit_worktree_setup_output(&self, _event: WorktreeSetupOutput) {}
+        fn emit_worktree_setup_status(&self, _event: WorktreeSetupStatus) {}
+        fn emit_clone_progress(&self, _event: CloneProgress) {}
+        fn emit_storage_changed(&self, _event: StorageChanged) {}
+        fn emit_approval_auto_decision(&self, _event: ApprovalAutoDecision) {}
+    }
+
+    /// A repository with `files` committed on HEAD.
+    fn repo_with(files: &[(&str, &str)]) -> (PathBuf, Repository) {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let root = dir.canonicalize().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            write(&root, path, content);
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        drop(tree);
+        (root, repo)
+    }
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn cached_paths(cache: &StatusCache) -> Vec<String> {
+        cache
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|entries| entries.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn classify_path_separates_work_files_ignores_and_git_metadata() {
+        let (root, repo) = repo_with(&[(".gitignore", "target/\n")]);
+        let git_dir = repo.path().canonicalize().unwrap();
+        let ignores = IgnoreMatcher::build(&root, &git_dir);
+        let classify = |path: &str| classify_path(&root.join(path), &root, &git_dir, &ignores);
+
+        assert_eq!(
+            classify("src/lib.rs"),
+            Change::Path("src/lib.rs".to_string())
+        );
+        assert_eq!(classify("target/debug/app"), Change::Skip);
+        assert_eq!(classify(".gitignore"), Change::Ignores);
+        assert_eq!(classify(".git/index"), Change::Full);
+        assert_eq!(classify(".git/refs/heads/main"), Change::Full);
+        assert_eq!(classify(".git/index.lock"), Change::Skip);
+        assert_eq!(classify(".git/objects/ab/cdef"), Change::Skip);
+        assert_eq!(classify(".git/info/exclude"), Change::Ignores);
+        assert_eq!(classify("vendor/dep/.git/HEAD"), Change::Skip);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn ignored_directories_are_not_watched() {
+        let (root, _repo) = repo_with(&[
+            (".gitignore", "node_modules/\ntarget\n"),
+            ("src/lib.rs", "\n"),
+        ]);
+        write(&root, "node_modules/dep/index.js", "\n");
+        write(&root, "web/target/debug/app", "\n");
+
+        let mut dirs = watched_dirs(&root)
+            .map(|dir| dir.strip_prefix(&root).unwrap().to_path_buf())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        assert_eq!(
+            dirs,
+            vec![PathBuf::new(), PathBuf::from("src"), PathBuf::from("web")]
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn incremental_refresh_patches_only_the_changed_paths() {
+        let (root, _repo) = repo_with(&[("a.txt", "one\n"), ("b.txt", "one\n")]);
+        let cache: StatusCache = Arc::new(StdMutex::new(None));
+
+        // Paths are ignored until a full scan primed the cache.
+        write(&root, "a.txt", "two\n");
+        refresh_cache(&root, &cache, Some(&["b.txt".to_string()])).unwrap();
+        assert_eq!(cached_paths(&cache), vec!["a.txt"]);
+
+        write(&root, "b.txt", "two\n");
+        write(&root, "dir/c.txt", "new\n");
+        refresh_cache(&root, &cache, Some(&["b.txt".to_string()])).unwrap();
+        assert_eq!(cached_paths(&cache), vec!["a.txt", "b.txt"]);
+
+        std::fs::write(root.join("a.txt"), "one\n").unwrap();
+        refresh_cache(
+            &root,
+            &cache,
+            Some(&["a.txt".to_string(), "dir".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(cached_paths(&cache), vec!["b.txt", "dir/c.txt"]);
+
+        std::fs::remove_dir_all(root.join("dir")).unwrap();
+        refresh_cache(&root, &cache, Some(&["dir".to_string()])).unwrap();
+        assert_eq!(cached_paths(&cache), vec!["b.txt"]);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn changes_are_debounced_into_one_refresh() {
+        let (root, repo) = repo_with(&[("a.txt", "one\n")]);
+        let git_dir = repo.path().canonicalize().unwrap();
+        let cache: StatusCache = Arc::new(StdMutex::new(None));
+        let (event_tx, mut events) = mpsc::unbounded_channel();
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let task = tokio::spawn(run_watcher(
+                    "ws".to_string(),
+                    root.clone(),
+                    git_dir.clone(),
+                    cache.clone(),
+                    SharedDiffCache::default(),
+                    None,
+                    RecordingSink { tx: event_tx },
+                    rx,
+                ));
+                for path in ["a.txt", "b.txt", "c.txt"] {
+                    write(&root, path, "changed\n");
+                    tx.send(vec![root.join(path)]).unwrap();
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                let event = timeout(Duration::from_secs(5), events.recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(event.workspace_id, "ws");
+                assert!(!event.full);
+                assert_eq!(event.paths, vec!["a.txt", "b.txt", "c.txt"]);
+                assert_eq!(cached_paths(&cache), vec!["a.txt", "b.txt", "c.txt"]);
+                tokio::time::sleep(Duration::from_millis(400)).await;
+                assert!(events.try_recv().is_err());
+
+                tx.send(vec![git_dir.join("objects").join("ab")]).unwrap();
+                tokio::time::sleep(Duration::from_millis(400)).await;
+                assert!(events.try_recv().is_err());
+
+                tx.send(vec![git_dir.join("index"), root.join("a.txt")])
+                    .unwrap();
+                let event = timeout(Duration::from_secs(5), events.recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert!(event.full);
+                assert!(event.paths.is_empty());
+                task.abort();
+            });
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn diffs_are_recomputed_only_for_changed_paths() {
+        let (root, repo) = repo_with(&[("a.txt", "one\n"), ("b.txt", "one\n")]);
+        let diffs = SharedDiffCache::default();
+        let limits = DiffLimits::unlimited();
+        let patched = |diffs: &SharedDiffCache, limits: &DiffLimits| {
+            cached_file_diffs(diffs, &repo, limits)
+                .unwrap()
+                .into_iter()
+                .map(|diff| (diff.path, diff.additions, diff.truncated))
+                .collect::<Vec<_>>()
+        };
+
+        write(&root, "a.txt", "one\ntwo\n");
+        assert_eq!(
+            patched(&diffs, &limits),
+            vec![("a.txt".to_string(), 1, false)]
+        );
+
+        // Unreported edits keep their cached diff until the watcher sees them.
+        write(&root, "a.txt", "one\ntwo\nthree\n");
+        write(&root, "b.txt", "one\ntwo\n");
+        assert_eq!(
+            patched(&diffs, &limits),
+            vec![("a.txt".to_string(), 1, false)]
+        );
+
+        diffs
+            .lock()
+            .unwrap()
+            .record(&Change::Path("b.txt".to_string()));
+        assert_eq!(
+            patched(&diffs, &limits),
+            vec![
+                ("a.txt".to_string(), 1, false),
+                ("b.txt".to_string(), 1, false)
+            ]
+        );
+
+        diffs.lock().unwrap().record(&Change::Full);
+        assert_eq!(
+            patched(&diffs, &limits),
+            vec![
+                ("a.txt".to_string(), 2, false),
+                ("b.txt".to_string(), 1, false)
+            ]
+        );
+
+        // The total budget is applied across the cached files on every call.
+        let first = cached_file_diffs(&diffs, &repo, &limits).unwrap()[0]
+            .diff
+            .len();
+        let budget = DiffLimits {
+            max_total_bytes: first,
+            ..limits
+        };
+        assert_eq!(
+            patched(&diffs, &budget),
+            vec![
+                ("a.txt".to_string(), 2, false),
+                ("b.txt".to_string(), 1, true)
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -2,20 +2,29 @@ use tauri::menu::{Menu, MenuItemBuilder, PredefinedMenuItem, Submenu};
 use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
 
 mod backend;
+mod clone;
 mod codex;
 mod codex_config;
 mod dictation;
 mod event_sink;
+mod forge;
 mod git;
 mod git_utils;
+mod git_watcher;
 mod prompts;
+mod sandbox;
+mod secrets;
 mod settings;
 mod state;
 mod terminal;
 mod storage;
+mod storage_watcher;
 mod types;
 mod utils;
+mod workspace_manifest;
 mod workspaces;
+mod worktree_paths;
+mod worktree_setup;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -207,6 +216,9 @@ pub fn run() {
         .setup(|app| {
             let state = state::AppState::load(&app.handle());
             app.manage(state);
+            if let Err(err) = storage_watcher::start_storage_watcher(app.handle().clone()) {
+                eprintln!("Failed to watch the data dir: {err}");
+            }
             #[cfg(desktop)]
             app.handle()
                 .plugin(tauri_plugin_updater::Builder::new().build())?;
@@ -218,13 +230,28 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             settings::get_app_settings,
             settings::update_app_settings,
+            settings::get_storage_issues,
+            settings::set_secret,
+            settings::clear_secret,
             codex::codex_doctor,
             workspaces::list_workspaces,
             workspaces::add_workspace,
             workspaces::add_worktree,
+            clone::clone_workspace,
+            workspace_manifest::export_workspace_manifest,
+            workspace_manifest::import_workspace_manifest,
+            workspaces::start_github_issue_worktree,
+            workspaces::checkout_pull_request_worktree,
             workspaces::remove_workspace,
             workspaces::remove_worktree,
             workspaces::apply_worktree_changes,
+            workspaces::integrate_worktree,
+            workspaces::sync_worktrees,
+            workspaces::apply_worktree_sync,
+            worktree_setup::get_worktree_setup_status,
+            worktree_setup::wait_for_worktree_setup,
+            worktree_setup::rerun_worktree_setup,
+            worktree_setup::cancel_worktree_setup,
             workspaces::update_workspace_settings,
             workspaces::update_workspace_codex_bin,
             codex::start_thread,
@@ -238,23 +265,52 @@ pub fn run() {
             codex::collaboration_mode_list,
             workspaces::connect_workspace,
             git::get_git_status,
+            git::watch_git_status,
+            git::unwatch_git_status,
             git::list_git_roots,
             git::get_git_diffs,
+            git::get_git_file_diff,
             git::get_git_log,
+            git::get_git_blame,
+            git::get_git_file_log,
             git::get_git_remote,
             git::stage_git_file,
             git::unstage_git_file,
             git::revert_git_file,
             git::revert_git_all,
             git::get_github_issues,
+            git::get_github_issue,
             git::get_github_pull_requests,
             git::get_github_pull_request_diff,
             git::get_github_pull_request_comments,
+            git::preview_github_pull_request_review,
+            git::get_github_checks,
+            forge::get_forge_remote,
+            forge::get_forge_issues,
+            forge::get_forge_merge_requests,
+            forge::get_forge_merge_request_diff,
+            forge::get_forge_merge_request_comments,
+            git::send_github_check_failures_to_agent,
+            git::submit_github_pull_request_review,
+            git::create_github_pull_request,
+            git::draft_github_pull_request_with_agent,
             workspaces::list_workspace_files,
             workspaces::open_workspace_in,
             git::list_git_branches,
             git::checkout_git_branch,
             git::create_git_branch,
+            git::list_git_stashes,
+            git::stash_git_changes,
+            git::apply_git_stash,
+            git::pop_git_stash,
+            git::drop_git_stash,
+            git::get_git_stash_diff,
+            git::get_git_conflicts,
+            git::get_git_conflict_versions,
+            git::take_git_conflict_ours,
+            git::take_git_conflict_theirs,
+            git::mark_git_conflict_resolved,
+            git::resolve_git_conflicts_with_agent,
             codex::model_list,
             codex::account_rate_limits,
             codex::skills_list,
@@ -64,7 +64,8 @@ fn require_workspace_entry(
 
 fn app_data_dir(state: &State<'_, AppState>) -> Result<PathBuf, String> {
     state
-        .settings_path
+        .settings_file
+        .path()
         .parent()
         .map(|path| path.to_path_buf())
         .ok_or_else(|| "Unable to resolve app data dir.".to_string())
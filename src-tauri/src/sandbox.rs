@@ -0,0 +1,334 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::types::{
+    AppSettings, ApprovalPolicy, SandboxProfile, WorkspaceEntry, WorkspaceSettings,
+};
+use crate::worktree_paths::expand_home;
+
+/// What a turn runs with once the caller's choices are filled in from the
+/// workspace defaults (and, for the access mode, the global default).
+pub(crate) struct TurnPolicy {
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+    pub(crate) sandbox_policy: Value,
+    pub(crate) approval_policy: ApprovalPolicy,
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Picks the sandbox for a turn: a profile or access mode chosen for the
+/// message wins, then the workspace's profile or default access mode, then
+/// the global default access mode.
+pub(crate) fn resolve_turn_policy(
+    model: Option<String>,
+    effort: Option<String>,
+    access_mode: Option<String>,
+    sandbox_profile: Option<String>,
+    workspace_path: &str,
+    workspace: &WorkspaceSettings,
+    app_settings: &AppSettings,
+) -> Result<TurnPolicy, String> {
+    let profile_name = non_empty(sandbox_profile).or_else(|| {
+        if non_empty(access_mode.clone()).is_some() {
+            None
+        } else {
+            non_empty(workspace.sandbox_profile.clone())
+        }
+    });
+    let (sandbox_policy, approval_policy) = match profile_name {
+        Some(name) => {
+            let profile = app_settings
+                .sandbox_profiles
+                .iter()
+                .find(|profile| profile.name == name)
+                .ok_or_else(|| format!("Unknown sandbox profile: {name}"))?;
+            sandbox_policy_for_profile(profile, workspace_path)
+        }
+        None => {
+            let access_mode = non_empty(access_mode)
+                .or_else(|| non_empty(workspace.default_access_mode.clone()))
+                .or_else(|| non_empty(Some(app_settings.default_access_mode.clone())))
+                .unwrap_or_else(|| "current".to_string());
+            sandbox_policy_for_access_mode(&access_mode, workspace_path, workspace)
+        }
+    };
+    Ok(TurnPolicy {
+        model: non_empty(model).or_else(|| non_empty(workspace.default_model.clone())),
+        effort: non_empty(effort).or_else(|| non_empty(workspace.default_effort.clone())),
+        sandbox_policy,
+        approval_policy,
+    })
+}
+
+/// The workspace followed by `roots`, with relative roots resolved against
+/// the workspace.
+fn writable_roots(workspace_path: &str, roots: &[String]) -> Vec<String> {
+    let mut writable_roots = vec![workspace_path.to_string()];
+    for root in roots {
+        let root = root.trim();
+        if root.is_empty() {
+            continue;
+        }
+        let path = expand_home(root);
+        let path = if path.is_absolute() {
+            path
+        } else {
+            PathBuf::from(workspace_path).join(path)
+        };
+        writable_roots.push(path.to_string_lossy().to_string());
+    }
+    writable_roots
+}
+
+/// Maps an access mode to app-server `sandboxPolicy` JSON and its approval
+/// policy. The workspace adds writable roots and network access to the
+/// `current` mode and may override the approval policy of any mode.
+pub(crate) fn sandbox_policy_for_access_mode(
+    access_mode: &str,
+    workspace_path: &str,
+    workspace: &WorkspaceSettings,
+) -> (Value, ApprovalPolicy) {
+    let sandbox_policy = match access_mode {
+        "full-access" => json!({
+            "type": "dangerFullAccess"
+        }),
+        "read-only" => json!({
+            "type": "readOnly"
+        }),
+        _ => json!({
+            "type": "workspaceWrite",
+            "writableRoots": writable_roots(workspace_path, &workspace.writable_roots),
+            "networkAccess": workspace.network_access.unwrap_or(true)
+        }),
+    };
+    let approval_policy = workspace
+        .approval_policy
+        .unwrap_or(if access_mode == "full-access" {
+            ApprovalPolicy::Never
+        } else {
+            ApprovalPolicy::OnRequest
+        });
+    (sandbox_policy, approval_policy)
+}
+
+pub(crate) fn sandbox_policy_for_profile(
+    profile: &SandboxProfile,
+    workspace_path: &str,
+) -> (Value, ApprovalPolicy) {
+    let sandbox_policy = json!({
+        "type": "workspaceWrite",
+        "writableRoots": writable_roots(workspace_path, &profile.writable_roots),
+        "networkAccess": profile.network_access
+    });
+    (sandbox_policy, profile.approval_policy)
+}
+
+/// Profiles are picked by name, so names must be present and unique, and a
+/// profile can't be removed or renamed while a workspace still uses it.
+pub(crate) fn validate_sandbox_profiles<'a>(
+    profiles: &[SandboxProfile],
+    workspaces: impl IntoIterator<Item = &'a WorkspaceEntry>,
+) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for profile in profiles {
+        let name = profile.name.trim();
+        if name.is_empty() {
+            return Err("Sandbox profiles need a name.".to_string());
+        }
+        if name != profile.name {
+            return Err(format!(
+                "Sandbox profile name \"{}\" has surrounding whitespace.",
+                profile.name
+            ));
+        }
+        if !names.insert(name) {
+            return Err(format!("Duplicate sandbox profile: {name}"));
+        }
+    }
+    for workspace in workspaces {
+        if let Some(name) = non_empty(workspace.settings.sandbox_profile.clone()) {
+            if !names.contains(name.as_str()) {
+                return Err(format!(
+                    "Sandbox profile \"{name}\" is used by {}; pick another profile there first.",
+                    workspace.name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A workspace can only pick a profile that exists.
+pub(crate) fn validate_workspace_sandbox_profile(
+    workspace: &WorkspaceSettings,
+    profiles: &[SandboxProfile],
+) -> Result<(), String> {
+    match non_empty(workspace.sandbox_profile.clone()) {
+        Some(name) if !profiles.iter().any(|profile| profile.name == name) => {
+            Err(format!("Unknown sandbox profile: {name}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        resolve_turn_policy, validate_sandbox_profiles, validate_workspace_sandbox_profile,
+    };
+    use crate::types::{
+        AppSettings, ApprovalPolicy, SandboxProfile, WorkspaceEntry, WorkspaceKind,
+        WorkspaceSettings,
+    };
+
+    #[test]
+    fn workspace_defaults_apply_when_the_caller_omits_them() {
+        let workspace = WorkspaceSettings {
+            default_model: Some("gpt-5".to_string()),
+            default_access_mode: Some("read-only".to_string()),
+            ..Default::default()
+        };
+        let app_settings = AppSettings::default();
+
+        let policy =
+            resolve_turn_policy(None, None, None, None, "/repo", &workspace, &app_settings)
+                .expect("policy");
+        assert_eq!(policy.model.as_deref(), Some("gpt-5"));
+        assert_eq!(policy.sandbox_policy["type"], "readOnly");
+        assert_eq!(policy.approval_policy, ApprovalPolicy::OnRequest);
+
+        let policy = resolve_turn_policy(
+            Some("o3".to_string()),
+            Some("high".to_string()),
+            Some("full-access".to_string()),
+            None,
+            "/repo",
+            &workspace,
+            &app_settings,
+        )
+        .expect("policy");
+        assert_eq!(policy.model.as_deref(), Some("o3"));
+        assert_eq!(policy.effort.as_deref(), Some("high"));
+        assert_eq!(policy.sandbox_policy["type"], "dangerFullAccess");
+        assert_eq!(policy.approval_policy, ApprovalPolicy::Never);
+    }
+
+    #[test]
+    fn workspace_sandbox_settings_extend_the_current_mode() {
+        let workspace = WorkspaceSettings {
+            writable_roots: vec!["../shared".to_string(), "/var/cache/build".to_string()],
+            network_access: Some(false),
+            approval_policy: Some(ApprovalPolicy::Untrusted),
+            ..Default::default()
+        };
+        let policy = resolve_turn_policy(
+            None,
+            None,
+            None,
+            None,
+            "/repo",
+            &workspace,
+            &AppSettings::default(),
+        )
+        .expect("policy");
+        assert_eq!(policy.sandbox_policy["type"], "workspaceWrite");
+        assert_eq!(
+            policy.sandbox_policy["writableRoots"],
+            serde_json::json!(["/repo", "/repo/../shared", "/var/cache/build"])
+        );
+        assert_eq!(policy.sandbox_policy["networkAccess"], false);
+        assert_eq!(policy.approval_policy, ApprovalPolicy::Untrusted);
+    }
+
+    #[test]
+    fn sandbox_profiles_apply_per_message_or_per_workspace() {
+        let mut app_settings = AppSettings {
+            sandbox_profiles: vec![SandboxProfile {
+                name: "offline".to_string(),
+                writable_roots: vec!["~/.cache/cargo".to_string()],
+                network_access: false,
+                approval_policy: ApprovalPolicy::OnFailure,
+            }],
+            ..Default::default()
+        };
+        let workspace = WorkspaceSettings {
+            sandbox_profile: Some("offline".to_string()),
+            ..Default::default()
+        };
+
+        let policy =
+            resolve_turn_policy(None, None, None, None, "/repo", &workspace, &app_settings)
+                .expect("workspace profile");
+        assert_eq!(policy.sandbox_policy["networkAccess"], false);
+        assert_eq!(policy.sandbox_policy["writableRoots"][0], "/repo");
+        assert_eq!(policy.approval_policy, ApprovalPolicy::OnFailure);
+
+        let policy = resolve_turn_policy(
+            None,
+            None,
+            Some("read-only".to_string()),
+            None,
+            "/repo",
+            &workspace,
+            &app_settings,
+        )
+        .expect("message access mode");
+        assert_eq!(policy.sandbox_policy["type"], "readOnly");
+
+        assert!(resolve_turn_policy(
+            None,
+            None,
+            None,
+            Some("missing".to_string()),
+            "/repo",
+            &workspace,
+            &app_settings,
+        )
+        .is_err());
+        app_settings
+            .sandbox_profiles
+            .push(app_settings.sandbox_profiles[0].clone());
+        assert!(validate_sandbox_profiles(&app_settings.sandbox_profiles, []).is_err());
+    }
+
+    #[test]
+    fn profiles_in_use_cant_be_removed_or_renamed() {
+        let profile = SandboxProfile {
+            name: "offline".to_string(),
+            writable_roots: Vec::new(),
+            network_access: false,
+            approval_policy: ApprovalPolicy::OnRequest,
+        };
+        let workspace = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "api".to_string(),
+            path: "/repo".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                sandbox_profile: Some("offline".to_string()),
+                ..Default::default()
+            },
+        };
+
+        assert!(validate_sandbox_profiles(std::slice::from_ref(&profile), [&workspace]).is_ok());
+        assert!(validate_sandbox_profiles(&[], [&workspace]).is_err());
+        let renamed = SandboxProfile {
+            name: "sealed".to_string(),
+            ..profile.clone()
+        };
+        assert!(validate_sandbox_profiles(&[renamed], [&workspace]).is_err());
+
+        assert!(validate_workspace_sandbox_profile(&workspace.settings, &[profile]).is_ok());
+        assert!(validate_workspace_sandbox_profile(&workspace.settings, &[]).is_err());
+    }
+}
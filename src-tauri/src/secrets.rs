@@ -0,0 +1,489 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::storage::lock_data_file;
+use crate::types::{AppSettings, SecretKind, SecretRef, SecretStoreKind};
+
+const KEYRING_SERVICE: &str = "codex-monitor";
+/// Set to `file` to skip the OS keyring, e.g. on a headless machine.
+const STORE_ENV: &str = "CODEX_MONITOR_SECRET_STORE";
+/// Passphrase for the encrypted file. Without one a random key is kept in
+/// `secrets.key` next to it, readable by the current user only.
+const PASSPHRASE_ENV: &str = "CODEX_MONITOR_SECRETS_PASSPHRASE";
+const PBKDF2_ROUNDS: u32 = 600_000;
+const SECRET_FILE_VERSION: u32 = 1;
+
+pub(crate) fn secret_key(kind: SecretKind) -> &'static str {
+    match kind {
+        SecretKind::RemoteBackend => "remoteBackendToken",
+        SecretKind::GitHub => "githubToken",
+        SecretKind::GitLab => "gitlabToken",
+    }
+}
+
+pub(crate) fn secret_ref(settings: &AppSettings, kind: SecretKind) -> Option<&SecretRef> {
+    match kind {
+        SecretKind::RemoteBackend => settings.remote_backend_token.as_ref(),
+        SecretKind::GitHub => settings.github_token.as_ref(),
+        SecretKind::GitLab => settings.gitlab_token.as_ref(),
+    }
+}
+
+/// The settings field holding the reference for `kind`.
+pub(crate) fn secret_slot(settings: &mut AppSettings, kind: SecretKind) -> &mut Option<SecretRef> {
+    match kind {
+        SecretKind::RemoteBackend => &mut settings.remote_backend_token,
+        SecretKind::GitHub => &mut settings.github_token,
+        SecretKind::GitLab => &mut settings.gitlab_token,
+    }
+}
+
+/// Moves plain-text tokens from older settings files into the store.
+/// Returns whether the settings changed and need saving, and why any token
+/// stayed behind (it is tried again at the next start).
+pub(crate) fn adopt_legacy_secrets(
+    settings: &mut AppSettings,
+    store: &SecretStore,
+) -> (bool, Option<String>) {
+    let mut changed = false;
+    let mut errors = Vec::new();
+    for (kind, value) in std::mem::take(&mut settings.legacy_secrets) {
+        match store.set(kind, &value) {
+            Ok(secret) => {
+                *secret_slot(settings, kind) = Some(secret);
+                changed = true;
+            }
+            Err(err) => {
+                errors.push(format!(
+                    "Failed to move {} to the secret store: {err}",
+                    secret_key(kind)
+                ));
+                settings.legacy_secrets.insert(kind, value);
+            }
+        }
+    }
+    let error = (!errors.is_empty()).then(|| errors.join("\n"));
+    (changed, error)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+enum KeySource {
+    Passphrase,
+    MachineKey,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedSecret {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecretFile {
+    version: u32,
+    key_source: KeySource,
+    salt: String,
+    /// PBKDF2 rounds for the passphrase; `0` when the random key in
+    /// `secrets.key` is used as is.
+    rounds: u32,
+    #[serde(default)]
+    secrets: BTreeMap<String, SealedSecret>,
+}
+
+/// The cipher for the key settings of the file it was made for.
+struct CachedCipher {
+    key_source: KeySource,
+    salt: String,
+    rounds: u32,
+    cipher: Aes256Gcm,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    if !text.len().is_multiple_of(2) {
+        return Err("Invalid hex data.".to_string());
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&text[index..index + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn is_keyring_unavailable(err: &keyring::Error) -> bool {
+    matches!(
+        err,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
+
+/// Stores secrets in the OS keyring, falling back to an encrypted file in
+/// the data dir when no keyring is reachable.
+pub(crate) struct SecretStore {
+    file_path: PathBuf,
+    use_keyring: bool,
+    rounds: u32,
+    /// Deriving the key from a passphrase is deliberately slow, so it is
+    /// done once per file.
+    cipher: StdMutex<Option<CachedCipher>>,
+}
+
+impl SecretStore {
+    pub(crate) fn new(data_dir: &Path) -> Self {
+        let use_keyring = std::env::var(STORE_ENV)
+            .map(|value| !value.trim().eq_ignore_ascii_case("file"))
+            .unwrap_or(true);
+        Self {
+            file_path: data_dir.join("secrets.json"),
+            use_keyring,
+            rounds: PBKDF2_ROUNDS,
+            cipher: StdMutex::new(None),
+        }
+    }
+
+    /// Runs `task` on the blocking pool: keyring calls can wait on the OS
+    /// and the encrypted file is read from and written to disk.
+    pub(crate) async fn run<T: Send + 'static>(
+        self: &Arc<Self>,
+        task: impl FnOnce(&SecretStore) -> T + Send + 'static,
+    ) -> Result<T, String> {
+        let store = Arc::clone(self);
+        tokio::task::spawn_blocking(move || task(&store))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn set(&self, kind: SecretKind, value: &str) -> Result<SecretRef, String> {
+        let key = secret_key(kind);
+        if self.use_keyring {
+            let saved = keyring::Entry::new(KEYRING_SERVICE, key)
+                .and_then(|entry| entry.set_password(value));
+            match saved {
+                Ok(()) => {
+                    // The reference points at the keyring, so an old copy
+                    // left in the file is never read.
+                    let _ = self.remove_from_file(key);
+                    return Ok(SecretRef {
+                        store: SecretStoreKind::Keyring,
+                        key: key.to_string(),
+                    });
+                }
+                Err(err) if is_keyring_unavailable(&err) => {}
+                Err(err) => return Err(format!("Failed to save {key} in the keyring: {err}")),
+            }
+        }
+        self.update_file(|secrets, cipher| {
+            let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+            let ciphertext = cipher
+                .encrypt(
+                    &nonce,
+                    Payload {
+                        msg: value.as_bytes(),
+                        aad: key.as_bytes(),
+                    },
+                )
+                .map_err(|_| format!("Failed to encrypt {key}."))?;
+            secrets.insert(
+                key.to_string(),
+                SealedSecret {
+                    nonce: to_hex(&nonce),
+                    ciphertext: to_hex(&ciphertext),
+                },
+            );
+            Ok(())
+        })?;
+        Ok(SecretRef {
+            store: SecretStoreKind::EncryptedFile,
+            key: key.to_string(),
+        })
+    }
+
+    /// Reads a secret; `None` when it was deleted behind our back.
+    pub(crate) fn get(&self, secret: &SecretRef) -> Result<Option<String>, String> {
+        match secret.store {
+            SecretStoreKind::Keyring => {
+                let entry =
+                    keyring::Entry::new(KEYRING_SERVICE, &secret.key).map_err(|e| e.to_string())?;
+                match entry.get_password() {
+                    Ok(value) => Ok(Some(value)),
+                    Err(keyring::Error::NoEntry) => Ok(None),
+                    Err(err) => Err(format!(
+                        "Failed to read {} from the keyring: {err}",
+                        secret.key
+                    )),
+                }
+            }
+            SecretStoreKind::EncryptedFile => {
+                let Some(file) = self.read_file()? else {
+                    return Ok(None);
+                };
+                let Some(sealed) = file.secrets.get(&secret.key) else {
+                    return Ok(None);
+                };
+                let cipher = self.cipher(&file)?;
+                let nonce = from_hex(&sealed.nonce)?;
+                if nonce.len() != 12 {
+                    return Err(format!("Invalid nonce for {}.", secret.key));
+                }
+                let plaintext = cipher
+                    .decrypt(
+                        Nonce::from_slice(&nonce),
+                        Payload {
+                            msg: &from_hex(&sealed.ciphertext)?,
+                            aad: secret.key.as_bytes(),
+                        },
+                    )
+                    .map_err(|_| {
+                        format!(
+                            "Could not decrypt {}; the passphrase or key file changed.",
+                            secret.key
+                        )
+                    })?;
+                String::from_utf8(plaintext)
+                    .map(Some)
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    pub(crate) fn delete(&self, secret: &SecretRef) -> Result<(), String> {
+        match secret.store {
+            SecretStoreKind::Keyring => {
+                let entry =
+                    keyring::Entry::new(KEYRING_SERVICE, &secret.key).map_err(|e| e.to_string())?;
+                match entry.delete_credential() {
+                    Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                    Err(err) => Err(format!(
+                        "Failed to remove {} from the keyring: {err}",
+                        secret.key
+                    )),
+                }
+            }
+            SecretStoreKind::EncryptedFile => self.remove_from_file(&secret.key),
+        }
+    }
+
+    /// Removing a secret needs no key, so it works even when the file is
+    /// locked with a passphrase that is not set.
+    fn remove_from_file(&self, key: &str) -> Result<(), String> {
+        if !self.file_path.exists() {
+            return Ok(());
+        }
+        let _lock = lock_data_file(&self.file_path)?;
+        let Some(mut file) = self.read_file()? else {
+            return Ok(());
+        };
+        if file.secrets.remove(key).is_none() {
+            return Ok(());
+        }
+        let data = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        write_private(&self.file_path, data.as_bytes())
+    }
+
+    fn key_material(&self, source: KeySource) -> Result<Vec<u8>, String> {
+        if source == KeySource::Passphrase {
+            return std::env::var(PASSPHRASE_ENV)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(String::into_bytes)
+                .ok_or_else(|| format!("The secrets file is locked; set {PASSPHRASE_ENV}."));
+        }
+        let key_path = self.file_path.with_file_name("secrets.key");
+        match std::fs::read_to_string(&key_path) {
+            Ok(key) => from_hex(key.trim()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                write_private(&key_path, to_hex(&key).as_bytes())?;
+                Ok(key.to_vec())
+            }
+            Err(err) => Err(format!("Failed to read {}: {err}", key_path.display())),
+        }
+    }
+
+    fn cipher(&self, file: &SecretFile) -> Result<Aes256Gcm, String> {
+        let mut cached = self
+            .cipher
+            .lock()
+            .map_err(|_| "Secret store lock poisoned.")?;
+        if let Some(cached) = cached.as_ref().filter(|cached| {
+            cached.key_source == file.key_source
+                && cached.salt == file.salt
+                && cached.rounds == file.rounds
+        }) {
+            return Ok(cached.cipher.clone());
+        }
+        let material = self.key_material(file.key_source)?;
+        let key: [u8; 32] = match (file.key_source, file.rounds) {
+            (KeySource::MachineKey, 0) => material
+                .try_into()
+                .map_err(|_| "secrets.key does not hold a 256-bit key.".to_string())?,
+            (KeySource::Passphrase, 0) => {
+                return Err(format!(
+                    "{} has no key derivation rounds.",
+                    self.file_path.display()
+                ))
+            }
+            (_, rounds) => {
+                let salt = from_hex(&file.salt)?;
+                let mut key = [0u8; 32];
+                pbkdf2::pbkdf2_hmac::<Sha256>(&material, &salt, rounds, &mut key);
+                key
+            }
+        };
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        *cached = Some(CachedCipher {
+            key_source: file.key_source,
+            salt: file.salt.clone(),
+            rounds: file.rounds,
+            cipher: cipher.clone(),
+        });
+        Ok(cipher)
+    }
+
+    fn read_file(&self) -> Result<Option<SecretFile>, String> {
+        let data = match std::fs::read_to_string(&self.file_path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.to_string()),
+        };
+        let file: SecretFile = serde_json::from_str(&data)
+            .map_err(|e| format!("Failed to read {}: {e}", self.file_path.display()))?;
+        if file.version > SECRET_FILE_VERSION {
+            return Err(format!(
+                "{} was written by a newer version of the app.",
+                self.file_path.display()
+            ));
+        }
+        Ok(Some(file))
+    }
+
+    /// Applies `change` to the sealed secrets under the data file lock. A
+    /// new file is keyed with the passphrase when one is set.
+    fn update_file(
+        &self,
+        change: impl FnOnce(&mut BTreeMap<String, SealedSecret>, &Aes256Gcm) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let _lock = lock_data_file(&self.file_path)?;
+        let mut file = match self.read_file()? {
+            Some(file) => file,
+            None => {
+                let key_source = if std::env::var(PASSPHRASE_ENV).is_ok_and(|v| !v.is_empty()) {
+                    KeySource::Passphrase
+                } else {
+                    KeySource::MachineKey
+                };
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                // The random key needs no stretching.
+                let rounds = match key_source {
+                    KeySource::Passphrase => self.rounds,
+                    KeySource::MachineKey => 0,
+                };
+                SecretFile {
+                    version: SECRET_FILE_VERSION,
+                    key_source,
+                    salt: to_hex(&salt),
+                    rounds,
+                    secrets: BTreeMap::new(),
+                }
+            }
+        };
+        let cipher = self.cipher(&file)?;
+        change(&mut file.secrets, &cipher)?;
+        let data = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        write_private(&self.file_path, data.as_bytes())
+    }
+}
+
+/// Writes `path` atomically, readable by the current user only.
+fn write_private(path: &Path, data: &[u8]) -> Result<(), String> {
+    let parent = path.parent().ok_or("Invalid secrets path.")?;
+    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    let temp_path = parent.join(format!(".secrets.{}.tmp", Uuid::new_v4()));
+    let result = (|| {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&temp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result.map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use super::{adopt_legacy_secrets, SecretStore};
+    use crate::types::{AppSettings, SecretKind, SecretStoreKind};
+    use uuid::Uuid;
+
+    fn file_store() -> SecretStore {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        SecretStore {
+            file_path: temp_dir.join("secrets.json"),
+            use_keyring: false,
+            rounds: 1_000,
+            cipher: StdMutex::new(None),
+        }
+    }
+
+    #[test]
+    fn encrypted_file_round_trips_without_plain_text() {
+        let store = file_store();
+        let secret = store
+            .set(SecretKind::GitHub, "ghp_secret")
+            .expect("set secret");
+        assert_eq!(secret.store, SecretStoreKind::EncryptedFile);
+        assert_eq!(
+            store.get(&secret).expect("get secret").as_deref(),
+            Some("ghp_secret")
+        );
+        let data = std::fs::read_to_string(&store.file_path).expect("read file");
+        assert!(!data.contains("ghp_secret"));
+        // The random key file is used as the key without derivation.
+        let file: serde_json::Value = serde_json::from_str(&data).expect("parse file");
+        assert_eq!(file["rounds"], 0);
+
+        store.delete(&secret).expect("delete secret");
+        assert!(store.get(&secret).expect("get deleted").is_none());
+    }
+
+    #[test]
+    fn legacy_secrets_move_into_the_store() {
+        let store = file_store();
+        let mut settings = AppSettings::default();
+        settings
+            .legacy_secrets
+            .insert(SecretKind::RemoteBackend, "daemon-token".to_string());
+        assert_eq!(adopt_legacy_secrets(&mut settings, &store), (true, None));
+        assert!(settings.legacy_secrets.is_empty());
+        let secret = settings.remote_backend_token.expect("reference saved");
+        assert_eq!(
+            store.get(&secret).expect("get secret").as_deref(),
+            Some("daemon-token")
+        );
+        let json = serde_json::to_string(&AppSettings::default()).expect("serialize");
+        assert!(!json.contains("legacySecrets"));
+    }
+}
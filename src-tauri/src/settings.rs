@@ -1,13 +1,16 @@
 use tauri::State;
 
 use crate::codex_config;
+use crate::sandbox::validate_sandbox_profiles;
+use crate::secrets::{secret_ref, secret_slot};
 use crate::state::AppState;
 use crate::storage::write_settings;
-use crate::types::AppSettings;
+use crate::types::{AppSettings, SecretKind, StorageIssue};
 
 #[tauri::command]
 pub(crate) async fn get_app_settings(state: State<'_, AppState>) -> Result<AppSettings, String> {
     let mut settings = state.app_settings.lock().await.clone();
+    settings.legacy_secrets.clear();
     if let Ok(Some(collab_enabled)) = codex_config::read_collab_enabled() {
         settings.experimental_collab_enabled = collab_enabled;
     }
@@ -22,14 +25,98 @@ pub(crate) async fn get_app_settings(state: State<'_, AppState>) -> Result<AppSe
 
 #[tauri::command]
 pub(crate) async fn update_app_settings(
-    settings: AppSettings,
+    mut settings: AppSettings,
     state: State<'_, AppState>,
 ) -> Result<AppSettings, String> {
+    {
+        let workspaces = state.workspaces.lock().await;
+        validate_sandbox_profiles(&settings.sandbox_profiles, workspaces.values())?;
+    }
     let _ = codex_config::write_collab_enabled(settings.experimental_collab_enabled);
     let _ = codex_config::write_steer_enabled(settings.experimental_steer_enabled);
     let _ = codex_config::write_unified_exec_enabled(settings.experimental_unified_exec_enabled);
-    write_settings(&state.settings_path, &settings)?;
     let mut current = state.app_settings.lock().await;
+    settings.keep_secrets_from(&current);
+    write_settings(&state.settings_file, &settings)?;
+    *current = settings.clone();
+    settings.legacy_secrets.clear();
+    Ok(settings)
+}
+
+/// Saves a secret in the secret store; settings only keep a reference.
+#[tauri::command]
+pub(crate) async fn set_secret(
+    kind: SecretKind,
+    value: String,
+    state: State<'_, AppState>,
+) -> Result<AppSettings, String> {
+    let value = value.trim().to_string();
+    if value.is_empty() {
+        return Err("Secret is empty; use clear_secret to remove it.".to_string());
+    }
+    let mut current = state.app_settings.lock().await;
+    let mut settings = current.clone();
+    let secret = state
+        .secrets
+        .run(move |secrets| secrets.set(kind, &value))
+        .await??;
+    if let Some(previous) = secret_slot(&mut settings, kind).replace(secret.clone()) {
+        // A copy left in the other store would resurface if this one fails.
+        if previous.store != secret.store {
+            let _ = state
+                .secrets
+                .run(move |secrets| secrets.delete(&previous))
+                .await;
+        }
+    }
+    settings.legacy_secrets.remove(&kind);
+    write_settings(&state.settings_file, &settings)?;
     *current = settings.clone();
+    settings.legacy_secrets.clear();
     Ok(settings)
 }
+
+#[tauri::command]
+pub(crate) async fn clear_secret(
+    kind: SecretKind,
+    state: State<'_, AppState>,
+) -> Result<AppSettings, String> {
+    let mut current = state.app_settings.lock().await;
+    let mut settings = current.clone();
+    if let Some(previous) = secret_slot(&mut settings, kind).take() {
+        state
+            .secrets
+            .run(move |secrets| secrets.delete(&previous))
+            .await??;
+    }
+    settings.legacy_secrets.remove(&kind);
+    write_settings(&state.settings_file, &settings)?;
+    *current = settings.clone();
+    settings.legacy_secrets.clear();
+    Ok(settings)
+}
+
+/// Reads a saved secret, or `None` when none is set or it cannot be read.
+pub(crate) async fn read_secret(state: &AppState, kind: SecretKind) -> Option<String> {
+    let secret = secret_ref(&*state.app_settings.lock().await, kind).cloned()?;
+    match state
+        .secrets
+        .run(move |secrets| secrets.get(&secret))
+        .await
+        .and_then(|value| value)
+    {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!("{err}");
+            None
+        }
+    }
+}
+
+/// Data files that could not be read at startup and what was done instead.
+#[tauri::command]
+pub(crate) async fn get_storage_issues(
+    state: State<'_, AppState>,
+) -> Result<Vec<StorageIssue>, String> {
+    Ok(state.storage_issues.lock().await.clone())
+}
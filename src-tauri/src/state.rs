@@ -1,22 +1,35 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
 use std::sync::Arc;
 
 use tauri::{AppHandle, Manager};
 use tokio::sync::Mutex;
 
+use crate::backend::worktree_setup::WorktreeSetupRun;
 use crate::dictation::DictationState;
-use crate::storage::{read_settings, read_workspaces};
-use crate::types::{AppSettings, WorkspaceEntry};
+use crate::forge::{ForgeCache, GitHubApiState};
+use crate::secrets::{adopt_legacy_secrets, SecretStore};
+use crate::storage::{
+    load_settings, load_workspaces, remove_plaintext_token_backups, untouched_issue,
+    write_settings, DataFile,
+};
+use crate::types::{AppSettings, StorageIssue, WorkspaceEntry};
 
 pub(crate) struct AppState {
     pub(crate) workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
     pub(crate) sessions: Mutex<HashMap<String, Arc<crate::codex::WorkspaceSession>>>,
     pub(crate) terminal_sessions:
         Mutex<HashMap<String, Arc<crate::terminal::TerminalSession>>>,
-    pub(crate) storage_path: PathBuf,
-    pub(crate) settings_path: PathBuf,
+    pub(crate) git_status_watchers:
+        Mutex<HashMap<String, Arc<crate::git_watcher::GitStatusWatcher>>>,
+    pub(crate) worktree_setups: Mutex<HashMap<String, WorktreeSetupRun>>,
+    pub(crate) forge_cache: Mutex<ForgeCache>,
+    pub(crate) github_api: Arc<Mutex<GitHubApiState>>,
+    pub(crate) workspaces_file: DataFile,
+    pub(crate) settings_file: DataFile,
     pub(crate) app_settings: Mutex<AppSettings>,
+    pub(crate) secrets: Arc<SecretStore>,
+    /// Data files that were unreadable at startup, for the UI to report.
+    pub(crate) storage_issues: Mutex<Vec<StorageIssue>>,
     pub(crate) dictation: Mutex<DictationState>,
 }
 
@@ -26,17 +39,56 @@ impl AppState {
             .path()
             .app_data_dir()
             .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| ".".into()));
-        let storage_path = data_dir.join("workspaces.json");
-        let settings_path = data_dir.join("settings.json");
-        let workspaces = read_workspaces(&storage_path).unwrap_or_default();
-        let app_settings = read_settings(&settings_path).unwrap_or_default();
+        let workspaces_file = DataFile::new(data_dir.join("workspaces.json"));
+        let settings_file = DataFile::new(data_dir.join("settings.json"));
+        let (workspaces, workspaces_issue) =
+            load_workspaces(&workspaces_file).unwrap_or_else(|error| {
+                (
+                    HashMap::new(),
+                    Some(untouched_issue(&workspaces_file, error)),
+                )
+            });
+        let (mut app_settings, settings_issue) =
+            load_settings(&settings_file).unwrap_or_else(|error| {
+                (
+                    AppSettings::default(),
+                    Some(untouched_issue(&settings_file, error)),
+                )
+            });
+        let secrets = SecretStore::new(&data_dir);
+        let (adopted, adopt_error) = adopt_legacy_secrets(&mut app_settings, &secrets);
+        let saved = !adopted
+            || write_settings(&settings_file, &app_settings)
+                .map_err(|err| eprintln!("Failed to save settings: {err}"))
+                .is_ok();
+        // Backups from before the migration still hold the tokens in plain text.
+        if saved && settings_issue.is_none() && app_settings.legacy_secrets.is_empty() {
+            if let Err(err) = remove_plaintext_token_backups(&settings_file) {
+                eprintln!("Failed to remove old settings backups: {err}");
+            }
+        }
+        let secrets_issue = adopt_error.map(|error| untouched_issue(&settings_file, error));
+        let storage_issues: Vec<_> = workspaces_issue
+            .into_iter()
+            .chain(settings_issue)
+            .chain(secrets_issue)
+            .collect();
+        for issue in &storage_issues {
+            eprintln!("Failed to read {}: {}", issue.file, issue.error);
+        }
         Self {
             workspaces: Mutex::new(workspaces),
             sessions: Mutex::new(HashMap::new()),
             terminal_sessions: Mutex::new(HashMap::new()),
-            storage_path,
-            settings_path,
+            git_status_watchers: Mutex::new(HashMap::new()),
+            worktree_setups: Mutex::new(HashMap::new()),
+            forge_cache: Mutex::new(ForgeCache::default()),
+            github_api: Arc::new(Mutex::new(GitHubApiState::default())),
+            workspaces_file,
+            settings_file,
             app_settings: Mutex::new(app_settings),
+            secrets: Arc::new(secrets),
+            storage_issues: Mutex::new(storage_issues),
             dictation: Mutex::new(DictationState::default()),
         }
     }
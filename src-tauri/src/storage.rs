@@ -1,56 +1,641 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::types::{AppSettings, WorkspaceEntry};
+use notify::Watcher;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use uuid::Uuid;
 
-pub(crate) fn read_workspaces(path: &PathBuf) -> Result<HashMap<String, WorkspaceEntry>, String> {
+use crate::types::{AppSettings, StorageIssue, WorkspaceEntry};
+
+pub(crate) const WORKSPACES_SCHEMA_VERSION: u64 = 1;
+pub(crate) const SETTINGS_SCHEMA_VERSION: u64 = 2;
+
+/// How many rolling backups are kept per file.
+const BACKUP_COUNT: usize = 5;
+/// Backups rotate at most this often, so frequent saves keep older history.
+const BACKUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+type Migration = fn(Value) -> Result<Value, String>;
+
+/// `WORKSPACE_MIGRATIONS[n]` upgrades a version `n` document to `n + 1`.
+const WORKSPACE_MIGRATIONS: &[Migration] = &[migrate_workspaces_v0];
+/// `SETTINGS_MIGRATIONS[n]` upgrades a version `n` document to `n + 1`.
+const SETTINGS_MIGRATIONS: &[Migration] = &[migrate_settings_v0, migrate_settings_v1];
+
+/// Version 0 stored a bare array of workspaces.
+fn migrate_workspaces_v0(value: Value) -> Result<Value, String> {
+    if !value.is_array() {
+        return Err("Expected a list of workspaces.".to_string());
+    }
+    Ok(json!({ "schemaVersion": 1, "workspaces": value }))
+}
+
+/// Version 0 settings had no version field and need no other changes.
+fn migrate_settings_v0(mut value: Value) -> Result<Value, String> {
+    let object = value.as_object_mut().ok_or("Expected a settings object.")?;
+    object.insert("schemaVersion".to_string(), json!(1));
+    Ok(value)
+}
+
+/// Version 1 kept tokens in plain text. They wait in `legacySecrets` until
+/// the secret store takes them over at startup.
+fn migrate_settings_v1(mut value: Value) -> Result<Value, String> {
+    let object = value.as_object_mut().ok_or("Expected a settings object.")?;
+    let mut legacy = serde_json::Map::new();
+    for key in ["remoteBackendToken", "githubToken"] {
+        if let Some(Value::String(token)) = object.remove(key) {
+            if !token.trim().is_empty() {
+                legacy.insert(key.to_string(), json!(token.trim()));
+            }
+        }
+    }
+    if !legacy.is_empty() {
+        object.insert("legacySecrets".to_string(), Value::Object(legacy));
+    }
+    object.insert("schemaVersion".to_string(), json!(2));
+    Ok(value)
+}
+
+fn schema_version(value: &Value) -> u64 {
+    value
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+fn migrate(mut value: Value, current: u64, migrations: &[Migration]) -> Result<Value, String> {
+    let mut version = schema_version(&value);
+    while version < current {
+        let migration = migrations
+            .get(version as usize)
+            .ok_or_else(|| format!("No migration from schema {version}."))?;
+        value = migration(value)?;
+        version = schema_version(&value);
+    }
+    Ok(value)
+}
+
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name("backups")
+        .join(format!("{name}.{index}"))
+}
+
+/// Copies the current file into the backup slots, shifting older backups
+/// down and dropping the oldest.
+fn rotate_backups(path: &Path) -> std::io::Result<()> {
     if !path.exists() {
-        return Ok(HashMap::new());
+        return Ok(());
+    }
+    let newest = backup_path(path, 1);
+    let recent = std::fs::metadata(&newest)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < BACKUP_INTERVAL);
+    if recent {
+        return Ok(());
+    }
+    if let Some(parent) = newest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    for index in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, index);
+        if from.exists() {
+            std::fs::rename(&from, backup_path(path, index + 1))?;
+        }
+    }
+    std::fs::copy(path, &newest)?;
+    Ok(())
+}
+
+/// Replaces `path` with `data` via a synced temp file and a rename, so a
+/// crash leaves either the old or the new contents.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    let parent = path.parent().ok_or("Invalid storage path.")?;
+    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    rotate_backups(path).map_err(|e| format!("Failed to back up {}: {e}", path.display()))?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = parent.join(format!(".{name}.{}.tmp", Uuid::new_v4()));
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        // Persist the rename itself; directories cannot be opened on Windows.
+        #[cfg(unix)]
+        std::fs::File::open(parent)?.sync_all()?;
+        Ok::<_, std::io::Error>(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result.map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// A data file's contents keyed for merging: workspaces by id, settings by
+/// field name.
+type Entries = BTreeMap<String, Value>;
+
+struct DataFormat {
+    label: &'static str,
+    schema_version: u64,
+    migrations: &'static [Migration],
+    /// Splits a migrated document into entries, normalized through the
+    /// typed structs so missing defaults compare equal.
+    entries: fn(Value) -> Result<Entries, String>,
+    document: fn(&Entries) -> Value,
+}
+
+const WORKSPACES_FORMAT: DataFormat = DataFormat {
+    label: "Workspace",
+    schema_version: WORKSPACES_SCHEMA_VERSION,
+    migrations: WORKSPACE_MIGRATIONS,
+    entries: workspace_entries,
+    document: workspaces_document,
+};
+
+const SETTINGS_FORMAT: DataFormat = DataFormat {
+    label: "Setting",
+    schema_version: SETTINGS_SCHEMA_VERSION,
+    migrations: SETTINGS_MIGRATIONS,
+    entries: settings_entries,
+    document: settings_document,
+};
+
+fn workspace_entries(document: Value) -> Result<Entries, String> {
+    let list: Vec<WorkspaceEntry> =
+        serde_json::from_value(document.get("workspaces").cloned().unwrap_or(json!([])))
+            .map_err(|e| e.to_string())?;
+    list.into_iter()
+        .map(|entry| {
+            let value = serde_json::to_value(&entry).map_err(|e| e.to_string())?;
+            Ok((entry.id, value))
+        })
+        .collect()
+}
+
+fn workspaces_document(entries: &Entries) -> Value {
+    json!({ "workspaces": entries.values().collect::<Vec<_>>() })
+}
+
+fn settings_entries(document: Value) -> Result<Entries, String> {
+    let settings: AppSettings = serde_json::from_value(document).map_err(|e| e.to_string())?;
+    match serde_json::to_value(settings).map_err(|e| e.to_string())? {
+        Value::Object(map) => Ok(map.into_iter().collect()),
+        _ => Err("Expected a settings object.".to_string()),
+    }
+}
+
+fn settings_document(entries: &Entries) -> Value {
+    Value::Object(entries.clone().into_iter().collect())
+}
+
+/// What this process last read from or wrote to a data file: the base for
+/// merging writes other processes made in the meantime.
+struct Snapshot {
+    revision: u64,
+    entries: Entries,
+    /// Set when a write merged in or was rejected because of changes this
+    /// process has not loaded yet.
+    stale: bool,
+}
+
+/// A data file shared with other processes, with this process's snapshot
+/// of it. Reads and writes go through the same `DataFile`.
+pub(crate) struct DataFile {
+    path: PathBuf,
+    snapshot: StdMutex<Option<Snapshot>>,
+    /// Where to ask for a reload when a write ran into unloaded changes.
+    reload_requests: StdMutex<Option<mpsc::UnboundedSender<PathBuf>>>,
+}
+
+impl DataFile {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            snapshot: StdMutex::new(None),
+            reload_requests: StdMutex::new(None),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn base(&self) -> Option<(u64, Entries)> {
+        self.snapshot.lock().ok().and_then(|snapshot| {
+            snapshot
+                .as_ref()
+                .map(|snapshot| (snapshot.revision, snapshot.entries.clone()))
+        })
+    }
+
+    fn remember(&self, revision: u64, entries: Entries, stale: bool) {
+        if let Ok(mut snapshot) = self.snapshot.lock() {
+            *snapshot = Some(Snapshot {
+                revision,
+                entries,
+                stale,
+            });
+        }
+        if stale {
+            let tx = self.reload_requests.lock().ok().and_then(|tx| tx.clone());
+            if let Some(tx) = tx {
+                let _ = tx.send(self.path.clone());
+            }
+        }
+    }
+}
+
+/// Why a data file could not be read. Only `Invalid` files are treated as
+/// corrupt; the others are left alone.
+#[derive(Debug)]
+enum ReadError {
+    /// Opening or reading failed, e.g. permission denied.
+    Io(String),
+    /// Written by a newer version of the app.
+    Newer(String),
+    /// The contents do not parse or migrate.
+    Invalid(String),
+}
+
+impl ReadError {
+    fn into_message(self) -> String {
+        match self {
+            ReadError::Io(message) | ReadError::Newer(message) | ReadError::Invalid(message) => {
+                message
+            }
+        }
+    }
+}
+
+/// Reads, migrates and splits a data file; `None` when it does not exist.
+fn read_entries(path: &Path, format: &DataFormat) -> Result<Option<(u64, Entries)>, ReadError> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(ReadError::Io(format!(
+                "Failed to read {}: {err}",
+                path.display()
+            )))
+        }
+    };
+    let value: Value =
+        serde_json::from_str(&data).map_err(|e| ReadError::Invalid(e.to_string()))?;
+    let version = schema_version(&value);
+    if version > format.schema_version {
+        return Err(ReadError::Newer(format!(
+            "{} was written by a newer version of the app (schema {version}, expected {}).",
+            path.display(),
+            format.schema_version
+        )));
     }
-    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let list: Vec<WorkspaceEntry> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
-    Ok(list
-        .into_iter()
-        .map(|entry| (entry.id.clone(), entry))
-        .collect())
+    let value =
+        migrate(value, format.schema_version, format.migrations).map_err(ReadError::Invalid)?;
+    let revision = value.get("revision").and_then(Value::as_u64).unwrap_or(0);
+    let entries = (format.entries)(value).map_err(ReadError::Invalid)?;
+    Ok(Some((revision, entries)))
 }
 
-pub(crate) fn write_workspaces(path: &PathBuf, entries: &[WorkspaceEntry]) -> Result<(), String> {
+/// Takes an exclusive advisory lock on `<file>.lock`, held until the
+/// returned file is dropped. Every process sharing the data dir locks
+/// before writing.
+pub(crate) fn lock_data_file(path: &Path) -> Result<File, String> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
-    let data = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_file_name(format!("{name}.lock")))
+        .map_err(|e| format!("Failed to open lock for {}: {e}", path.display()))?;
+    lock.lock()
+        .map_err(|e| format!("Failed to lock {}: {e}", path.display()))?;
+    Ok(lock)
 }
 
-pub(crate) fn read_settings(path: &PathBuf) -> Result<AppSettings, String> {
-    if !path.exists() {
+/// Three-way merges entries: a side's change wins where the other side left
+/// the entry as it was in `base`. Returns the first key both sides changed
+/// differently.
+fn merge_entries(base: &Entries, ours: &Entries, theirs: &Entries) -> Result<Entries, String> {
+    let keys: BTreeSet<&String> = base
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .collect();
+    let mut merged = Entries::new();
+    for key in keys {
+        let (base_value, our_value, their_value) = (base.get(key), ours.get(key), theirs.get(key));
+        let value = if our_value == base_value || our_value == their_value {
+            their_value
+        } else if their_value == base_value {
+            our_value
+        } else {
+            return Err(key.clone());
+        };
+        if let Some(value) = value {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(merged)
+}
+
+fn write_entries(file: &DataFile, format: &DataFormat, ours: Entries) -> Result<(), String> {
+    let path = file.path();
+    let _lock = lock_data_file(path)?;
+    // Never write over a file we could not read: it may hold another
+    // process's data, or a newer version's.
+    let disk = read_entries(path, format)
+        .map_err(|err| format!("Not saving {}: {}", path.display(), err.into_message()))?;
+    let base = file.base();
+    let (revision, entries, merged) = match (disk, base) {
+        (Some((disk_revision, theirs)), Some((base_revision, base)))
+            if disk_revision != base_revision =>
+        {
+            match merge_entries(&base, &ours, &theirs) {
+                Ok(entries) => {
+                    let merged = entries != ours;
+                    (disk_revision + 1, entries, merged)
+                }
+                Err(key) => {
+                    file.remember(base_revision, base, true);
+                    return Err(format!(
+                        "{} {key} was changed by another process. Reloaded the latest data; try again.",
+                        format.label
+                    ));
+                }
+            }
+        }
+        (Some((disk_revision, _)), _) => (disk_revision + 1, ours, false),
+        (None, base) => (base.map_or(0, |(revision, _)| revision) + 1, ours, false),
+    };
+
+    let mut document = (format.document)(&entries);
+    if let Some(object) = document.as_object_mut() {
+        object.insert("schemaVersion".to_string(), json!(format.schema_version));
+        object.insert("revision".to_string(), json!(revision));
+    }
+    let data = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+    write_atomic(path, data.as_bytes())?;
+    file.remember(revision, entries, merged);
+    Ok(())
+}
+
+fn try_read_workspaces(file: &DataFile) -> Result<HashMap<String, WorkspaceEntry>, ReadError> {
+    let Some((revision, entries)) = read_entries(file.path(), &WORKSPACES_FORMAT)? else {
+        return Ok(HashMap::new());
+    };
+    let workspaces = entries
+        .iter()
+        .map(|(id, value)| {
+            serde_json::from_value(value.clone())
+                .map(|entry| (id.clone(), entry))
+                .map_err(|e| ReadError::Invalid(e.to_string()))
+        })
+        .collect::<Result<_, ReadError>>()?;
+    file.remember(revision, entries, false);
+    Ok(workspaces)
+}
+
+pub(crate) fn read_workspaces(file: &DataFile) -> Result<HashMap<String, WorkspaceEntry>, String> {
+    try_read_workspaces(file).map_err(ReadError::into_message)
+}
+
+pub(crate) fn write_workspaces(file: &DataFile, entries: &[WorkspaceEntry]) -> Result<(), String> {
+    let entries = entries
+        .iter()
+        .map(|entry| {
+            let value = serde_json::to_value(entry).map_err(|e| e.to_string())?;
+            Ok((entry.id.clone(), value))
+        })
+        .collect::<Result<Entries, String>>()?;
+    write_entries(file, &WORKSPACES_FORMAT, entries)
+}
+
+fn try_read_settings(file: &DataFile) -> Result<AppSettings, ReadError> {
+    let Some((revision, entries)) = read_entries(file.path(), &SETTINGS_FORMAT)? else {
         return Ok(AppSettings::default());
+    };
+    let settings = serde_json::from_value(settings_document(&entries))
+        .map_err(|e| ReadError::Invalid(e.to_string()))?;
+    file.remember(revision, entries, false);
+    Ok(settings)
+}
+
+pub(crate) fn read_settings(file: &DataFile) -> Result<AppSettings, String> {
+    try_read_settings(file).map_err(ReadError::into_message)
+}
+
+pub(crate) fn write_settings(file: &DataFile, settings: &AppSettings) -> Result<(), String> {
+    let entries = settings_entries(serde_json::to_value(settings).map_err(|e| e.to_string())?)?;
+    write_entries(file, &SETTINGS_FORMAT, entries)
+}
+
+/// Deletes settings backups that still hold plain-text tokens, from before
+/// the secret store took them over. Call once no legacy secrets are left.
+pub(crate) fn remove_plaintext_token_backups(file: &DataFile) -> Result<(), String> {
+    for index in 1..=BACKUP_COUNT {
+        let path = backup_path(file.path(), index);
+        let Some(value) = std::fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<Value>(&data).ok())
+        else {
+            continue;
+        };
+        let holds_token = value.get("legacySecrets").is_some()
+            || ["remoteBackendToken", "githubToken"]
+                .iter()
+                .any(|key| value.get(key).is_some_and(Value::is_string));
+        if holds_token {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+        }
     }
-    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&data).map_err(|e| e.to_string())
+    Ok(())
 }
 
-pub(crate) fn write_settings(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
+/// Whether a data file holds changes this process has not loaded: another
+/// process wrote it, or one of our writes merged in or was rejected
+/// because of such changes.
+pub(crate) fn has_unloaded_changes(file: &DataFile) -> bool {
+    let revision = std::fs::read_to_string(file.path())
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        .map(|value| value.get("revision").and_then(Value::as_u64).unwrap_or(0));
+    let Ok(snapshot) = file.snapshot.lock() else {
+        return true;
+    };
+    match (snapshot.as_ref(), revision) {
+        (Some(snapshot), Some(revision)) => snapshot.stale || snapshot.revision != revision,
+        (None, Some(_)) => true,
+        (_, None) => false,
+    }
+}
+
+/// Watches data files for writes by other processes, sending a file's path
+/// whenever it may need reloading. Keep the returned watcher alive.
+pub(crate) fn watch_data_files(
+    files: &[&DataFile],
+    tx: mpsc::UnboundedSender<PathBuf>,
+) -> Result<notify::RecommendedWatcher, String> {
+    for file in files {
+        if let Ok(mut requests) = file.reload_requests.lock() {
+            *requests = Some(tx.clone());
+        }
+    }
+    let paths: Vec<PathBuf> = files.iter().map(|file| file.path.clone()).collect();
+    let targets = paths.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if event.kind.is_access() {
+            return;
+        }
+        for target in &targets {
+            if event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == target.file_name())
+            {
+                let _ = tx.send(target.clone());
+            }
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    let mut parents: Vec<&Path> = paths.iter().filter_map(|path| path.parent()).collect();
+    parents.dedup();
+    for parent in parents {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        watcher
+            .watch(parent, notify::RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(watcher)
+}
+
+/// Reads a data file at startup. A corrupt file is moved aside (so the next
+/// write cannot destroy it) and the newest readable backup is used instead;
+/// the returned issue describes what happened. Files written by a newer
+/// version and IO failures are returned as errors and left in place.
+fn load_or_recover<T: Default>(
+    file: &DataFile,
+    read: fn(&DataFile) -> Result<T, ReadError>,
+) -> Result<(T, Option<StorageIssue>), String> {
+    let path = file.path();
+    let error = match read(file) {
+        Ok(value) => return Ok((value, None)),
+        Err(ReadError::Invalid(err)) => err,
+        Err(err) => return Err(err.into_message()),
+    };
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let preserved = path.with_file_name(format!("{name}.corrupt-{stamp}"));
+    let preserved_path = std::fs::rename(path, &preserved)
+        .ok()
+        .map(|_| preserved.to_string_lossy().to_string());
+
+    let mut issue = StorageIssue {
+        file: path.to_string_lossy().to_string(),
+        error,
+        preserved_path,
+        restored_from: None,
+    };
+    for index in 1..=BACKUP_COUNT {
+        let backup = backup_path(path, index);
+        if !backup.exists() {
+            continue;
+        }
+        if let Ok(value) = read(&DataFile::new(backup.clone())) {
+            issue.restored_from = Some(backup.to_string_lossy().to_string());
+            // The data is loaded either way; without the copy the next write
+            // recreates the file.
+            if let Err(err) = std::fs::copy(&backup, path) {
+                issue.error = format!("{}; restoring the backup failed: {err}", issue.error);
+            }
+            return Ok((value, Some(issue)));
+        }
+    }
+    Ok((T::default(), Some(issue)))
+}
+
+/// Describes a data file that could not be loaded and was left as it is.
+/// Writes to it fail until it can be read again.
+pub(crate) fn untouched_issue(file: &DataFile, error: String) -> StorageIssue {
+    StorageIssue {
+        file: file.path().to_string_lossy().to_string(),
+        error,
+        preserved_path: None,
+        restored_from: None,
     }
-    let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+}
+
+pub(crate) fn load_workspaces(
+    file: &DataFile,
+) -> Result<(HashMap<String, WorkspaceEntry>, Option<StorageIssue>), String> {
+    load_or_recover(file, try_read_workspaces)
+}
+
+pub(crate) fn load_settings(
+    file: &DataFile,
+) -> Result<(AppSettings, Option<StorageIssue>), String> {
+    load_or_recover(file, try_read_settings)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{read_workspaces, write_workspaces};
-    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use super::{
+        backup_path, has_unloaded_changes, load_settings, load_workspaces, read_settings,
+        read_workspaces, remove_plaintext_token_backups, write_settings, write_workspaces,
+        DataFile, WORKSPACES_SCHEMA_VERSION,
+    };
+    use crate::types::{SecretKind, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
     use uuid::Uuid;
 
+    fn workspace(id: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: "Workspace".to_string(),
+            path: "/tmp".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
     #[test]
     fn write_read_workspaces_persists_sort_and_group() {
         let temp_dir =
             std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
         std::fs::create_dir_all(&temp_dir).expect("create temp dir");
-        let path = temp_dir.join("workspaces.json");
+        let file = DataFile::new(temp_dir.join("workspaces.json"));
 
         let mut settings = WorkspaceSettings::default();
         settings.sort_order = Some(5);
@@ -69,12 +654,188 @@ mod tests {
             settings: settings.clone(),
         };
 
-        write_workspaces(&path, &[entry]).expect("write workspaces");
-        let read = read_workspaces(&path).expect("read workspaces");
+        write_workspaces(&file, &[entry]).expect("write workspaces");
+        let read = read_workspaces(&file).expect("read workspaces");
         let stored = read.get("w1").expect("stored workspace");
         assert_eq!(stored.settings.sort_order, Some(5));
         assert_eq!(stored.settings.group_id.as_deref(), Some("group-42"));
         assert!(stored.settings.sidebar_collapsed);
         assert_eq!(stored.settings.git_root.as_deref(), Some("/tmp"));
     }
+
+    #[test]
+    fn legacy_files_are_migrated_and_newer_schemas_rejected() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).expect("create temp dir");
+        let file = DataFile::new(temp_dir.join("workspaces.json"));
+        let path = file.path();
+
+        let legacy = serde_json::to_string(&vec![workspace("w1")]).expect("serialize");
+        std::fs::write(path, legacy).expect("write legacy");
+        assert!(read_workspaces(&file)
+            .expect("read legacy")
+            .contains_key("w1"));
+
+        write_workspaces(&file, &[workspace("w1")]).expect("write workspaces");
+        let stored: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).expect("read")).expect("json");
+        assert_eq!(stored["schemaVersion"], WORKSPACES_SCHEMA_VERSION);
+
+        std::fs::write(path, r#"{"schemaVersion": 99, "workspaces": []}"#).expect("write");
+        assert!(read_workspaces(&file).is_err());
+
+        let settings_file = DataFile::new(temp_dir.join("settings.json"));
+        let settings_path = settings_file.path();
+        std::fs::write(settings_path, r#"{"uiScale": 1.5}"#).expect("write settings");
+        let settings = read_settings(&settings_file).expect("read legacy settings");
+        assert_eq!(settings.ui_scale, 1.5);
+
+        std::fs::write(
+            settings_path,
+            r#"{"schemaVersion": 1, "githubToken": "ghp_x", "remoteBackendToken": ""}"#,
+        )
+        .expect("write v1 settings");
+        let settings = read_settings(&settings_file).expect("read v1 settings");
+        assert!(settings.github_token.is_none());
+        assert_eq!(
+            settings
+                .legacy_secrets
+                .get(&SecretKind::GitHub)
+                .map(String::as_str),
+            Some("ghp_x")
+        );
+        assert!(!settings
+            .legacy_secrets
+            .contains_key(&SecretKind::RemoteBackend));
+    }
+
+    #[test]
+    fn adopted_tokens_do_not_linger_in_backups() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).expect("create temp dir");
+        let file = DataFile::new(temp_dir.join("settings.json"));
+        std::fs::write(
+            file.path(),
+            r#"{"schemaVersion": 1, "githubToken": "ghp_secret", "uiScale": 1.5}"#,
+        )
+        .expect("write v1 settings");
+
+        let mut settings = read_settings(&file).expect("read v1 settings");
+        write_settings(&file, &settings).expect("save with legacy secrets");
+        std::fs::rename(backup_path(file.path(), 1), backup_path(file.path(), 3))
+            .expect("age backup");
+        settings.legacy_secrets.clear();
+        write_settings(&file, &settings).expect("save after adoption");
+        std::fs::write(backup_path(file.path(), 2), r#"{"schemaVersion": 2}"#)
+            .expect("write clean backup");
+        for index in [1, 4] {
+            let backup = std::fs::read_to_string(backup_path(file.path(), index)).expect("read");
+            assert!(backup.contains("ghp_secret"));
+        }
+
+        remove_plaintext_token_backups(&file).expect("remove backups");
+        let backups = std::fs::read_dir(temp_dir.join("backups")).expect("backups");
+        let mut remaining = 0;
+        for backup in backups {
+            let contents = std::fs::read_to_string(backup.expect("entry").path()).expect("read");
+            assert!(!contents.contains("ghp_secret"));
+            remaining += 1;
+        }
+        assert_eq!(remaining, 1);
+        assert!(!std::fs::read_to_string(file.path())
+            .expect("read settings")
+            .contains("ghp_secret"));
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn corrupt_workspaces_are_preserved_and_restored_from_backup() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let file = DataFile::new(temp_dir.join("workspaces.json"));
+        let path = file.path();
+
+        write_workspaces(&file, &[workspace("w1")]).expect("first write");
+        write_workspaces(&file, &[workspace("w1"), workspace("w2")]).expect("second write");
+        assert!(backup_path(path, 1).exists());
+
+        std::fs::write(path, "{\"schemaVersion\": 1, \"workspa").expect("corrupt");
+        let (workspaces, issue) = load_workspaces(&file).expect("recovered");
+        let issue = issue.expect("corruption is reported");
+        assert!(workspaces.contains_key("w1"));
+        assert!(issue.restored_from.is_some());
+        let preserved = issue.preserved_path.expect("corrupt file kept");
+        assert!(std::fs::read_to_string(preserved)
+            .expect("read preserved")
+            .contains("workspa"));
+        assert!(read_workspaces(&file).is_ok());
+    }
+
+    #[test]
+    fn newer_and_unreadable_files_are_left_in_place() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let file = DataFile::new(temp_dir.join("workspaces.json"));
+        let path = file.path();
+        write_workspaces(&file, &[workspace("w1")]).expect("first write");
+        write_workspaces(&file, &[workspace("w1"), workspace("w2")]).expect("second write");
+
+        let newer = r#"{"schemaVersion": 99, "workspaces": []}"#;
+        std::fs::write(path, newer).expect("write newer");
+        let err = load_workspaces(&file).expect_err("newer schema");
+        assert!(err.contains("newer version"));
+        assert!(write_workspaces(&file, &[workspace("w1")]).is_err());
+        assert_eq!(std::fs::read_to_string(path).expect("read"), newer);
+
+        // A directory in place of the file fails to read without being corrupt.
+        let settings_file = DataFile::new(temp_dir.join("settings.json"));
+        let settings_path = settings_file.path();
+        std::fs::create_dir_all(settings_path).expect("create dir");
+        assert!(load_settings(&settings_file).is_err());
+        assert!(settings_path.is_dir());
+
+        let quarantined = std::fs::read_dir(&temp_dir)
+            .expect("read dir")
+            .filter_map(Result::ok)
+            .any(|entry| entry.file_name().to_string_lossy().contains(".corrupt-"));
+        assert!(!quarantined);
+    }
+
+    #[test]
+    fn writes_merge_external_changes_and_reject_conflicts() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let file = DataFile::new(temp_dir.join("workspaces.json"));
+        let path = file.path();
+        write_workspaces(&file, &[workspace("w1")]).expect("first write");
+        assert!(!has_unloaded_changes(&file));
+
+        // Another process adds w3 behind our back.
+        let external = serde_json::json!({
+            "schemaVersion": WORKSPACES_SCHEMA_VERSION,
+            "revision": 7,
+            "workspaces": [workspace("w1"), workspace("w3")],
+        });
+        std::fs::write(path, external.to_string()).expect("external write");
+        assert!(has_unloaded_changes(&file));
+
+        write_workspaces(&file, &[workspace("w1"), workspace("w2")]).expect("merged write");
+        let stored = read_workspaces(&file).expect("read merged");
+        assert!(["w1", "w2", "w3"].iter().all(|id| stored.contains_key(*id)));
+
+        let mut renamed = workspace("w1");
+        renamed.name = "Theirs".to_string();
+        let external = serde_json::json!({
+            "schemaVersion": WORKSPACES_SCHEMA_VERSION,
+            "revision": 20,
+            "workspaces": [renamed, workspace("w2"), workspace("w3")],
+        });
+        std::fs::write(path, external.to_string()).expect("external write");
+        let mut ours = workspace("w1");
+        ours.name = "Ours".to_string();
+        let err = write_workspaces(&file, &[ours, workspace("w2"), workspace("w3")])
+            .expect_err("conflicting write");
+        assert!(err.contains("w1"));
+        assert!(has_unloaded_changes(&file));
+        let stored = read_workspaces(&file).expect("read latest");
+        assert_eq!(stored["w1"].name, "Theirs");
+        assert!(!has_unloaded_changes(&file));
+    }
 }
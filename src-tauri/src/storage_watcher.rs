@@ -0,0 +1,78 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+
+use crate::backend::app_server::{approval_rules_by_workspace, update_approval_rules};
+use crate::backend::events::{EventSink, StorageChanged};
+use crate::event_sink::TauriEventSink;
+use crate::state::AppState;
+use crate::storage::{has_unloaded_changes, read_settings, read_workspaces, watch_data_files};
+
+/// Atomic writes show up as several events; let them settle first.
+const SETTLE_DELAY: Duration = Duration::from_millis(150);
+
+/// Reloads `workspaces.json` and `settings.json` when another process (such
+/// as the daemon sharing the data dir) changes them.
+pub(crate) fn start_storage_watcher(app: AppHandle) -> Result<(), String> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let watcher = {
+        let state = app.state::<AppState>();
+        watch_data_files(&[&state.workspaces_file, &state.settings_file], tx)?
+    };
+    tauri::async_runtime::spawn(async move {
+        let _watcher = watcher;
+        while let Some(path) = rx.recv().await {
+            tokio::time::sleep(SETTLE_DELAY).await;
+            let mut changed = BTreeSet::from([path]);
+            while let Ok(path) = rx.try_recv() {
+                changed.insert(path);
+            }
+            for path in changed {
+                reload(&app, &path).await;
+            }
+        }
+    });
+    Ok(())
+}
+
+async fn reload(app: &AppHandle, path: &Path) {
+    let state = app.state::<AppState>();
+    let event_sink = TauriEventSink::new(app.clone());
+    // Hold the in-memory copy while reading so a concurrent command cannot
+    // write between the read and the swap.
+    if path == state.workspaces_file.path() {
+        let rules = {
+            let mut workspaces = state.workspaces.lock().await;
+            if !has_unloaded_changes(&state.workspaces_file) {
+                return;
+            }
+            match read_workspaces(&state.workspaces_file) {
+                Ok(latest) => {
+                    *workspaces = latest;
+                    approval_rules_by_workspace(&workspaces)
+                }
+                Err(err) => {
+                    eprintln!("Failed to reload {}: {err}", path.display());
+                    return;
+                }
+            }
+        };
+        update_approval_rules(&state.sessions, rules).await;
+        event_sink.emit_storage_changed(StorageChanged { file: "workspaces" });
+    } else if path == state.settings_file.path() {
+        let mut settings = state.app_settings.lock().await;
+        if !has_unloaded_changes(&state.settings_file) {
+            return;
+        }
+        match read_settings(&state.settings_file) {
+            Ok(latest) => {
+                *settings = latest;
+                event_sink.emit_storage_changed(StorageChanged { file: "settings" });
+            }
+            Err(err) => eprintln!("Failed to reload {}: {err}", path.display()),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -12,6 +14,37 @@ pub(crate) struct GitFileStatus {
 pub(crate) struct GitFileDiff {
     pub(crate) path: String,
     pub(crate) diff: String,
+    #[serde(default)]
+    pub(crate) kind: GitDiffKind,
+    #[serde(default)]
+    pub(crate) additions: usize,
+    #[serde(default)]
+    pub(crate) deletions: usize,
+    #[serde(default)]
+    pub(crate) truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) image: Option<GitImageDiff>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum GitDiffKind {
+    #[default]
+    Text,
+    Binary,
+    Image,
+    Lockfile,
+    Minified,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitImageDiff {
+    #[serde(rename = "mimeType")]
+    pub(crate) mime_type: String,
+    /// Base64 image data, `None` when that side doesn't exist or is over
+    /// the diff size limit.
+    pub(crate) before: Option<String>,
+    pub(crate) after: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -22,6 +55,32 @@ pub(crate) struct GitLogEntry {
     pub(crate) timestamp: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitFileLogEntry {
+    #[serde(flatten)]
+    pub(crate) commit: GitLogEntry,
+    pub(crate) path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitBlameHunk {
+    #[serde(rename = "startLine")]
+    pub(crate) start_line: usize,
+    #[serde(rename = "lineCount")]
+    pub(crate) line_count: usize,
+    pub(crate) sha: String,
+    pub(crate) author: String,
+    #[serde(default)]
+    pub(crate) email: String,
+    pub(crate) timestamp: i64,
+    #[serde(default)]
+    pub(crate) summary: String,
+    #[serde(default, rename = "origPath")]
+    pub(crate) orig_path: Option<String>,
+    #[serde(default)]
+    pub(crate) committed: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitLogResponse {
     pub(crate) total: usize,
@@ -47,10 +106,48 @@ pub(crate) struct GitHubIssue {
     pub(crate) updated_at: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubLabel {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) color: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubIssueComment {
+    #[serde(default)]
+    pub(crate) author: Option<GitHubPullRequestAuthor>,
+    #[serde(default)]
+    pub(crate) body: String,
+    #[serde(rename = "createdAt")]
+    pub(crate) created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubIssueDetail {
+    pub(crate) number: u64,
+    pub(crate) title: String,
+    pub(crate) url: String,
+    #[serde(default)]
+    pub(crate) state: String,
+    #[serde(default)]
+    pub(crate) body: String,
+    #[serde(default)]
+    pub(crate) labels: Vec<GitHubLabel>,
+    #[serde(default)]
+    pub(crate) author: Option<GitHubPullRequestAuthor>,
+    #[serde(default)]
+    pub(crate) comments: Vec<GitHubIssueComment>,
+    #[serde(rename = "updatedAt")]
+    pub(crate) updated_at: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitHubIssuesResponse {
     pub(crate) total: usize,
     pub(crate) issues: Vec<GitHubIssue>,
+    #[serde(default, rename = "nextCursor")]
+    pub(crate) next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -83,6 +180,30 @@ pub(crate) struct GitHubPullRequestsResponse {
     pub(crate) total: usize,
     #[serde(rename = "pullRequests")]
     pub(crate) pull_requests: Vec<GitHubPullRequest>,
+    #[serde(default, rename = "nextCursor")]
+    pub(crate) next_cursor: Option<String>,
+}
+
+/// Filters and paging for issue and pull request lists. Every field is
+/// optional; the default is the first page of open items.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct ForgeListQuery {
+    /// `open`, `closed`, `merged` (pull requests only) or `all`.
+    #[serde(default)]
+    pub(crate) state: Option<String>,
+    #[serde(default)]
+    pub(crate) labels: Vec<String>,
+    #[serde(default)]
+    pub(crate) author: Option<String>,
+    #[serde(default)]
+    pub(crate) assignee: Option<String>,
+    #[serde(default)]
+    pub(crate) search: Option<String>,
+    /// Opaque cursor from a previous response's `nextCursor`.
+    #[serde(default)]
+    pub(crate) cursor: Option<String>,
+    #[serde(default, rename = "pageSize")]
+    pub(crate) page_size: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -92,6 +213,150 @@ pub(crate) struct GitHubPullRequestDiff {
     pub(crate) diff: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ForgeKind {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+/// Named after the settings field holding each reference.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum SecretKind {
+    #[serde(rename = "remoteBackendToken")]
+    RemoteBackend,
+    #[serde(rename = "githubToken")]
+    GitHub,
+    #[serde(rename = "gitlabToken")]
+    GitLab,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SecretStoreKind {
+    /// The OS keychain, credential manager or Secret Service.
+    Keyring,
+    /// `secrets.json` in the data dir, encrypted with a passphrase or a
+    /// random key kept in `secrets.key` next to it.
+    EncryptedFile,
+}
+
+/// Where a secret is kept. Settings store this instead of the value.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct SecretRef {
+    pub(crate) store: SecretStoreKind,
+    pub(crate) key: String,
+}
+
+/// The hosting service behind a repository's default remote.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct ForgeRemote {
+    pub(crate) kind: ForgeKind,
+    pub(crate) host: String,
+    /// `owner/repo`, or the full group path on GitLab.
+    pub(crate) repo: String,
+    #[serde(rename = "webUrl")]
+    pub(crate) web_url: String,
+    #[serde(rename = "apiBase")]
+    pub(crate) api_base: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubCheckRun {
+    pub(crate) id: u64,
+    pub(crate) name: String,
+    pub(crate) status: String,
+    #[serde(default)]
+    pub(crate) conclusion: Option<String>,
+    #[serde(default)]
+    pub(crate) url: Option<String>,
+    #[serde(default, rename = "startedAt")]
+    pub(crate) started_at: Option<String>,
+    #[serde(default, rename = "completedAt")]
+    pub(crate) completed_at: Option<String>,
+    #[serde(default, rename = "appSlug")]
+    pub(crate) app_slug: Option<String>,
+    #[serde(default, rename = "logExcerpt")]
+    pub(crate) log_excerpt: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubCommitStatus {
+    pub(crate) context: String,
+    pub(crate) state: String,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) url: Option<String>,
+    #[serde(default, rename = "updatedAt")]
+    pub(crate) updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubChecksResponse {
+    pub(crate) sha: String,
+    /// Overall result: `success`, `failure`, `pending` or `neutral` when nothing ran.
+    pub(crate) state: String,
+    #[serde(rename = "checkRuns")]
+    pub(crate) check_runs: Vec<GitHubCheckRun>,
+    pub(crate) statuses: Vec<GitHubCommitStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubReviewComment {
+    pub(crate) path: String,
+    pub(crate) line: u64,
+    /// `RIGHT` for added or context lines, `LEFT` for removed lines.
+    #[serde(default = "default_review_side")]
+    pub(crate) side: String,
+    #[serde(default, rename = "startLine", skip_serializing_if = "Option::is_none")]
+    pub(crate) start_line: Option<u64>,
+    pub(crate) body: String,
+}
+
+fn default_review_side() -> String {
+    "RIGHT".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubReviewDraft {
+    /// One of `COMMENT`, `APPROVE` or `REQUEST_CHANGES`.
+    pub(crate) event: String,
+    #[serde(default)]
+    pub(crate) body: String,
+    #[serde(default)]
+    pub(crate) comments: Vec<GitHubReviewComment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubReviewCommentPreview {
+    #[serde(flatten)]
+    pub(crate) comment: GitHubReviewComment,
+    pub(crate) excerpt: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubReviewPreview {
+    pub(crate) event: String,
+    pub(crate) body: String,
+    pub(crate) comments: Vec<GitHubReviewCommentPreview>,
+    pub(crate) errors: Vec<String>,
+    /// Present only when the review is valid; required to submit it.
+    pub(crate) digest: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubPullRequestReview {
+    pub(crate) id: u64,
+    pub(crate) state: String,
+    #[serde(default)]
+    pub(crate) url: String,
+    #[serde(default, rename = "submittedAt")]
+    pub(crate) submitted_at: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitHubPullRequestComment {
     pub(crate) id: u64,
@@ -105,12 +370,52 @@ pub(crate) struct GitHubPullRequestComment {
     pub(crate) author: Option<GitHubPullRequestAuthor>,
 }
 
+/// A pull request title and body an agent drafted on `thread_id`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubPullRequestDraft {
+    #[serde(rename = "threadId")]
+    pub(crate) thread_id: String,
+    pub(crate) title: String,
+    pub(crate) body: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct BranchInfo {
     pub(crate) name: String,
     pub(crate) last_commit: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitStashEntry {
+    pub(crate) index: usize,
+    pub(crate) message: String,
+    pub(crate) sha: String,
+    pub(crate) timestamp: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitConflictFile {
+    pub(crate) path: String,
+    pub(crate) kind: String,
+    #[serde(default, rename = "baseSha")]
+    pub(crate) base_sha: Option<String>,
+    #[serde(default, rename = "oursSha")]
+    pub(crate) ours_sha: Option<String>,
+    #[serde(default, rename = "theirsSha")]
+    pub(crate) theirs_sha: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitConflictVersions {
+    pub(crate) path: String,
+    #[serde(default)]
+    pub(crate) base: Option<String>,
+    #[serde(default)]
+    pub(crate) ours: Option<String>,
+    #[serde(default)]
+    pub(crate) theirs: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorkspaceEntry {
     pub(crate) id: String,
@@ -166,6 +471,181 @@ impl WorkspaceKind {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorktreeInfo {
     pub(crate) branch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) issue: Option<GitHubIssueLink>,
+    #[serde(
+        default,
+        rename = "pullRequest",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) pull_request: Option<GitHubPullRequestLink>,
+}
+
+/// The pull request a review worktree was checked out from.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubPullRequestLink {
+    pub(crate) number: u64,
+    pub(crate) title: String,
+    pub(crate) url: String,
+    #[serde(rename = "headRefName")]
+    pub(crate) head_ref_name: String,
+    #[serde(rename = "baseRefName")]
+    pub(crate) base_ref_name: String,
+}
+
+/// The GitHub issue a worktree was started from, and the thread working on it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubIssueLink {
+    pub(crate) number: u64,
+    pub(crate) title: String,
+    pub(crate) url: String,
+    #[serde(default, rename = "threadId")]
+    pub(crate) thread_id: Option<String>,
+}
+
+/// How a worktree on disk relates to the stored workspace entries.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WorktreeSyncStatus {
+    /// Tracked and checked out on the stored branch.
+    Ok,
+    /// Tracked, but the directory is gone; can be pruned.
+    Missing,
+    /// Known to git but not to the app; can be imported.
+    Untracked,
+    /// Tracked, but git lost track of the directory or it moved to another
+    /// branch; can be repaired.
+    Stale,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorktreeSyncItem {
+    pub(crate) path: String,
+    pub(crate) status: WorktreeSyncStatus,
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: Option<String>,
+    /// The branch git reports, `None` when detached or unknown to git.
+    pub(crate) branch: Option<String>,
+    #[serde(rename = "storedBranch")]
+    pub(crate) stored_branch: Option<String>,
+    pub(crate) locked: bool,
+    #[serde(rename = "diskUsageBytes")]
+    pub(crate) disk_usage_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WorktreeSyncActionKind {
+    Import,
+    Prune,
+    Repair,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorktreeSyncAction {
+    pub(crate) action: WorktreeSyncActionKind,
+    pub(crate) path: String,
+}
+
+/// How a worktree branch's commits are brought into the parent's branch.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum WorktreeIntegrationStrategy {
+    Merge,
+    Squash,
+    /// Rebase the worktree branch onto the parent's branch, then
+    /// fast-forward the parent.
+    Rebase,
+    CherryPick,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorktreeIntegrationResult {
+    pub(crate) strategy: WorktreeIntegrationStrategy,
+    #[serde(rename = "dryRun")]
+    pub(crate) dry_run: bool,
+    pub(crate) branch: String,
+    #[serde(rename = "targetBranch")]
+    pub(crate) target_branch: String,
+    /// The commits being integrated, newest first.
+    pub(crate) commits: Vec<GitLogEntry>,
+    /// For dry runs, the changes the parent's branch would receive.
+    pub(crate) diff: Vec<GitFileDiff>,
+    pub(crate) conflicts: Vec<GitConflictFile>,
+    /// The workspace where conflicts are left to resolve: the worktree when
+    /// rebasing, the parent otherwise.
+    #[serde(rename = "conflictWorkspaceId")]
+    pub(crate) conflict_workspace_id: Option<String>,
+    #[serde(rename = "headSha")]
+    pub(crate) head_sha: Option<String>,
+    #[serde(rename = "removedWorktree")]
+    pub(crate) removed_worktree: bool,
+}
+
+/// A data file that could not be read at startup. A corrupt file is kept
+/// next to the original rather than being overwritten; one written by a
+/// newer version, or that could not be opened, is left where it is.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct StorageIssue {
+    pub(crate) file: String,
+    pub(crate) error: String,
+    #[serde(rename = "preservedPath")]
+    pub(crate) preserved_path: Option<String>,
+    /// The backup the data was restored from, if any was readable.
+    #[serde(rename = "restoredFrom")]
+    pub(crate) restored_from: Option<String>,
+}
+
+/// A machine-independent description of a set of workspaces and their
+/// groups, meant to be shared and imported on another machine.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkspaceManifest {
+    pub(crate) version: u32,
+    #[serde(default)]
+    pub(crate) groups: Vec<WorkspaceGroup>,
+    #[serde(default)]
+    pub(crate) workspaces: Vec<WorkspaceManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkspaceManifestEntry {
+    pub(crate) name: String,
+    /// Where the workspace lives relative to the import root.
+    pub(crate) path: String,
+    #[serde(default, rename = "remoteUrl")]
+    pub(crate) remote_url: Option<String>,
+    /// The workspace's location inside its repository, when it is not the
+    /// repository root.
+    #[serde(default, rename = "repoSubdir")]
+    pub(crate) repo_subdir: Option<String>,
+    /// No longer exported, and ignored on import.
+    #[serde(default, rename = "codexBin", skip_serializing_if = "Option::is_none")]
+    pub(crate) codex_bin: Option<String>,
+    /// Layout settings only (sort order, group, git root); `groupId` refers
+    /// to the manifest's groups. Anything else is ignored on import.
+    #[serde(default)]
+    pub(crate) settings: WorkspaceSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WorkspaceImportStatus {
+    /// Already registered at the resolved path.
+    Existing,
+    /// Found on disk and registered.
+    Resolved,
+    /// Cloned from its remote and registered.
+    Cloned,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkspaceImportItem {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) status: WorkspaceImportStatus,
+    pub(crate) workspace: Option<WorkspaceInfo>,
+    pub(crate) error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -186,6 +666,127 @@ pub(crate) struct WorkspaceSettings {
     pub(crate) group_id: Option<String>,
     #[serde(default, rename = "gitRoot")]
     pub(crate) git_root: Option<String>,
+    #[serde(default, rename = "worktreeSetup")]
+    pub(crate) worktree_setup: WorktreeSetupConfig,
+    /// Where this workspace's worktrees are created, overriding the global
+    /// root. Relative paths resolve against the workspace.
+    #[serde(default, rename = "worktreeRoot")]
+    pub(crate) worktree_root: Option<String>,
+    /// Overrides the global branch name template for new worktrees.
+    #[serde(default, rename = "worktreeBranchTemplate")]
+    pub(crate) worktree_branch_template: Option<String>,
+    /// Used when a message does not pick a model.
+    #[serde(default, rename = "defaultModel")]
+    pub(crate) default_model: Option<String>,
+    /// Used when a message does not pick a reasoning effort.
+    #[serde(default, rename = "defaultEffort")]
+    pub(crate) default_effort: Option<String>,
+    /// Used when a message does not pick an access mode, before the global
+    /// `defaultAccessMode`.
+    #[serde(default, rename = "defaultAccessMode")]
+    pub(crate) default_access_mode: Option<String>,
+    /// Writable besides the workspace in the `current` access mode, e.g. a
+    /// shared cache. Relative paths resolve against the workspace.
+    #[serde(default, rename = "writableRoots")]
+    pub(crate) writable_roots: Vec<String>,
+    /// Network access in the `current` access mode; on when unset.
+    #[serde(default, rename = "networkAccess")]
+    pub(crate) network_access: Option<bool>,
+    /// Overrides the approval policy implied by the access mode.
+    #[serde(default, rename = "approvalPolicy")]
+    pub(crate) approval_policy: Option<ApprovalPolicy>,
+    /// Name of a profile in `sandboxProfiles`, used when a message does not
+    /// pick an access mode or profile.
+    #[serde(default, rename = "sandboxProfile")]
+    pub(crate) sandbox_profile: Option<String>,
+    #[serde(default, rename = "approvalRules")]
+    pub(crate) approval_rules: ApprovalRules,
+}
+
+/// Answers command and file change approval requests without asking. Deny
+/// rules win over allow rules; requests no rule settles go to the UI.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub(crate) struct ApprovalRules {
+    /// Command prefixes such as `cargo test`; `*` matches any text.
+    #[serde(default, rename = "allowCommands")]
+    pub(crate) allow_commands: Vec<String>,
+    /// Matched against every command in a pipeline or `&&` chain, e.g.
+    /// `rm -rf` or `curl`.
+    #[serde(default, rename = "denyCommands")]
+    pub(crate) deny_commands: Vec<String>,
+    /// `.gitignore`-style patterns relative to the workspace, e.g. `src/`.
+    #[serde(default, rename = "allowPaths")]
+    pub(crate) allow_paths: Vec<String>,
+    #[serde(default, rename = "denyPaths")]
+    pub(crate) deny_paths: Vec<String>,
+}
+
+/// A named sandbox: the workspace plus `writable_roots` are writable.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct SandboxProfile {
+    pub(crate) name: String,
+    /// Extra writable paths, e.g. a shared cache dir or a sibling repo.
+    /// Relative paths resolve against the workspace.
+    #[serde(default, rename = "writableRoots")]
+    pub(crate) writable_roots: Vec<String>,
+    #[serde(default = "default_network_access", rename = "networkAccess")]
+    pub(crate) network_access: bool,
+    #[serde(default, rename = "approvalPolicy")]
+    pub(crate) approval_policy: ApprovalPolicy,
+}
+
+fn default_network_access() -> bool {
+    true
+}
+
+/// When the agent asks before running a command.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ApprovalPolicy {
+    Never,
+    #[default]
+    OnRequest,
+    OnFailure,
+    Untrusted,
+}
+
+/// How new worktrees of a workspace are prepared: files copied over from
+/// the workspace, then commands run inside the worktree.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct WorktreeSetupConfig {
+    /// Relative paths or globs, e.g. `.env` or `config/*.local.json`.
+    #[serde(default)]
+    pub(crate) copy: Vec<String>,
+    /// Shell commands run in order, e.g. `npm ci`.
+    #[serde(default)]
+    pub(crate) commands: Vec<String>,
+}
+
+impl WorktreeSetupConfig {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.copy.is_empty() && self.commands.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WorktreeSetupState {
+    Running,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorktreeSetupStatus {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    pub(crate) state: WorktreeSetupState,
+    #[serde(rename = "copiedFiles")]
+    pub(crate) copied_files: usize,
+    /// The command running now, or the one that failed.
+    pub(crate) command: Option<String>,
+    pub(crate) error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -196,8 +797,9 @@ pub(crate) struct AppSettings {
     pub(crate) backend_mode: BackendMode,
     #[serde(default = "default_remote_backend_host", rename = "remoteBackendHost")]
     pub(crate) remote_backend_host: String,
+    /// Token for the remote daemon, held in the secret store.
     #[serde(default, rename = "remoteBackendToken")]
-    pub(crate) remote_backend_token: Option<String>,
+    pub(crate) remote_backend_token: Option<SecretRef>,
     #[serde(default = "default_access_mode", rename = "defaultAccessMode")]
     pub(crate) default_access_mode: String,
     #[serde(
@@ -257,6 +859,39 @@ pub(crate) struct AppSettings {
     pub(crate) dictation_hold_key: String,
     #[serde(default = "default_workspace_groups", rename = "workspaceGroups")]
     pub(crate) workspace_groups: Vec<WorkspaceGroup>,
+    #[serde(default = "default_diff_max_file_bytes", rename = "diffMaxFileBytes")]
+    pub(crate) diff_max_file_bytes: usize,
+    #[serde(default = "default_diff_max_total_bytes", rename = "diffMaxTotalBytes")]
+    pub(crate) diff_max_total_bytes: usize,
+    /// Forge overrides for self-hosted remotes, keyed by host name.
+    #[serde(default, rename = "forgeHosts")]
+    pub(crate) forge_hosts: BTreeMap<String, ForgeKind>,
+    /// Named sandboxes, selectable per message or per workspace.
+    #[serde(default, rename = "sandboxProfiles")]
+    pub(crate) sandbox_profiles: Vec<SandboxProfile>,
+    /// Token for the GitHub API, held in the secret store. When unset,
+    /// `GH_TOKEN`/`GITHUB_TOKEN` or `gh auth token` are used instead.
+    #[serde(default, rename = "githubToken")]
+    pub(crate) github_token: Option<SecretRef>,
+    /// Token for the GitLab API, held in the secret store. When unset,
+    /// `GITLAB_TOKEN` or `glab` are used instead.
+    #[serde(default, rename = "gitlabToken")]
+    pub(crate) gitlab_token: Option<SecretRef>,
+    /// Plain-text tokens from older settings files, waiting to be moved
+    /// into the secret store at startup.
+    #[serde(
+        default,
+        rename = "legacySecrets",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub(crate) legacy_secrets: BTreeMap<SecretKind, String>,
+    /// Directory new worktrees are created in, one subdirectory per
+    /// workspace. Defaults to the app data dir.
+    #[serde(default, rename = "worktreeRoot")]
+    pub(crate) worktree_root: Option<String>,
+    /// Branch name template for new worktrees, e.g. `codex/{date}-{slug}`.
+    #[serde(default, rename = "worktreeBranchTemplate")]
+    pub(crate) worktree_branch_template: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -328,6 +963,25 @@ fn default_workspace_groups() -> Vec<WorkspaceGroup> {
     Vec::new()
 }
 
+fn default_diff_max_file_bytes() -> usize {
+    256 * 1024
+}
+
+fn default_diff_max_total_bytes() -> usize {
+    4 * 1024 * 1024
+}
+
+impl AppSettings {
+    /// Secret references only change through the secret commands, so a
+    /// settings update keeps the ones already saved.
+    pub(crate) fn keep_secrets_from(&mut self, current: &AppSettings) {
+        self.remote_backend_token = current.remote_backend_token.clone();
+        self.github_token = current.github_token.clone();
+        self.gitlab_token = current.gitlab_token.clone();
+        self.legacy_secrets = current.legacy_secrets.clone();
+    }
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
@@ -351,6 +1005,15 @@ impl Default for AppSettings {
             dictation_preferred_language: None,
             dictation_hold_key: default_dictation_hold_key(),
             workspace_groups: default_workspace_groups(),
+            diff_max_file_bytes: default_diff_max_file_bytes(),
+            diff_max_total_bytes: default_diff_max_total_bytes(),
+            forge_hosts: BTreeMap::new(),
+            sandbox_profiles: Vec::new(),
+            github_token: None,
+            gitlab_token: None,
+            legacy_secrets: BTreeMap::new(),
+            worktree_root: None,
+            worktree_branch_template: None,
         }
     }
 }
@@ -386,9 +1049,14 @@ mod tests {
         assert!(!settings.experimental_steer_enabled);
         assert!(!settings.dictation_enabled);
         assert_eq!(settings.dictation_model_id, "base");
+        assert_eq!(settings.diff_max_file_bytes, 256 * 1024);
+        assert_eq!(settings.diff_max_total_bytes, 4 * 1024 * 1024);
         assert!(settings.dictation_preferred_language.is_none());
         assert_eq!(settings.dictation_hold_key, "alt");
         assert!(settings.workspace_groups.is_empty());
+        assert!(settings.forge_hosts.is_empty());
+        assert!(settings.github_token.is_none());
+        assert!(settings.gitlab_token.is_none());
     }
 
     #[test]
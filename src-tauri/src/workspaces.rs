@@ -1,21 +1,37 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use ignore::WalkBuilder;
+use serde_json::json;
 use tauri::{AppHandle, Manager, State};
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 use uuid::Uuid;
 
-use crate::codex::spawn_workspace_session;
+use crate::backend::approvals::validate_approval_rules;
+use crate::backend::worktree_branches::resolve_worktree_branch;
+use crate::codex::{spawn_workspace_session, start_agent_review, start_agent_turn};
+use crate::git::{
+    diff_limits, fetch_github_issue, fetch_github_pull_request, github_issue_prompt,
+    start_git_status_watcher,
+};
+use crate::sandbox::validate_workspace_sandbox_profile;
 use crate::state::AppState;
-use crate::git_utils::resolve_git_root;
+use crate::git_utils::{
+    commits_between, create_or_fast_forward_branch, default_remote_name, list_conflicts,
+    preview_merge, preview_replay, resolve_git_root,
+};
 use crate::storage::write_workspaces;
 use crate::types::{
-    WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
+    GitHubIssueLink, GitHubPullRequestLink, GitLogEntry, WorkspaceEntry, WorkspaceInfo,
+    WorkspaceKind, WorkspaceSettings, WorktreeInfo, WorktreeIntegrationResult,
+    WorktreeIntegrationStrategy, WorktreeSyncAction, WorktreeSyncActionKind, WorktreeSyncItem,
+    WorktreeSyncStatus,
 };
 use crate::utils::normalize_git_path;
+use crate::worktree_paths::{branch_slug, worktree_root_dir};
+use crate::worktree_setup::{ensure_setup_finished, start_worktree_setup};
 
 fn resolve_codex_home(entry: &WorkspaceEntry, parent_path: Option<&str>) -> Option<PathBuf> {
     if entry.kind.is_worktree() {
@@ -57,6 +73,16 @@ fn sanitize_worktree_name(branch: &str) -> String {
     }
 }
 
+/// Builds a branch name like `issue-123-fix-login-crash` from an issue.
+fn issue_branch_name(number: u64, title: &str) -> String {
+    let slug = branch_slug(title);
+    if slug.is_empty() {
+        format!("issue-{number}")
+    } else {
+        format!("issue-{number}-{slug}")
+    }
+}
+
 fn list_workspace_files_inner(root: &PathBuf, max_files: usize) -> Vec<String> {
     let mut results = Vec::new();
     let walker = WalkBuilder::new(root)
@@ -226,6 +252,172 @@ fn unique_worktree_path(base_dir: &PathBuf, name: &str) -> PathBuf {
     candidate
 }
 
+/// One entry of `git worktree list --porcelain`.
+#[derive(Debug, Default, PartialEq)]
+struct GitWorktree {
+    path: String,
+    branch: Option<String>,
+    bare: bool,
+    locked: bool,
+    prunable: bool,
+}
+
+fn parse_worktree_list(output: &str) -> Vec<GitWorktree> {
+    let mut worktrees = Vec::new();
+    let mut current: Option<GitWorktree> = None;
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktrees.extend(current.take());
+            current = Some(GitWorktree {
+                path: path.to_string(),
+                ..GitWorktree::default()
+            });
+            continue;
+        }
+        let Some(worktree) = current.as_mut() else {
+            continue;
+        };
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "branch" => {
+                worktree.branch = Some(value.trim_start_matches("refs/heads/").to_string());
+            }
+            "bare" => worktree.bare = true,
+            "locked" => worktree.locked = true,
+            "prunable" => worktree.prunable = true,
+            _ => {}
+        }
+    }
+    worktrees.extend(current);
+    worktrees
+}
+
+/// Resolves symlinks (e.g. `/tmp` on macOS) so paths from git and from the
+/// store compare equal; paths that no longer exist are compared as stored.
+fn canonical_path(path: &str) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// Matches the linked worktrees git knows about (everything after the main
+/// worktree) against the worktree workspaces stored for one parent.
+fn reconcile_worktrees(
+    listed: &[GitWorktree],
+    children: &[WorkspaceEntry],
+) -> Vec<WorktreeSyncItem> {
+    let linked = listed
+        .iter()
+        .skip(1)
+        .filter(|worktree| !worktree.bare)
+        .map(|worktree| (canonical_path(&worktree.path), worktree))
+        .collect::<Vec<_>>();
+    let mut matched = vec![false; linked.len()];
+    let mut items = Vec::new();
+    for child in children {
+        let child_path = canonical_path(&child.path);
+        let found = linked.iter().position(|(path, _)| *path == child_path);
+        if let Some(index) = found {
+            matched[index] = true;
+        }
+        let listed = found.map(|index| linked[index].1);
+        let stored_branch = child.worktree.as_ref().map(|info| info.branch.clone());
+        let status = match listed {
+            _ if !Path::new(&child.path).is_dir() => WorktreeSyncStatus::Missing,
+            None => WorktreeSyncStatus::Stale,
+            Some(worktree) if worktree.prunable || worktree.branch != stored_branch => {
+                WorktreeSyncStatus::Stale
+            }
+            Some(_) => WorktreeSyncStatus::Ok,
+        };
+        items.push(WorktreeSyncItem {
+            path: child.path.clone(),
+            status,
+            workspace_id: Some(child.id.clone()),
+            branch: listed.and_then(|worktree| worktree.branch.clone()),
+            stored_branch,
+            locked: listed.is_some_and(|worktree| worktree.locked),
+            disk_usage_bytes: None,
+        });
+    }
+    for ((_, worktree), matched) in linked.iter().zip(matched) {
+        // Worktrees whose directory is gone are git's to prune, not ours.
+        if matched || !Path::new(&worktree.path).is_dir() {
+            continue;
+        }
+        items.push(WorktreeSyncItem {
+            path: worktree.path.clone(),
+            status: WorktreeSyncStatus::Untracked,
+            workspace_id: None,
+            branch: worktree.branch.clone(),
+            stored_branch: None,
+            locked: worktree.locked,
+            disk_usage_bytes: None,
+        });
+    }
+    items
+}
+
+/// Total size of the files under `root`, without following symlinks.
+fn directory_size(root: &Path) -> u64 {
+    WalkBuilder::new(root)
+        .standard_filters(false)
+        .follow_links(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+async fn worktree_parent_and_children(
+    parent_id: &str,
+    state: &AppState,
+) -> Result<(WorkspaceEntry, Vec<WorkspaceEntry>), String> {
+    let workspaces = state.workspaces.lock().await;
+    let parent = workspaces
+        .get(parent_id)
+        .cloned()
+        .ok_or("parent workspace not found")?;
+    if parent.kind.is_worktree() {
+        return Err("Worktrees belong to a main workspace.".to_string());
+    }
+    let children = workspaces
+        .values()
+        .filter(|entry| entry.kind.is_worktree() && entry.parent_id.as_deref() == Some(parent_id))
+        .cloned()
+        .collect();
+    Ok((parent, children))
+}
+
+async fn list_git_worktrees(parent: &WorkspaceEntry) -> Result<Vec<GitWorktree>, String> {
+    let output = run_git_command(
+        &PathBuf::from(&parent.path),
+        &["worktree", "list", "--porcelain"],
+    )
+    .await?;
+    Ok(parse_worktree_list(&output))
+}
+
+async fn worktree_sync_report(
+    parent_id: &str,
+    state: &AppState,
+) -> Result<Vec<WorktreeSyncItem>, String> {
+    let (parent, children) = worktree_parent_and_children(parent_id, state).await?;
+    let listed = list_git_worktrees(&parent).await?;
+    let mut items = reconcile_worktrees(&listed, &children);
+    tokio::task::spawn_blocking(move || {
+        for item in &mut items {
+            let path = Path::new(&item.path);
+            if path.is_dir() {
+                item.disk_usage_bytes = Some(directory_size(path));
+            }
+        }
+        items
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub(crate) async fn list_workspaces(
     state: State<'_, AppState>,
@@ -256,6 +448,18 @@ pub(crate) async fn add_workspace(
     codex_bin: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    register_workspace(path, codex_bin, WorkspaceSettings::default(), &state, app).await
+}
+
+/// Registers the directory at `path` as a main workspace, connects a
+/// session to it and persists the workspace list.
+pub(crate) async fn register_workspace(
+    path: String,
+    codex_bin: Option<String>,
+    settings: WorkspaceSettings,
+    state: &AppState,
+    app: AppHandle,
 ) -> Result<WorkspaceInfo, String> {
     let name = PathBuf::from(&path)
         .file_name()
@@ -270,9 +474,8 @@ pub(crate) async fn add_workspace(
         kind: WorkspaceKind::Main,
         parent_id: None,
         worktree: None,
-        settings: WorkspaceSettings::default(),
+        settings,
     };
-
     let default_bin = {
         let settings = state.app_settings.lock().await;
         settings.codex_bin.clone()
@@ -283,7 +486,7 @@ pub(crate) async fn add_workspace(
         let mut workspaces = state.workspaces.lock().await;
         workspaces.insert(entry.id.clone(), entry.clone());
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)?;
+        write_workspaces(&state.workspaces_file, &list)?;
     }
     state
         .sessions
@@ -304,36 +507,42 @@ pub(crate) async fn add_workspace(
     })
 }
 
-#[tauri::command]
-pub(crate) async fn add_worktree(
-    parent_id: String,
-    branch: String,
-    state: State<'_, AppState>,
-    app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
-    let branch = branch.trim();
-    if branch.is_empty() {
-        return Err("Branch name is required.".to_string());
+pub(crate) fn workspace_info(entry: WorkspaceEntry, connected: bool) -> WorkspaceInfo {
+    WorkspaceInfo {
+        id: entry.id,
+        name: entry.name,
+        path: entry.path,
+        codex_bin: entry.codex_bin,
+        connected,
+        kind: entry.kind,
+        parent_id: entry.parent_id,
+        worktree: entry.worktree,
+        settings: entry.settings,
     }
+}
 
-    let parent_entry = {
-        let workspaces = state.workspaces.lock().await;
-        workspaces
-            .get(&parent_id)
-            .cloned()
-            .ok_or("parent workspace not found")?
-    };
-
+/// Adds a git worktree for `branch` under the configured worktree root
+/// (creating the branch from `base_ref`, or HEAD, when it does not exist
+/// yet), registers it as a workspace, connects a session to it and starts
+/// the parent's worktree setup.
+async fn create_worktree_workspace(
+    parent_entry: &WorkspaceEntry,
+    branch: &str,
+    base_ref: Option<&str>,
+    worktree: WorktreeInfo,
+    state: &AppState,
+    app: AppHandle,
+) -> Result<WorkspaceEntry, String> {
     if parent_entry.kind.is_worktree() {
         return Err("Cannot create a worktree from another worktree.".to_string());
     }
 
-    let worktree_root = app
+    let data_dir = app
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?
-        .join("worktrees")
-        .join(&parent_entry.id);
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))?;
+    let global_root = state.app_settings.lock().await.worktree_root.clone();
+    let worktree_root = worktree_root_dir(parent_entry, global_root.as_deref(), &data_dir);
     std::fs::create_dir_all(&worktree_root)
         .map_err(|e| format!("Failed to create worktree directory: {e}"))?;
 
@@ -343,17 +552,18 @@ pub(crate) async fn add_worktree(
 
     let branch_exists = git_branch_exists(&PathBuf::from(&parent_entry.path), branch).await?;
     if branch_exists {
+        if base_ref.is_some() {
+            return Err(format!("Branch {branch} already exists."));
+        }
         run_git_command(
             &PathBuf::from(&parent_entry.path),
             &["worktree", "add", &worktree_path_string, branch],
         )
         .await?;
     } else {
-        run_git_command(
-            &PathBuf::from(&parent_entry.path),
-            &["worktree", "add", "-b", branch, &worktree_path_string],
-        )
-        .await?;
+        let mut args = vec!["worktree", "add", "-b", branch, &worktree_path_string];
+        args.extend(base_ref);
+        run_git_command(&PathBuf::from(&parent_entry.path), &args).await?;
     }
 
     let entry = WorkspaceEntry {
@@ -363,9 +573,7 @@ pub(crate) async fn add_worktree(
         codex_bin: parent_entry.codex_bin.clone(),
         kind: WorkspaceKind::Worktree,
         parent_id: Some(parent_entry.id.clone()),
-        worktree: Some(WorktreeInfo {
-            branch: branch.to_string(),
-        }),
+        worktree: Some(worktree),
         settings: WorkspaceSettings::default(),
     };
 
@@ -374,30 +582,245 @@ pub(crate) async fn add_worktree(
         settings.codex_bin.clone()
     };
     let codex_home = resolve_codex_home(&entry, Some(&parent_entry.path));
-    let session = spawn_workspace_session(entry.clone(), default_bin, app, codex_home).await?;
+    let session =
+        spawn_workspace_session(entry.clone(), default_bin, app.clone(), codex_home).await?;
     {
         let mut workspaces = state.workspaces.lock().await;
         workspaces.insert(entry.id.clone(), entry.clone());
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)?;
+        write_workspaces(&state.workspaces_file, &list)?;
     }
     state
         .sessions
         .lock()
         .await
         .insert(entry.id.clone(), session);
+    start_worktree_setup(state, parent_entry, &entry, app).await;
 
-    Ok(WorkspaceInfo {
-        id: entry.id,
-        name: entry.name,
-        path: entry.path,
-        codex_bin: entry.codex_bin,
-        connected: true,
-        kind: entry.kind,
-        parent_id: entry.parent_id,
-        worktree: entry.worktree,
-        settings: entry.settings,
-    })
+    Ok(entry)
+}
+
+#[tauri::command]
+pub(crate) async fn add_worktree(
+    parent_id: String,
+    branch: String,
+    base_ref: Option<String>,
+    existing_branch: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    let branch = branch.trim();
+    if branch.is_empty() {
+        return Err("Branch name is required.".to_string());
+    }
+    let base_ref = base_ref
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+
+    let parent_entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&parent_id)
+            .cloned()
+            .ok_or("parent workspace not found")?
+    };
+    let global_template = state
+        .app_settings
+        .lock()
+        .await
+        .worktree_branch_template
+        .clone();
+    let (branch, base_ref) = resolve_worktree_branch(
+        &parent_entry,
+        global_template.as_deref(),
+        branch,
+        base_ref,
+        existing_branch.unwrap_or(false),
+    )?;
+
+    let worktree = WorktreeInfo {
+        branch: branch.clone(),
+        issue: None,
+        pull_request: None,
+    };
+    let entry = create_worktree_workspace(
+        &parent_entry,
+        &branch,
+        base_ref.as_deref(),
+        worktree,
+        &state,
+        app,
+    )
+    .await?;
+    Ok(workspace_info(entry, true))
+}
+
+#[tauri::command]
+pub(crate) async fn start_github_issue_worktree(
+    workspace_id: String,
+    issue_number: u64,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let parent_entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    let repo_root = resolve_git_root(&parent_entry)?;
+    let issue = fetch_github_issue(&state, &repo_root, issue_number).await?;
+
+    let branch = issue_branch_name(issue.number, &issue.title);
+    let worktree = WorktreeInfo {
+        branch: branch.clone(),
+        issue: Some(GitHubIssueLink {
+            number: issue.number,
+            title: issue.title.clone(),
+            url: issue.url.clone(),
+            thread_id: None,
+        }),
+        pull_request: None,
+    };
+    let mut entry =
+        create_worktree_workspace(&parent_entry, &branch, None, worktree, &state, app).await?;
+    let started = async {
+        // Let copied env files and installed dependencies land before the agent starts.
+        ensure_setup_finished(&state, &entry.id).await?;
+        let session = state
+            .sessions
+            .lock()
+            .await
+            .get(&entry.id)
+            .cloned()
+            .ok_or("workspace not connected")?;
+        start_agent_turn(&state, &session, None, github_issue_prompt(&issue)).await
+    }
+    .await;
+    let started = match started {
+        Ok(started) => started,
+        Err(err) => {
+            // Don't leave a worktree behind that nothing is working in; the
+            // branch stays, so a retry picks it up again.
+            let _ = remove_worktree_entry(&state, &entry, &parent_entry).await;
+            return Err(err);
+        }
+    };
+    let thread_id = started
+        .get("threadId")
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+
+    if let Some(link) = entry
+        .worktree
+        .as_mut()
+        .and_then(|worktree| worktree.issue.as_mut())
+    {
+        link.thread_id = thread_id.clone();
+    }
+    {
+        let mut workspaces = state.workspaces.lock().await;
+        workspaces.insert(entry.id.clone(), entry.clone());
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        write_workspaces(&state.workspaces_file, &list)?;
+    }
+
+    Ok(json!({
+        "workspace": workspace_info(entry, true),
+        "threadId": thread_id,
+        "turn": started.get("turn").cloned().unwrap_or(serde_json::Value::Null),
+    }))
+}
+
+#[tauri::command]
+pub(crate) async fn checkout_pull_request_worktree(
+    workspace_id: String,
+    pr_number: u64,
+    start_review: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let parent_entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    let repo_root = resolve_git_root(&parent_entry)?;
+    let pull_request = fetch_github_pull_request(&state, &repo_root, pr_number).await?;
+    let remote = {
+        let repo = git2::Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        default_remote_name(&repo).ok_or("No git remote configured.")?
+    };
+
+    // `refs/pull/N/head` also works for pull requests opened from forks. It is
+    // fetched next to the remote's branches so the local branch only moves
+    // forward.
+    let branch = format!("pr-{pr_number}");
+    let remote_ref = format!("refs/remotes/{remote}/pr/{pr_number}");
+    run_git_command(
+        &repo_root,
+        &[
+            "fetch",
+            &remote,
+            &format!("+pull/{pr_number}/head:{remote_ref}"),
+        ],
+    )
+    .await?;
+    run_git_command(&repo_root, &["fetch", &remote, &pull_request.base_ref_name]).await?;
+    {
+        let repo = git2::Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let head = repo.refname_to_id(&remote_ref).map_err(|e| e.to_string())?;
+        create_or_fast_forward_branch(&repo, &branch, head)?;
+    }
+
+    let worktree = WorktreeInfo {
+        branch: branch.clone(),
+        issue: None,
+        pull_request: Some(GitHubPullRequestLink {
+            number: pull_request.number,
+            title: pull_request.title.clone(),
+            url: pull_request.url.clone(),
+            head_ref_name: pull_request.head_ref_name.clone(),
+            base_ref_name: pull_request.base_ref_name.clone(),
+        }),
+    };
+    let mut entry =
+        create_worktree_workspace(&parent_entry, &branch, None, worktree, &state, app).await?;
+    entry.name = format!("PR #{pr_number}: {}", pull_request.title);
+    {
+        let mut workspaces = state.workspaces.lock().await;
+        workspaces.insert(entry.id.clone(), entry.clone());
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        write_workspaces(&state.workspaces_file, &list)?;
+    }
+
+    let review = if start_review.unwrap_or(false) {
+        ensure_setup_finished(&state, &entry.id).await?;
+        let session = state
+            .sessions
+            .lock()
+            .await
+            .get(&entry.id)
+            .cloned()
+            .ok_or("workspace not connected")?;
+        let target = json!({
+            "type": "baseBranch",
+            "branch": format!("{remote}/{}", pull_request.base_ref_name),
+        });
+        Some(start_agent_review(&session, None, target).await?)
+    } else {
+        None
+    };
+
+    Ok(json!({
+        "workspace": workspace_info(entry, true),
+        "pullRequest": pull_request,
+        "review": review,
+    }))
 }
 
 #[tauri::command]
@@ -428,6 +851,8 @@ pub(crate) async fn remove_workspace(
             let mut child_process = session.child.lock().await;
             let _ = child_process.kill().await;
         }
+        state.git_status_watchers.lock().await.remove(&child.id);
+        state.worktree_setups.lock().await.remove(&child.id);
         let child_path = PathBuf::from(&child.path);
         if child_path.exists() {
             run_git_command(
@@ -443,6 +868,7 @@ pub(crate) async fn remove_workspace(
         let mut child = session.child.lock().await;
         let _ = child.kill().await;
     }
+    state.git_status_watchers.lock().await.remove(&id);
 
     {
         let mut workspaces = state.workspaces.lock().await;
@@ -451,41 +877,47 @@ pub(crate) async fn remove_workspace(
             workspaces.remove(&child.id);
         }
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)?;
+        write_workspaces(&state.workspaces_file, &list)?;
     }
 
     Ok(())
 }
 
-#[tauri::command]
-pub(crate) async fn remove_worktree(
-    id: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let (entry, parent) = {
-        let workspaces = state.workspaces.lock().await;
-        let entry = workspaces
-            .get(&id)
-            .cloned()
-            .ok_or("workspace not found")?;
-        if !entry.kind.is_worktree() {
-            return Err("Not a worktree workspace.".to_string());
-        }
-        let parent_id = entry
-            .parent_id
-            .clone()
-            .ok_or("worktree parent not found")?;
-        let parent = workspaces
-            .get(&parent_id)
-            .cloned()
-            .ok_or("worktree parent not found")?;
-        (entry, parent)
-    };
+async fn worktree_with_parent(
+    state: &AppState,
+    id: &str,
+) -> Result<(WorkspaceEntry, WorkspaceEntry), String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(id)
+        .cloned()
+        .ok_or("workspace not found")?;
+    if !entry.kind.is_worktree() {
+        return Err("Not a worktree workspace.".to_string());
+    }
+    let parent_id = entry
+        .parent_id
+        .clone()
+        .ok_or("worktree parent not found")?;
+    let parent = workspaces
+        .get(&parent_id)
+        .cloned()
+        .ok_or("worktree parent not found")?;
+    Ok((entry, parent))
+}
 
+/// Stops the worktree's session, removes its checkout and forgets it.
+async fn remove_worktree_entry(
+    state: &AppState,
+    entry: &WorkspaceEntry,
+    parent: &WorkspaceEntry,
+) -> Result<(), String> {
     if let Some(session) = state.sessions.lock().await.remove(&entry.id) {
         let mut child = session.child.lock().await;
         let _ = child.kill().await;
     }
+    state.git_status_watchers.lock().await.remove(&entry.id);
+    state.worktree_setups.lock().await.remove(&entry.id);
 
     let parent_path = PathBuf::from(&parent.path);
     let entry_path = PathBuf::from(&entry.path);
@@ -502,12 +934,143 @@ pub(crate) async fn remove_worktree(
         let mut workspaces = state.workspaces.lock().await;
         workspaces.remove(&entry.id);
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)?;
+        write_workspaces(&state.workspaces_file, &list)?;
     }
 
     Ok(())
 }
 
+#[tauri::command]
+pub(crate) async fn remove_worktree(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let (entry, parent) = worktree_with_parent(&state, &id).await?;
+    remove_worktree_entry(&state, &entry, &parent).await
+}
+
+/// Compares the parent's `git worktree list` with the stored worktree
+/// workspaces and reports each worktree's status and disk usage.
+#[tauri::command]
+pub(crate) async fn sync_worktrees(
+    parent_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<WorktreeSyncItem>, String> {
+    worktree_sync_report(&parent_id, &state).await
+}
+
+/// Imports untracked worktrees, prunes missing ones and repairs stale ones,
+/// then returns the refreshed report. Imported worktrees are added
+/// disconnected; they connect when opened.
+#[tauri::command]
+pub(crate) async fn apply_worktree_sync(
+    parent_id: String,
+    actions: Vec<WorktreeSyncAction>,
+    state: State<'_, AppState>,
+) -> Result<Vec<WorktreeSyncItem>, String> {
+    let (parent, children) = worktree_parent_and_children(&parent_id, &state).await?;
+    let parent_path = PathBuf::from(&parent.path);
+    let items = reconcile_worktrees(&list_git_worktrees(&parent).await?, &children);
+
+    let mut imports = Vec::new();
+    let mut prunes = Vec::new();
+    let mut repairs = Vec::new();
+    for action in &actions {
+        let action_path = canonical_path(&action.path);
+        let item = items
+            .iter()
+            .find(|item| canonical_path(&item.path) == action_path)
+            .ok_or_else(|| format!("{} is not a worktree of this workspace.", action.path))?;
+        let (expected, verb) = match action.action {
+            WorktreeSyncActionKind::Import => (WorktreeSyncStatus::Untracked, "imported"),
+            WorktreeSyncActionKind::Prune => (WorktreeSyncStatus::Missing, "pruned"),
+            WorktreeSyncActionKind::Repair => (WorktreeSyncStatus::Stale, "repaired"),
+        };
+        if item.status != expected {
+            return Err(format!(
+                "{} cannot be {verb}; sync again to refresh its status.",
+                item.path
+            ));
+        }
+        match action.action {
+            WorktreeSyncActionKind::Import => {
+                let branch = item.branch.clone().ok_or_else(|| {
+                    format!("Check out a branch in {} before importing it.", item.path)
+                })?;
+                imports.push(WorkspaceEntry {
+                    id: Uuid::new_v4().to_string(),
+                    name: branch.clone(),
+                    path: item.path.clone(),
+                    codex_bin: parent.codex_bin.clone(),
+                    kind: WorkspaceKind::Worktree,
+                    parent_id: Some(parent.id.clone()),
+                    worktree: Some(WorktreeInfo {
+                        branch,
+                        issue: None,
+                        pull_request: None,
+                    }),
+                    settings: WorkspaceSettings::default(),
+                });
+            }
+            WorktreeSyncActionKind::Prune => prunes.extend(item.workspace_id.clone()),
+            WorktreeSyncActionKind::Repair => {
+                if item.branch.is_none() {
+                    // Git lost the directory (e.g. it was moved); relink it.
+                    run_git_command(&parent_path, &["worktree", "repair", &item.path]).await?;
+                }
+                repairs.extend(item.workspace_id.clone());
+            }
+        }
+    }
+
+    for id in &prunes {
+        if let Some(session) = state.sessions.lock().await.remove(id) {
+            let mut child = session.child.lock().await;
+            let _ = child.kill().await;
+        }
+        state.git_status_watchers.lock().await.remove(id);
+        state.worktree_setups.lock().await.remove(id);
+    }
+    if !prunes.is_empty() {
+        let _ = run_git_command(&parent_path, &["worktree", "prune", "--expire", "now"]).await;
+    }
+    let listed = if repairs.is_empty() {
+        Vec::new()
+    } else {
+        list_git_worktrees(&parent).await?
+    };
+
+    {
+        let mut workspaces = state.workspaces.lock().await;
+        for id in &prunes {
+            workspaces.remove(id);
+        }
+        for id in &repairs {
+            let Some(entry) = workspaces.get_mut(id) else {
+                continue;
+            };
+            let entry_path = canonical_path(&entry.path);
+            let branch = listed
+                .iter()
+                .find(|worktree| canonical_path(&worktree.path) == entry_path)
+                .and_then(|worktree| worktree.branch.clone());
+            if let (Some(branch), Some(info)) = (branch, entry.worktree.as_mut()) {
+                if entry.name == info.branch {
+                    entry.name = branch.clone();
+                }
+                info.branch = branch;
+            }
+        }
+        for entry in imports {
+            workspaces.insert(entry.id.clone(), entry);
+        }
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        write_workspaces(&state.workspaces_file, &list)?;
+    }
+
+    worktree_sync_report(&parent_id, &state).await
+}
+
 #[tauri::command]
 pub(crate) async fn apply_worktree_changes(
     workspace_id: String,
@@ -635,21 +1198,201 @@ pub(crate) async fn apply_worktree_changes(
     Err(detail.to_string())
 }
 
+fn squash_message(branch: &str, commits: &[GitLogEntry]) -> String {
+    let mut message = format!("Squash merge branch '{branch}'\n");
+    if !commits.is_empty() {
+        message.push('\n');
+    }
+    for commit in commits.iter().rev() {
+        message.push_str(&format!("* {}\n", commit.summary));
+    }
+    message
+}
+
+async fn has_uncommitted_changes(repo_root: &PathBuf) -> Result<bool, String> {
+    let status = run_git_command(repo_root, &["status", "--porcelain"]).await?;
+    Ok(!status.is_empty())
+}
+
+/// Brings the worktree branch's commits into the parent's current branch by
+/// merge, squash, rebase and fast-forward, or cherry-pick. A dry run only
+/// previews the resulting changes or conflicts. When the real run hits
+/// conflicts they are reported and left in progress to be resolved.
+#[tauri::command]
+pub(crate) async fn integrate_worktree(
+    workspace_id: String,
+    strategy: WorktreeIntegrationStrategy,
+    dry_run: Option<bool>,
+    message: Option<String>,
+    cleanup: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<WorktreeIntegrationResult, String> {
+    let (entry, parent) = worktree_with_parent(&state, &workspace_id).await?;
+    let branch = entry
+        .worktree
+        .as_ref()
+        .map(|worktree| worktree.branch.clone())
+        .ok_or("Not a worktree workspace.")?;
+    let worktree_root = resolve_git_root(&entry)?;
+    let parent_root = resolve_git_root(&parent)?;
+    let dry_run = dry_run.unwrap_or(false);
+    let limits = diff_limits(&state).await;
+
+    let (target_branch, target_sha, mut result) = {
+        let repo = git2::Repository::open(&parent_root).map_err(|e| e.to_string())?;
+        let head = repo.head().map_err(|e| e.to_string())?;
+        if !head.is_branch() {
+            return Err("Check out a branch in the parent workspace first.".to_string());
+        }
+        let target_branch = head.shorthand().unwrap_or("").to_string();
+        if target_branch == branch {
+            return Err(format!("The parent already has {branch} checked out."));
+        }
+        let target = head.peel_to_commit().map_err(|e| e.to_string())?.id();
+        let source = repo
+            .refname_to_id(&format!("refs/heads/{branch}"))
+            .map_err(|e| e.to_string())?;
+        let commits = commits_between(&repo, target, source).map_err(|e| e.to_string())?;
+        if commits.is_empty() {
+            return Err(format!(
+                "{target_branch} already contains every commit on {branch}."
+            ));
+        }
+        let (diff, conflicts) = if !dry_run {
+            (Vec::new(), Vec::new())
+        } else if matches!(
+            strategy,
+            WorktreeIntegrationStrategy::Rebase | WorktreeIntegrationStrategy::CherryPick
+        ) {
+            preview_replay(&repo, target, source, &limits).map_err(|e| e.to_string())?
+        } else {
+            preview_merge(&repo, target, source, &limits).map_err(|e| e.to_string())?
+        };
+        let result = WorktreeIntegrationResult {
+            strategy,
+            dry_run,
+            branch: branch.clone(),
+            target_branch: target_branch.clone(),
+            commits,
+            diff,
+            conflicts,
+            conflict_workspace_id: None,
+            head_sha: None,
+            removed_worktree: false,
+        };
+        (target_branch, target.to_string(), result)
+    };
+    if dry_run {
+        return Ok(result);
+    }
+
+    if has_uncommitted_changes(&parent_root).await? {
+        return Err(
+            "Your current branch has uncommitted changes. Please commit, stash, or discard them before integrating the worktree."
+                .to_string(),
+        );
+    }
+    if has_uncommitted_changes(&worktree_root).await? {
+        return Err(
+            "The worktree has uncommitted changes. Commit them first, or use Apply to copy them over."
+                .to_string(),
+        );
+    }
+
+    let message = message
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty());
+    let (outcome, conflict_root, conflict_workspace) = match strategy {
+        WorktreeIntegrationStrategy::Merge => {
+            let mut args = vec!["merge", "--no-ff", "--no-edit", branch.as_str()];
+            if let Some(message) = &message {
+                args.extend(["-m", message.as_str()]);
+            }
+            (
+                run_git_command(&parent_root, &args).await,
+                &parent_root,
+                &parent,
+            )
+        }
+        WorktreeIntegrationStrategy::Squash => {
+            let squashed = run_git_command(&parent_root, &["merge", "--squash", &branch]).await;
+            let outcome = match squashed {
+                Ok(_) => {
+                    let message = message
+                        .clone()
+                        .unwrap_or_else(|| squash_message(&branch, &result.commits));
+                    run_git_command(&parent_root, &["commit", "-m", &message]).await
+                }
+                Err(error) => Err(error),
+            };
+            (outcome, &parent_root, &parent)
+        }
+        WorktreeIntegrationStrategy::Rebase => {
+            match run_git_command(&worktree_root, &["rebase", &target_branch]).await {
+                Ok(_) => (
+                    run_git_command(&parent_root, &["merge", "--ff-only", &branch]).await,
+                    &parent_root,
+                    &parent,
+                ),
+                Err(error) => (Err(error), &worktree_root, &entry),
+            }
+        }
+        WorktreeIntegrationStrategy::CherryPick => {
+            let range = format!("{target_sha}..{branch}");
+            (
+                // Merge commits need a mainline to be picked; their changes
+                // arrive through the commits they merged.
+                run_git_command(&parent_root, &["cherry-pick", "--no-merges", &range]).await,
+                &parent_root,
+                &parent,
+            )
+        }
+    };
+
+    if let Err(error) = outcome {
+        let conflicts = git2::Repository::open(conflict_root)
+            .and_then(|repo| list_conflicts(&repo))
+            .unwrap_or_default();
+        if conflicts.is_empty() {
+            return Err(error);
+        }
+        result.conflicts = conflicts;
+        result.conflict_workspace_id = Some(conflict_workspace.id.clone());
+        return Ok(result);
+    }
+
+    result.head_sha = Some(run_git_command(&parent_root, &["rev-parse", "HEAD"]).await?);
+    if cleanup.unwrap_or(false) {
+        remove_worktree_entry(&state, &entry, &parent).await?;
+        result.removed_worktree = true;
+    }
+    Ok(result)
+}
+
 #[tauri::command]
 pub(crate) async fn update_workspace_settings(
     id: String,
     settings: WorkspaceSettings,
     state: State<'_, AppState>,
 ) -> Result<WorkspaceInfo, String> {
+    validate_approval_rules(&settings.approval_rules)?;
+    {
+        let app_settings = state.app_settings.lock().await;
+        validate_workspace_sandbox_profile(&settings, &app_settings.sandbox_profiles)?;
+    }
     let (entry_snapshot, list) = {
         let mut workspaces = state.workspaces.lock().await;
         let entry_snapshot = apply_workspace_settings_update(&mut workspaces, &id, settings)?;
         let list: Vec<_> = workspaces.values().cloned().collect();
         (entry_snapshot, list)
     };
-    write_workspaces(&state.storage_path, &list)?;
+    write_workspaces(&state.workspaces_file, &list)?;
 
-    let connected = state.sessions.lock().await.contains_key(&id);
+    let session = state.sessions.lock().await.get(&id).cloned();
+    if let Some(session) = &session {
+        *session.approval_rules.lock().await = entry_snapshot.settings.approval_rules.clone();
+    }
+    let connected = session.is_some();
     Ok(WorkspaceInfo {
         id: entry_snapshot.id,
         name: entry_snapshot.name,
@@ -681,7 +1424,7 @@ pub(crate) async fn update_workspace_codex_bin(
         let list: Vec<_> = workspaces.values().cloned().collect();
         (entry_snapshot, list)
     };
-    write_workspaces(&state.storage_path, &list)?;
+    write_workspaces(&state.workspaces_file, &list)?;
 
     let connected = state.sessions.lock().await.contains_key(&id);
     Ok(WorkspaceInfo {
@@ -724,6 +1467,8 @@ pub(crate) async fn connect_workspace(
         settings.codex_bin.clone()
     };
     let codex_home = resolve_codex_home(&entry, parent_path.as_deref());
+    // Workspaces that aren't repositories simply get no status events.
+    let _ = start_git_status_watcher(&state, &entry, app.clone()).await;
     let session = spawn_workspace_session(entry.clone(), default_bin, app, codex_home).await?;
     state.sessions.lock().await.insert(entry.id, session);
     Ok(())
@@ -763,11 +1508,16 @@ pub(crate) async fn open_workspace_in(
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
-    use std::path::PathBuf;
 
-    use super::{apply_workspace_settings_update, sanitize_worktree_name, sort_workspaces};
-    use crate::storage::{read_workspaces, write_workspaces};
-    use crate::types::{WorktreeInfo, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings};
+    use super::{
+        apply_workspace_settings_update, issue_branch_name, parse_worktree_list,
+        reconcile_worktrees, sanitize_worktree_name, sort_workspaces, GitWorktree,
+    };
+    use crate::storage::{read_workspaces, write_workspaces, DataFile};
+    use crate::types::{
+        WorktreeInfo, WorktreeSyncStatus, WorkspaceEntry, WorkspaceInfo, WorkspaceKind,
+        WorkspaceSettings,
+    };
     use uuid::Uuid;
 
     fn workspace(name: &str, sort_order: Option<u32>) -> WorkspaceInfo {
@@ -785,6 +1535,8 @@ mod tests {
                 Some("parent".to_string()),
                 Some(WorktreeInfo {
                     branch: name.to_string(),
+                    issue: None,
+                    pull_request: None,
                 }),
             )
         } else {
@@ -804,6 +1556,10 @@ mod tests {
                 sort_order,
                 group_id: None,
                 git_root: None,
+                worktree_setup: Default::default(),
+                worktree_root: None,
+                worktree_branch_template: None,
+                ..Default::default()
             },
         }
     }
@@ -821,6 +1577,18 @@ mod tests {
         assert_eq!(sanitize_worktree_name("feature--x"), "feature--x");
     }
 
+    #[test]
+    fn issue_branch_name_slugs_the_title() {
+        assert_eq!(
+            issue_branch_name(123, "Fix: login crash (macOS)"),
+            "issue-123-fix-login-crash-macos"
+        );
+        assert_eq!(issue_branch_name(7, "???"), "issue-7");
+        let long = issue_branch_name(9, &"word ".repeat(30));
+        assert!(long.len() <= "issue-9-".len() + 41);
+        assert!(!long.ends_with('-'));
+    }
+
     #[test]
     fn sort_workspaces_orders_by_sort_then_name() {
         let mut items = vec![
@@ -927,15 +1695,91 @@ mod tests {
         let temp_dir = std::env::temp_dir()
             .join(format!("codex-monitor-test-{}", Uuid::new_v4()));
         std::fs::create_dir_all(&temp_dir).expect("create temp dir");
-        let path = PathBuf::from(temp_dir.join("workspaces.json"));
+        let file = DataFile::new(temp_dir.join("workspaces.json"));
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&path, &list).expect("write workspaces");
+        write_workspaces(&file, &list).expect("write workspaces");
 
-        let read = read_workspaces(&path).expect("read workspaces");
+        let read = read_workspaces(&file).expect("read workspaces");
         let stored = read.get(&id).expect("stored workspace");
         assert_eq!(stored.settings.sort_order, Some(3));
         assert_eq!(stored.settings.group_id.as_deref(), Some("group-1"));
         assert!(stored.settings.sidebar_collapsed);
         assert_eq!(stored.settings.git_root.as_deref(), Some("/tmp"));
     }
+
+    #[test]
+    fn parse_worktree_list_reads_porcelain_blocks() {
+        let output = "worktree /repo\nHEAD 1111\nbranch refs/heads/main\n\nworktree /wt/feature\nHEAD 2222\nbranch refs/heads/feature/x\nlocked\n\nworktree /wt/gone\nHEAD 3333\ndetached\nprunable gitdir file points to non-existent location\n";
+        let worktrees = parse_worktree_list(output);
+        assert_eq!(worktrees.len(), 3);
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert_eq!(
+            worktrees[1],
+            GitWorktree {
+                path: "/wt/feature".to_string(),
+                branch: Some("feature/x".to_string()),
+                bare: false,
+                locked: true,
+                prunable: false,
+            }
+        );
+        assert!(worktrees[2].branch.is_none());
+        assert!(worktrees[2].prunable);
+    }
+
+    #[test]
+    fn reconcile_worktrees_classifies_entries() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        for name in ["ok", "moved", "drifted", "terminal"] {
+            std::fs::create_dir_all(root.join(name)).expect("create worktree dir");
+        }
+        let path = |name: &str| root.join(name).to_string_lossy().to_string();
+        let listed = |name: &str, branch: &str| GitWorktree {
+            path: path(name),
+            branch: Some(branch.to_string()),
+            ..GitWorktree::default()
+        };
+        let stored = |name: &str, branch: &str| WorkspaceEntry {
+            id: name.to_string(),
+            name: branch.to_string(),
+            path: path(name),
+            codex_bin: None,
+            kind: WorkspaceKind::Worktree,
+            parent_id: Some("parent".to_string()),
+            worktree: Some(WorktreeInfo {
+                branch: branch.to_string(),
+                issue: None,
+                pull_request: None,
+            }),
+            settings: WorkspaceSettings::default(),
+        };
+        let git = vec![
+            listed("main", "main"),
+            listed("ok", "ok"),
+            listed("drifted", "other"),
+            listed("terminal", "terminal"),
+        ];
+        let children = vec![
+            stored("ok", "ok"),
+            stored("missing", "missing"),
+            stored("moved", "moved"),
+            stored("drifted", "drifted"),
+        ];
+
+        let items = reconcile_worktrees(&git, &children);
+        let status = |name: &str| {
+            items
+                .iter()
+                .find(|item| item.path == path(name))
+                .map(|item| item.status)
+        };
+        assert_eq!(status("ok"), Some(WorktreeSyncStatus::Ok));
+        assert_eq!(status("missing"), Some(WorktreeSyncStatus::Missing));
+        assert_eq!(status("moved"), Some(WorktreeSyncStatus::Stale));
+        assert_eq!(status("drifted"), Some(WorktreeSyncStatus::Stale));
+        assert_eq!(status("terminal"), Some(WorktreeSyncStatus::Untracked));
+        assert_eq!(status("main"), None);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
@@ -0,0 +1,175 @@
+use std::path::{Path, PathBuf};
+
+use crate::types::WorkspaceEntry;
+
+/// Lowercases `text` into a dash-separated slug of at most ~40 characters.
+pub(crate) fn branch_slug(text: &str) -> String {
+    let mut slug = String::new();
+    for ch in text.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= 40 {
+            break;
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+/// Today's UTC date as `YYYY-MM-DD`.
+pub(crate) fn utc_date_string() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    // Days since the epoch to a civil date (Howard Hinnant's algorithm).
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Expands a branch name template such as `codex/{date}-{slug}`.
+///
+/// `{slug}` is the requested name slugified, `{name}` the requested name as
+/// typed, `{workspace}` the parent workspace's name slugified and `{date}`
+/// today's date.
+pub(crate) fn render_branch_template(
+    template: &str,
+    name: &str,
+    workspace_name: &str,
+    date: &str,
+) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or("Unclosed placeholder in branch template.")?;
+        let key = &rest[start + 1..start + end];
+        match key {
+            "slug" => result.push_str(&branch_slug(name)),
+            "name" => result.push_str(name),
+            "workspace" => result.push_str(&branch_slug(workspace_name)),
+            "date" => result.push_str(date),
+            _ => return Err(format!("Unknown branch template placeholder: {{{key}}}")),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+            return PathBuf::from(home).join(rest);
+        }
+    }
+    PathBuf::from(path)
+}
+
+/// Picks the directory new worktrees of `parent` are created in.
+///
+/// A per-workspace root is used as is (relative paths resolve against the
+/// workspace); the global root gets a subdirectory per workspace; without
+/// either, worktrees live in the app data dir.
+pub(crate) fn worktree_root_dir(
+    parent: &WorkspaceEntry,
+    global_root: Option<&str>,
+    data_dir: &Path,
+) -> PathBuf {
+    let configured = |value: Option<&str>| {
+        value
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(expand_home)
+    };
+    if let Some(root) = configured(parent.settings.worktree_root.as_deref()) {
+        return if root.is_absolute() {
+            root
+        } else {
+            PathBuf::from(&parent.path).join(root)
+        };
+    }
+    if let Some(root) = configured(global_root) {
+        let slug = branch_slug(&parent.name);
+        return root.join(if slug.is_empty() {
+            parent.id.as_str()
+        } else {
+            slug.as_str()
+        });
+    }
+    data_dir.join("worktrees").join(&parent.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{branch_slug, render_branch_template, worktree_root_dir};
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::path::{Path, PathBuf};
+
+    fn parent(settings: WorkspaceSettings) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: "parent-id".to_string(),
+            name: "My App".to_string(),
+            path: "/repos/my-app".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings,
+        }
+    }
+
+    #[test]
+    fn render_branch_template_fills_placeholders() {
+        assert_eq!(branch_slug("Fix the Login crash!"), "fix-the-login-crash");
+        assert_eq!(
+            render_branch_template("codex/{date}-{slug}", "Fix login", "App", "2026-10-18")
+                .expect("render"),
+            "codex/2026-10-18-fix-login"
+        );
+        assert_eq!(
+            render_branch_template("{workspace}/{name}", "feat/x", "My App", "").expect("render"),
+            "my-app/feat/x"
+        );
+        assert!(render_branch_template("codex/{user}", "x", "y", "").is_err());
+        assert!(render_branch_template("codex/{slug", "x", "y", "").is_err());
+    }
+
+    #[test]
+    fn worktree_root_dir_prefers_workspace_then_global_root() {
+        let data_dir = Path::new("/data");
+        assert_eq!(
+            worktree_root_dir(&parent(WorkspaceSettings::default()), None, data_dir),
+            PathBuf::from("/data/worktrees/parent-id")
+        );
+        assert_eq!(
+            worktree_root_dir(
+                &parent(WorkspaceSettings::default()),
+                Some("/worktrees"),
+                data_dir
+            ),
+            PathBuf::from("/worktrees/my-app")
+        );
+        let settings = WorkspaceSettings {
+            worktree_root: Some("../my-app-worktrees".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            worktree_root_dir(&parent(settings), Some("/worktrees"), data_dir),
+            PathBuf::from("/repos/my-app/../my-app-worktrees")
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::backend::events::EventSink;
+use crate::backend::worktree_setup::{
+    cancel_run, ensure_run_finished, wait_for_run, WorktreeSetupRun, SETUP_WAIT_TIMEOUT,
+};
+use crate::event_sink::TauriEventSink;
+use crate::state::AppState;
+use crate::types::{WorkspaceEntry, WorktreeSetupStatus};
+
+/// Starts the parent's setup for a new worktree, replacing any earlier run.
+/// Does nothing when the parent has no setup configured.
+pub(crate) async fn start_worktree_setup(
+    state: &AppState,
+    parent: &WorkspaceEntry,
+    worktree: &WorkspaceEntry,
+    app: AppHandle,
+) {
+    let config = parent.settings.worktree_setup.clone();
+    if config.is_empty() {
+        return;
+    }
+    let run = WorktreeSetupRun::start(
+        worktree.id.clone(),
+        PathBuf::from(&parent.path),
+        PathBuf::from(&worktree.path),
+        config,
+        TauriEventSink::new(app),
+    );
+    state
+        .worktree_setups
+        .lock()
+        .await
+        .insert(worktree.id.clone(), run);
+}
+
+/// Waits for a worktree's setup to finish, following reruns. Returns `None`
+/// when the worktree has no setup run (or it was canceled).
+pub(crate) async fn wait_for_setup(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<Option<WorktreeSetupStatus>, String> {
+    wait_for_run(&state.worktree_setups, workspace_id, SETUP_WAIT_TIMEOUT).await
+}
+
+/// Ensures a worktree is ready for a message: waits for its setup and
+/// rejects the message when the setup failed.
+pub(crate) async fn ensure_setup_finished(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<(), String> {
+    ensure_run_finished(&state.worktree_setups, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn get_worktree_setup_status(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<WorktreeSetupStatus>, String> {
+    Ok(state
+        .worktree_setups
+        .lock()
+        .await
+        .get(&workspace_id)
+        .map(WorktreeSetupRun::status))
+}
+
+#[tauri::command]
+pub(crate) async fn wait_for_worktree_setup(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<WorktreeSetupStatus>, String> {
+    wait_for_setup(&state, &workspace_id).await
+}
+
+/// Stops a worktree's setup, or dismisses a failed one, so messages can be
+/// sent without it.
+#[tauri::command]
+pub(crate) async fn cancel_worktree_setup(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if let Some(status) = cancel_run(&state.worktree_setups, &workspace_id).await {
+        TauriEventSink::new(app).emit_worktree_setup_status(status);
+    }
+    Ok(())
+}
+
+/// Runs the parent's current setup again, e.g. after fixing a failing command.
+#[tauri::command]
+pub(crate) async fn rerun_worktree_setup(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let (entry, parent) = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?;
+        let parent = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned()
+            .ok_or("Not a worktree workspace.")?;
+        (entry, parent)
+    };
+    if parent.settings.worktree_setup.is_empty() {
+        return Err("No worktree setup is configured for this workspace.".to_string());
+    }
+    start_worktree_setup(&state, &parent, &entry, app).await;
+    Ok(())
+}
@@ -79,6 +79,7 @@ import { useWorktreePrompt } from "./features/workspaces/hooks/useWorktreePrompt
 import { useUiScaleShortcuts } from "./features/layout/hooks/useUiScaleShortcuts";
 import { useWorkspaceSelection } from "./features/workspaces/hooks/useWorkspaceSelection";
 import { useNewAgentShortcut } from "./features/app/hooks/useNewAgentShortcut";
+import { useApprovalAutoDecisions } from "./features/app/hooks/useApprovalAutoDecisions";
 import { useAgentSoundNotifications } from "./features/notifications/hooks/useAgentSoundNotifications";
 import { useWindowFocusState } from "./features/layout/hooks/useWindowFocusState";
 import { useCopyThread } from "./features/threads/hooks/useCopyThread";
@@ -175,7 +176,9 @@ function MainApp() {
   const [selectedPullRequest, setSelectedPullRequest] =
     useState<GitHubPullRequest | null>(null);
   const [diffSource, setDiffSource] = useState<"local" | "pr">("local");
-  const [accessMode, setAccessMode] = useState<AccessMode>("current");
+  const [accessModeOverrides, setAccessModeOverrides] = useState<
+    Record<string, AccessMode>
+  >({});
   const [activeTab, setActiveTab] = useState<
     "projects" | "codex" | "git" | "log"
   >("codex");
@@ -263,6 +266,7 @@ function MainApp() {
   const composerInputRef = useRef<HTMLTextAreaElement | null>(null);
 
   const updater = useUpdater({ onDebug: addDebugEntry });
+  const { autoDecisions, dismissAutoDecision } = useApprovalAutoDecisions();
   const isWindowFocused = useWindowFocusState();
   const nextTestSoundIsError = useRef(false);
 
@@ -311,11 +315,20 @@ function MainApp() {
     onUpdateAppSettings: queueSaveSettings,
   });
 
-  useEffect(() => {
-    setAccessMode((prev) =>
-      prev === "current" ? appSettings.defaultAccessMode : prev
-    );
-  }, [appSettings.defaultAccessMode]);
+  // Only an access mode picked in the composer is sent with a message; until
+  // then the backend applies the workspace's profile or default access mode.
+  const accessModeKey = activeWorkspace?.id ?? "";
+  const accessModeOverride = accessModeOverrides[accessModeKey] ?? null;
+  const accessMode =
+    accessModeOverride ??
+    activeWorkspace?.settings.defaultAccessMode ??
+    appSettings.defaultAccessMode;
+  const setAccessMode = useCallback(
+    (mode: AccessMode) => {
+      setAccessModeOverrides((prev) => ({ ...prev, [accessModeKey]: mode }));
+    },
+    [accessModeKey],
+  );
 
   const { status: gitStatus, refresh: refreshGitStatus } =
     useGitStatus(activeWorkspace);
@@ -394,8 +407,11 @@ function MainApp() {
   } = useModels({
     activeWorkspace,
     onDebug: addDebugEntry,
-    preferredModelId: appSettings.lastComposerModelId,
-    preferredEffort: appSettings.lastComposerReasoningEffort,
+    preferredModelId:
+      activeWorkspace?.settings.defaultModel ?? appSettings.lastComposerModelId,
+    preferredEffort:
+      activeWorkspace?.settings.defaultEffort ??
+      appSettings.lastComposerReasoningEffort,
   });
 
   useComposerShortcuts({
@@ -536,22 +552,33 @@ function MainApp() {
     if (!selectedModelId && selectedEffort === null) {
       return;
     }
+    // A workspace default is not the user's last choice, so leave it out.
+    const hasDefaultModel = Boolean(activeWorkspace?.settings.defaultModel);
+    const hasDefaultEffort = Boolean(activeWorkspace?.settings.defaultEffort);
     setAppSettings((current) => {
+      const lastComposerModelId = hasDefaultModel
+        ? current.lastComposerModelId
+        : selectedModelId;
+      const lastComposerReasoningEffort = hasDefaultEffort
+        ? current.lastComposerReasoningEffort
+        : selectedEffort;
       if (
-        current.lastComposerModelId === selectedModelId &&
-        current.lastComposerReasoningEffort === selectedEffort
+        current.lastComposerModelId === lastComposerModelId &&
+        current.lastComposerReasoningEffort === lastComposerReasoningEffort
       ) {
         return current;
       }
       const nextSettings = {
         ...current,
-        lastComposerModelId: selectedModelId,
-        lastComposerReasoningEffort: selectedEffort,
+        lastComposerModelId,
+        lastComposerReasoningEffort,
       };
       void queueSaveSettings(nextSettings);
       return nextSettings;
     });
   }, [
+    activeWorkspace?.settings.defaultEffort,
+    activeWorkspace?.settings.defaultModel,
     appSettingsLoading,
     queueSaveSettings,
     selectedEffort,
@@ -611,7 +638,7 @@ function MainApp() {
     model: resolvedModel,
     effort: selectedEffort,
     collaborationMode: selectedCollaborationMode?.value ?? null,
-    accessMode,
+    accessMode: accessModeOverride ?? undefined,
     customPrompts: prompts,
     onMessageActivity: refreshGitStatus
   });
@@ -1163,6 +1190,8 @@ function MainApp() {
     activeRateLimits,
     approvals,
     handleApprovalDecision,
+    autoDecisions,
+    onDismissAutoDecision: dismissAutoDecision,
     onOpenSettings: () => handleOpenSettings(),
     onOpenDictationSettings: () => handleOpenSettings("dictation"),
     onOpenDebug: handleDebugClick,
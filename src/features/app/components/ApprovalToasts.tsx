@@ -1,16 +1,21 @@
 import { useEffect, useMemo } from "react";
 import type { ApprovalRequest, WorkspaceInfo } from "../../../types";
+import type { ApprovalAutoDecision } from "../hooks/useApprovalAutoDecisions";
 
 type ApprovalToastsProps = {
   approvals: ApprovalRequest[];
   workspaces: WorkspaceInfo[];
   onDecision: (request: ApprovalRequest, decision: "accept" | "decline") => void;
+  autoDecisions?: ApprovalAutoDecision[];
+  onDismissAutoDecision?: (id: string) => void;
 };
 
 export function ApprovalToasts({
   approvals,
   workspaces,
   onDecision,
+  autoDecisions = [],
+  onDismissAutoDecision,
 }: ApprovalToastsProps) {
   const workspaceLabels = useMemo(
     () => new Map(workspaces.map((workspace) => [workspace.id, workspace.name])),
@@ -46,7 +51,7 @@ export function ApprovalToasts({
     return () => window.removeEventListener("keydown", handler);
   }, [onDecision, primaryRequest]);
 
-  if (!approvals.length) {
+  if (!approvals.length && !autoDecisions.length) {
     return null;
   }
 
@@ -140,6 +145,43 @@ export function ApprovalToasts({
           </div>
         );
       })}
+      {autoDecisions.map((decision) => {
+        const workspaceName = workspaceLabels.get(decision.workspaceId);
+        return (
+          <div
+            key={decision.id}
+            className="approval-toast approval-toast-auto"
+            role="status"
+          >
+            <div className="approval-toast-header">
+              <div className="approval-toast-title">
+                {decision.decision === "accept" ? "Auto-approved" : "Auto-declined"}
+              </div>
+              {workspaceName ? (
+                <div className="approval-toast-workspace">{workspaceName}</div>
+              ) : null}
+            </div>
+            <div className="approval-toast-method">{methodLabel(decision.method)}</div>
+            <div className="approval-toast-details">
+              <pre className="approval-toast-detail-code">{decision.subject}</pre>
+              <div className="approval-toast-detail">
+                <div className="approval-toast-detail-label">Rule</div>
+                <div className="approval-toast-detail-value">{decision.rule}</div>
+              </div>
+            </div>
+            {onDismissAutoDecision ? (
+              <div className="approval-toast-actions">
+                <button
+                  className="secondary"
+                  onClick={() => onDismissAutoDecision(decision.id)}
+                >
+                  Dismiss
+                </button>
+              </div>
+            ) : null}
+          </div>
+        );
+      })}
     </div>
   );
 }
@@ -2,6 +2,7 @@ import type { MouseEvent } from "react";
 
 import type { WorkspaceInfo } from "../../../types";
 import { useI18n } from "../../../i18n";
+import { useWorktreeSetupStatus } from "../../workspaces/hooks/useWorktreeSetupStatus";
 
 type WorktreeCardProps = {
   worktree: WorkspaceInfo;
@@ -25,6 +26,11 @@ export function WorktreeCard({
   const { t } = useI18n();
   const worktreeCollapsed = worktree.settings.sidebarCollapsed;
   const worktreeBranch = worktree.worktree?.branch ?? "";
+  const { setupStatus, cancelSetup, rerunSetup } = useWorktreeSetupStatus(
+    worktree.id,
+  );
+  const setupRunning = setupStatus?.state === "running";
+  const setupFailed = setupStatus?.state === "failed";
 
   return (
     <div className="worktree-card">
@@ -72,6 +78,36 @@ export function WorktreeCard({
           )}
         </div>
       </div>
+      {(setupRunning || setupFailed) && (
+        <div
+          className={`worktree-setup ${setupFailed ? "failed" : ""}`}
+          title={setupStatus?.error ?? setupStatus?.command ?? undefined}
+        >
+          <span className="worktree-setup-label">
+            {setupFailed
+              ? t("workspaces.setup.failed")
+              : t("workspaces.setup.running")}
+          </span>
+          {setupFailed && (
+            <button
+              className="worktree-setup-action"
+              onClick={() => void rerunSetup()}
+              data-tauri-drag-region="false"
+            >
+              {t("workspaces.setup.rerun")}
+            </button>
+          )}
+          <button
+            className="worktree-setup-action"
+            onClick={() => void cancelSetup()}
+            data-tauri-drag-region="false"
+          >
+            {setupFailed
+              ? t("workspaces.setup.dismiss")
+              : t("workspaces.setup.cancel")}
+          </button>
+        </div>
+      )}
       {children}
     </div>
   );
@@ -0,0 +1,64 @@
+import { useCallback, useEffect, useRef, useState } from "react";
+import {
+  subscribeApprovalAutoDecisions,
+  type ApprovalAutoDecisionEvent,
+} from "../../../services/events";
+
+const VISIBLE_MS = 8000;
+const MAX_VISIBLE = 3;
+
+export type ApprovalAutoDecision = ApprovalAutoDecisionEvent & { id: string };
+
+// Approval requests settled by workspace rules never reach the approval
+// toasts, so each decision is shown briefly next to them instead.
+export function useApprovalAutoDecisions() {
+  const [decisions, setDecisions] = useState<ApprovalAutoDecision[]>([]);
+  const timersRef = useRef(new Map<string, number>());
+
+  const dismiss = useCallback((id: string) => {
+    const timer = timersRef.current.get(id);
+    if (timer !== undefined) {
+      window.clearTimeout(timer);
+      timersRef.current.delete(id);
+    }
+    setDecisions((prev) => prev.filter((decision) => decision.id !== id));
+  }, []);
+
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    let canceled = false;
+    subscribeApprovalAutoDecisions((event) => {
+      const id = `${event.workspaceId}-${event.requestId}-${Date.now()}`;
+      setDecisions((prev) => [...prev, { ...event, id }].slice(-MAX_VISIBLE));
+      timersRef.current.set(
+        id,
+        window.setTimeout(() => dismiss(id), VISIBLE_MS),
+      );
+    })
+      .then((handler) => {
+        if (canceled) {
+          handler();
+          return;
+        }
+        unlisten = handler;
+      })
+      .catch(() => {
+        // Without the listener decisions are only missing from the UI.
+      });
+    const timers = timersRef.current;
+    return () => {
+      canceled = true;
+      if (unlisten) {
+        try {
+          unlisten();
+        } catch {
+          // Ignore double-unlisten when tearing down.
+        }
+      }
+      timers.forEach((timer) => window.clearTimeout(timer));
+      timers.clear();
+    };
+  }, [dismiss]);
+
+  return { autoDecisions: decisions, dismissAutoDecision: dismiss };
+}
@@ -1,10 +1,15 @@
 import { useCallback, useEffect, useRef, useState } from "react";
-import type { GitHubIssue, WorkspaceInfo } from "../../../types";
-import { getGitHubIssues } from "../../../services/tauri";
+import type {
+  ForgeListQuery,
+  GitHubIssue,
+  WorkspaceInfo,
+} from "../../../types";
+import { getForgeIssues } from "../../../services/tauri";
 
 type GitHubIssuesState = {
   issues: GitHubIssue[];
   total: number;
+  nextCursor: string | null;
   isLoading: boolean;
   error: string | null;
 };
@@ -12,6 +17,7 @@ type GitHubIssuesState = {
 const emptyState: GitHubIssuesState = {
   issues: [],
   total: 0,
+  nextCursor: null,
   isLoading: false,
   error: null,
 };
@@ -19,10 +25,12 @@ const emptyState: GitHubIssuesState = {
 export function useGitHubIssues(
   activeWorkspace: WorkspaceInfo | null,
   enabled: boolean,
+  query: ForgeListQuery | null = null,
 ) {
   const [state, setState] = useState<GitHubIssuesState>(emptyState);
   const requestIdRef = useRef(0);
   const workspaceIdRef = useRef<string | null>(activeWorkspace?.id ?? null);
+  const queryKey = JSON.stringify(query ?? {});
 
   const refresh = useCallback(async () => {
     if (!activeWorkspace) {
@@ -34,7 +42,10 @@ export function useGitHubIssues(
     requestIdRef.current = requestId;
     setState((prev) => ({ ...prev, isLoading: true, error: null }));
     try {
-      const response = await getGitHubIssues(workspaceId);
+      const response = await getForgeIssues(
+        workspaceId,
+        JSON.parse(queryKey) as ForgeListQuery,
+      );
       if (
         requestIdRef.current !== requestId ||
         workspaceIdRef.current !== workspaceId
@@ -44,6 +55,7 @@ export function useGitHubIssues(
       setState({
         issues: response.issues,
         total: response.total,
+        nextCursor: response.nextCursor ?? null,
         isLoading: false,
         error: null,
       });
@@ -58,11 +70,55 @@ export function useGitHubIssues(
       setState({
         issues: [],
         total: 0,
+        nextCursor: null,
         isLoading: false,
         error: error instanceof Error ? error.message : String(error),
       });
     }
-  }, [activeWorkspace]);
+  }, [activeWorkspace, queryKey]);
+
+  const loadMore = useCallback(async () => {
+    const cursor = state.nextCursor;
+    if (!activeWorkspace || !cursor || state.isLoading) {
+      return;
+    }
+    const workspaceId = activeWorkspace.id;
+    const requestId = requestIdRef.current + 1;
+    requestIdRef.current = requestId;
+    setState((prev) => ({ ...prev, isLoading: true, error: null }));
+    try {
+      const response = await getForgeIssues(workspaceId, {
+        ...(JSON.parse(queryKey) as ForgeListQuery),
+        cursor,
+      });
+      if (
+        requestIdRef.current !== requestId ||
+        workspaceIdRef.current !== workspaceId
+      ) {
+        return;
+      }
+      setState((prev) => ({
+        issues: [...prev.issues, ...response.issues],
+        total: response.total,
+        nextCursor: response.nextCursor ?? null,
+        isLoading: false,
+        error: null,
+      }));
+    } catch (error) {
+      console.error("Failed to load more GitHub issues", error);
+      if (
+        requestIdRef.current !== requestId ||
+        workspaceIdRef.current !== workspaceId
+      ) {
+        return;
+      }
+      setState((prev) => ({
+        ...prev,
+        isLoading: false,
+        error: error instanceof Error ? error.message : String(error),
+      }));
+    }
+  }, [activeWorkspace, queryKey, state.isLoading, state.nextCursor]);
 
   useEffect(() => {
     const workspaceId = activeWorkspace?.id ?? null;
@@ -83,8 +139,10 @@ export function useGitHubIssues(
   return {
     issues: state.issues,
     total: state.total,
+    hasMore: state.nextCursor !== null,
     isLoading: state.isLoading,
     error: state.error,
     refresh,
+    loadMore,
   };
 }
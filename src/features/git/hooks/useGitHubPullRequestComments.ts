@@ -1,6 +1,6 @@
 import { useCallback, useEffect, useRef, useState } from "react";
 import type { GitHubPullRequestComment, WorkspaceInfo } from "../../../types";
-import { getGitHubPullRequestComments } from "../../../services/tauri";
+import { getForgeMergeRequestComments } from "../../../services/tauri";
 
 type PullRequestCommentsState = {
   comments: GitHubPullRequestComment[];
@@ -34,7 +34,7 @@ export function useGitHubPullRequestComments(
     requestIdRef.current = requestId;
     setState((prev) => ({ ...prev, isLoading: true, error: null }));
     try {
-      const comments = await getGitHubPullRequestComments(
+      const comments = await getForgeMergeRequestComments(
         workspaceId,
         prNumber,
       );
@@ -1,6 +1,6 @@
 import { useCallback, useEffect, useRef, useState } from "react";
 import type { GitHubPullRequestDiff, WorkspaceInfo } from "../../../types";
-import { getGitHubPullRequestDiff } from "../../../services/tauri";
+import { getForgeMergeRequestDiff } from "../../../services/tauri";
 
 type PullRequestDiffState = {
   diffs: GitHubPullRequestDiff[];
@@ -34,7 +34,7 @@ export function useGitHubPullRequestDiffs(
     requestIdRef.current = requestId;
     setState((prev) => ({ ...prev, isLoading: true, error: null }));
     try {
-      const diffs = await getGitHubPullRequestDiff(workspaceId, prNumber);
+      const diffs = await getForgeMergeRequestDiff(workspaceId, prNumber);
       if (
         requestIdRef.current !== requestId ||
         workspaceIdRef.current !== workspaceId ||
@@ -1,10 +1,15 @@
 import { useCallback, useEffect, useRef, useState } from "react";
-import type { GitHubPullRequest, WorkspaceInfo } from "../../../types";
-import { getGitHubPullRequests } from "../../../services/tauri";
+import type {
+  ForgeListQuery,
+  GitHubPullRequest,
+  WorkspaceInfo,
+} from "../../../types";
+import { getForgeMergeRequests } from "../../../services/tauri";
 
 type GitHubPullRequestsState = {
   pullRequests: GitHubPullRequest[];
   total: number;
+  nextCursor: string | null;
   isLoading: boolean;
   error: string | null;
 };
@@ -12,6 +17,7 @@ type GitHubPullRequestsState = {
 const emptyState: GitHubPullRequestsState = {
   pullRequests: [],
   total: 0,
+  nextCursor: null,
   isLoading: false,
   error: null,
 };
@@ -19,10 +25,12 @@ const emptyState: GitHubPullRequestsState = {
 export function useGitHubPullRequests(
   activeWorkspace: WorkspaceInfo | null,
   enabled: boolean,
+  query: ForgeListQuery | null = null,
 ) {
   const [state, setState] = useState<GitHubPullRequestsState>(emptyState);
   const requestIdRef = useRef(0);
   const workspaceIdRef = useRef<string | null>(activeWorkspace?.id ?? null);
+  const queryKey = JSON.stringify(query ?? {});
 
   const refresh = useCallback(async () => {
     if (!activeWorkspace) {
@@ -34,7 +42,10 @@ export function useGitHubPullRequests(
     requestIdRef.current = requestId;
     setState((prev) => ({ ...prev, isLoading: true, error: null }));
     try {
-      const response = await getGitHubPullRequests(workspaceId);
+      const response = await getForgeMergeRequests(
+        workspaceId,
+        JSON.parse(queryKey) as ForgeListQuery,
+      );
       if (
         requestIdRef.current !== requestId ||
         workspaceIdRef.current !== workspaceId
@@ -44,6 +55,7 @@ export function useGitHubPullRequests(
       setState({
         pullRequests: response.pullRequests,
         total: response.total,
+        nextCursor: response.nextCursor ?? null,
         isLoading: false,
         error: null,
       });
@@ -58,11 +70,55 @@ export function useGitHubPullRequests(
       setState({
         pullRequests: [],
         total: 0,
+        nextCursor: null,
         isLoading: false,
         error: error instanceof Error ? error.message : String(error),
       });
     }
-  }, [activeWorkspace]);
+  }, [activeWorkspace, queryKey]);
+
+  const loadMore = useCallback(async () => {
+    const cursor = state.nextCursor;
+    if (!activeWorkspace || !cursor || state.isLoading) {
+      return;
+    }
+    const workspaceId = activeWorkspace.id;
+    const requestId = requestIdRef.current + 1;
+    requestIdRef.current = requestId;
+    setState((prev) => ({ ...prev, isLoading: true, error: null }));
+    try {
+      const response = await getForgeMergeRequests(workspaceId, {
+        ...(JSON.parse(queryKey) as ForgeListQuery),
+        cursor,
+      });
+      if (
+        requestIdRef.current !== requestId ||
+        workspaceIdRef.current !== workspaceId
+      ) {
+        return;
+      }
+      setState((prev) => ({
+        pullRequests: [...prev.pullRequests, ...response.pullRequests],
+        total: response.total,
+        nextCursor: response.nextCursor ?? null,
+        isLoading: false,
+        error: null,
+      }));
+    } catch (error) {
+      console.error("Failed to load more GitHub pull requests", error);
+      if (
+        requestIdRef.current !== requestId ||
+        workspaceIdRef.current !== workspaceId
+      ) {
+        return;
+      }
+      setState((prev) => ({
+        ...prev,
+        isLoading: false,
+        error: error instanceof Error ? error.message : String(error),
+      }));
+    }
+  }, [activeWorkspace, queryKey, state.isLoading, state.nextCursor]);
 
   useEffect(() => {
     const workspaceId = activeWorkspace?.id ?? null;
@@ -83,8 +139,10 @@ export function useGitHubPullRequests(
   return {
     pullRequests: state.pullRequests,
     total: state.total,
+    hasMore: state.nextCursor !== null,
     isLoading: state.isLoading,
     error: state.error,
     refresh,
+    loadMore,
   };
 }
@@ -1,6 +1,7 @@
 import { useCallback, useEffect, useRef, useState } from "react";
 import type { GitFileStatus, WorkspaceInfo } from "../../../types";
-import { getGitStatus } from "../../../services/tauri";
+import { getGitStatus, watchGitStatus } from "../../../services/tauri";
+import { subscribeGitStatusChanged } from "../../../services/events";
 
 type GitStatusState = {
   branchName: string;
@@ -22,7 +23,6 @@ const emptyStatus: GitStatusState = {
   error: null,
 };
 
-const REFRESH_INTERVAL_MS = 3000;
 export function useGitStatus(activeWorkspace: WorkspaceInfo | null) {
   const [status, setStatus] = useState<GitStatusState>(emptyStatus);
   const requestIdRef = useRef(0);
@@ -104,11 +104,43 @@ export function useGitStatus(activeWorkspace: WorkspaceInfo | null) {
       refresh()?.catch(() => {});
     };
 
-    fetchStatus();
-    const interval = window.setInterval(fetchStatus, REFRESH_INTERVAL_MS);
+    let unlisten: (() => void) | null = null;
+    let canceled = false;
+    subscribeGitStatusChanged((event) => {
+      if (event.workspaceId === workspaceId) {
+        fetchStatus();
+      }
+    })
+      .then((handler) => {
+        if (canceled) {
+          handler();
+          return;
+        }
+        unlisten = handler;
+      })
+      .catch(() => {
+        // Without the listener the status only refreshes on demand.
+      });
+    // Connected workspaces are already watched; this covers the rest.
+    watchGitStatus(workspaceId)
+      .catch(() => {
+        // Workspaces that aren't repositories have nothing to watch.
+      })
+      .finally(() => {
+        if (!canceled) {
+          fetchStatus();
+        }
+      });
 
     return () => {
-      window.clearInterval(interval);
+      canceled = true;
+      if (unlisten) {
+        try {
+          unlisten();
+        } catch {
+          // Ignore double-unlisten when tearing down.
+        }
+      }
     };
   }, [refresh, workspaceId]);
 
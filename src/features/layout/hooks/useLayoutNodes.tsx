@@ -5,6 +5,7 @@ import { Home } from "../../home/components/Home";
 import { MainHeader } from "../../app/components/MainHeader";
 import { Messages } from "../../messages/components/Messages";
 import { ApprovalToasts } from "../../app/components/ApprovalToasts";
+import type { ApprovalAutoDecision } from "../../app/hooks/useApprovalAutoDecisions";
 import { UpdateToast } from "../../update/components/UpdateToast";
 import { Composer } from "../../composer/components/Composer";
 import { GitDiffPanel } from "../../git/components/GitDiffPanel";
@@ -84,6 +85,8 @@ type LayoutNodesOptions = {
     request: ApprovalRequest,
     decision: "accept" | "decline",
   ) => void;
+  autoDecisions: ApprovalAutoDecision[];
+  onDismissAutoDecision: (id: string) => void;
   onOpenSettings: () => void;
   onOpenDictationSettings?: () => void;
   onOpenDebug: () => void;
@@ -433,6 +436,8 @@ export function useLayoutNodes(options: LayoutNodesOptions): LayoutNodesResult {
       approvals={options.approvals}
       workspaces={options.workspaces}
       onDecision={options.handleApprovalDecision}
+      autoDecisions={options.autoDecisions}
+      onDismissAutoDecision={options.onDismissAutoDecision}
     />
   );
 
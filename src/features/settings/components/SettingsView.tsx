@@ -20,6 +20,7 @@ import type {
   WorkspaceGroup,
   WorkspaceInfo,
 } from "../../../types";
+import { clearSecret, setSecret } from "../../../services/tauri";
 import { useI18n } from "../../../i18n";
 import type { Locale } from "../../../i18n";
 import { formatDownloadSize } from "../../../utils/formatting";
@@ -102,7 +103,7 @@ export function SettingsView({
   const [activeSection, setActiveSection] = useState<CodexSection>("projects");
   const [codexPathDraft, setCodexPathDraft] = useState(appSettings.codexBin ?? "");
   const [remoteHostDraft, setRemoteHostDraft] = useState(appSettings.remoteBackendHost);
-  const [remoteTokenDraft, setRemoteTokenDraft] = useState(appSettings.remoteBackendToken ?? "");
+  const [remoteTokenDraft, setRemoteTokenDraft] = useState("");
   const [scaleDraft, setScaleDraft] = useState(
     `${Math.round(clampUiScale(appSettings.uiScale) * 100)}%`,
   );
@@ -144,10 +145,6 @@ export function SettingsView({
     setRemoteHostDraft(appSettings.remoteBackendHost);
   }, [appSettings.remoteBackendHost]);
 
-  useEffect(() => {
-    setRemoteTokenDraft(appSettings.remoteBackendToken ?? "");
-  }, [appSettings.remoteBackendToken]);
-
   useEffect(() => {
     setScaleDraft(`${Math.round(clampUiScale(appSettings.uiScale) * 100)}%`);
   }, [appSettings.uiScale]);
@@ -225,14 +222,23 @@ export function SettingsView({
   };
 
   const handleCommitRemoteToken = async () => {
-    const nextToken = remoteTokenDraft.trim() ? remoteTokenDraft.trim() : null;
-    setRemoteTokenDraft(nextToken ?? "");
-    if (nextToken === appSettings.remoteBackendToken) {
+    const nextToken = remoteTokenDraft.trim();
+    setRemoteTokenDraft("");
+    if (!nextToken) {
       return;
     }
+    const saved = await setSecret("remoteBackendToken", nextToken);
+    await onUpdateAppSettings({
+      ...appSettings,
+      remoteBackendToken: saved.remoteBackendToken,
+    });
+  };
+
+  const handleClearRemoteToken = async () => {
+    const saved = await clearSecret("remoteBackendToken");
     await onUpdateAppSettings({
       ...appSettings,
-      remoteBackendToken: nextToken,
+      remoteBackendToken: saved.remoteBackendToken,
     });
   };
 
@@ -1256,7 +1262,11 @@ export function SettingsView({
                         type="password"
                         className="settings-input settings-input--compact"
                         value={remoteTokenDraft}
-                        placeholder={t("settings.codex.remote_token_placeholder")}
+                        placeholder={
+                          appSettings.remoteBackendToken
+                            ? t("settings.codex.remote_token_saved")
+                            : t("settings.codex.remote_token_placeholder")
+                        }
                         onChange={(event) => setRemoteTokenDraft(event.target.value)}
                         onBlur={() => {
                           void handleCommitRemoteToken();
@@ -1269,6 +1279,18 @@ export function SettingsView({
                         }}
                         aria-label={t("settings.codex.remote_token_aria")}
                       />
+                      {appSettings.remoteBackendToken && (
+                        <button
+                          type="button"
+                          className="ghost icon-button"
+                          onClick={() => {
+                            void handleClearRemoteToken();
+                          }}
+                          aria-label={t("settings.codex.remote_token_clear")}
+                        >
+                          <X aria-hidden />
+                        </button>
+                      )}
                     </div>
                     <div className="settings-help">
                       Start the daemon separately and point CodexMonitor to it (host:port + token).
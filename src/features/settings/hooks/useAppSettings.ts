@@ -24,6 +24,14 @@ const defaultSettings: AppSettings = {
   dictationPreferredLanguage: null,
   dictationHoldKey: "alt",
   workspaceGroups: [],
+  diffMaxFileBytes: 256 * 1024,
+  diffMaxTotalBytes: 4 * 1024 * 1024,
+  forgeHosts: {},
+  sandboxProfiles: [],
+  githubToken: null,
+  gitlabToken: null,
+  worktreeRoot: null,
+  worktreeBranchTemplate: null,
 };
 
 function normalizeAppSettings(settings: AppSettings): AppSettings {
@@ -0,0 +1,77 @@
+import { useCallback, useEffect, useState } from "react";
+import type { WorktreeSetupStatus } from "../../../types";
+import { subscribeWorktreeSetupStatus } from "../../../services/events";
+import {
+  cancelWorktreeSetup,
+  getWorktreeSetupStatus,
+  rerunWorktreeSetup,
+} from "../../../services/tauri";
+
+// Messages to a worktree wait for its setup, so a running or failed setup
+// is shown with a way to cancel or rerun it.
+export function useWorktreeSetupStatus(workspaceId: string) {
+  const [status, setStatus] = useState<WorktreeSetupStatus | null>(null);
+
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    let canceled = false;
+    getWorktreeSetupStatus(workspaceId)
+      .then((current) => {
+        if (!canceled) {
+          setStatus(current);
+        }
+      })
+      .catch(() => {
+        // Events still update the status once setup reports progress.
+      });
+    subscribeWorktreeSetupStatus((event) => {
+      if (event.workspaceId === workspaceId) {
+        setStatus(event);
+      }
+    })
+      .then((handler) => {
+        if (canceled) {
+          handler();
+          return;
+        }
+        unlisten = handler;
+      })
+      .catch(() => {
+        // Without the listener only the initial status is shown.
+      });
+    return () => {
+      canceled = true;
+      if (unlisten) {
+        try {
+          unlisten();
+        } catch {
+          // Ignore double-unlisten when tearing down.
+        }
+      }
+    };
+  }, [workspaceId]);
+
+  const showError = useCallback((error: unknown) => {
+    const message = error instanceof Error ? error.message : String(error);
+    setStatus((prev) => (prev ? { ...prev, error: message } : prev));
+  }, []);
+
+  const cancel = useCallback(async () => {
+    try {
+      await cancelWorktreeSetup(workspaceId);
+      setStatus(null);
+    } catch (error) {
+      showError(error);
+    }
+  }, [showError, workspaceId]);
+
+  const rerun = useCallback(async () => {
+    try {
+      await rerunWorktreeSetup(workspaceId);
+    } catch (error) {
+      showError(error);
+    }
+  }, [showError, workspaceId]);
+
+  return { setupStatus: status, cancelSetup: cancel, rerunSetup: rerun };
+}
@@ -1,5 +1,10 @@
 import { listen } from "@tauri-apps/api/event";
-import type { AppServerEvent, DictationEvent, DictationModelStatus } from "../types";
+import type {
+  AppServerEvent,
+  DictationEvent,
+  DictationModelStatus,
+  WorktreeSetupStatus,
+} from "../types";
 
 export type Unsubscribe = () => void;
 
@@ -9,6 +14,41 @@ export type TerminalOutputEvent = {
   data: string;
 };
 
+export type GitStatusChangedEvent = {
+  workspaceId: string;
+  paths: string[];
+  full: boolean;
+};
+
+export type WorktreeSetupOutputEvent = {
+  workspaceId: string;
+  command: string;
+  stream: "stdout" | "stderr";
+  line: string;
+};
+
+export type CloneProgressEvent = {
+  destination: string;
+  phase: string;
+  percent: number | null;
+  current: number | null;
+  total: number | null;
+  line: string;
+};
+
+export type StorageChangedEvent = {
+  file: "workspaces" | "settings";
+};
+
+export type ApprovalAutoDecisionEvent = {
+  workspaceId: string;
+  requestId: number;
+  method: string;
+  decision: "accept" | "decline";
+  rule: string;
+  subject: string;
+};
+
 export async function subscribeAppServerEvents(
   onEvent: (event: AppServerEvent) => void,
 ): Promise<Unsubscribe> {
@@ -40,3 +80,54 @@ export async function subscribeTerminalOutput(
     onEvent(event.payload);
   });
 }
+
+export async function subscribeGitStatusChanged(
+  onEvent: (event: GitStatusChangedEvent) => void,
+): Promise<Unsubscribe> {
+  return listen<GitStatusChangedEvent>("git-status-changed", (event) => {
+    onEvent(event.payload);
+  });
+}
+
+export async function subscribeWorktreeSetupOutput(
+  onEvent: (event: WorktreeSetupOutputEvent) => void,
+): Promise<Unsubscribe> {
+  return listen<WorktreeSetupOutputEvent>("worktree-setup-output", (event) => {
+    onEvent(event.payload);
+  });
+}
+
+export async function subscribeWorktreeSetupStatus(
+  onEvent: (event: WorktreeSetupStatus) => void,
+): Promise<Unsubscribe> {
+  return listen<WorktreeSetupStatus>("worktree-setup-status", (event) => {
+    onEvent(event.payload);
+  });
+}
+
+export async function subscribeCloneProgress(
+  onEvent: (event: CloneProgressEvent) => void,
+): Promise<Unsubscribe> {
+  return listen<CloneProgressEvent>("clone-progress", (event) => {
+    onEvent(event.payload);
+  });
+}
+
+export async function subscribeStorageChanged(
+  onEvent: (event: StorageChangedEvent) => void,
+): Promise<Unsubscribe> {
+  return listen<StorageChangedEvent>("storage-changed", (event) => {
+    onEvent(event.payload);
+  });
+}
+
+export async function subscribeApprovalAutoDecisions(
+  onEvent: (event: ApprovalAutoDecisionEvent) => void,
+): Promise<Unsubscribe> {
+  return listen<ApprovalAutoDecisionEvent>(
+    "approval-auto-decision",
+    (event) => {
+      onEvent(event.payload);
+    },
+  );
+}
@@ -5,17 +5,40 @@ import type {
   CodexDoctorResult,
   DictationModelStatus,
   DictationSessionState,
+  SecretKind,
+  StorageIssue,
+  WorkspaceImportItem,
   WorkspaceInfo,
+  WorkspaceManifest,
   WorkspaceSettings,
+  WorktreeIntegrationResult,
+  WorktreeIntegrationStrategy,
+  WorktreeSetupStatus,
+  WorktreeSyncAction,
+  WorktreeSyncItem,
 } from "../types";
 import type {
+  ForgeListQuery,
+  ForgeRemote,
+  GitBlameHunk,
+  GitConflictFile,
+  GitConflictVersions,
   GitFileDiff,
+  GitFileLogEntry,
   GitFileStatus,
+  GitHubChecksResponse,
+  GitHubIssueDetail,
   GitHubIssuesResponse,
+  GitHubPullRequest,
   GitHubPullRequestComment,
   GitHubPullRequestDiff,
+  GitHubPullRequestDraft,
+  GitHubPullRequestReview,
   GitHubPullRequestsResponse,
+  GitHubReviewDraft,
+  GitHubReviewPreview,
   GitLogResponse,
+  GitStashEntry,
   ReviewTarget,
 } from "../types";
 
@@ -54,11 +77,55 @@ export async function addWorkspace(
   return invoke<WorkspaceInfo>("add_workspace", { path, codex_bin });
 }
 
+export async function cloneWorkspace(
+  url: string,
+  destination: string,
+  options: { branch?: string; depth?: number; codexBin?: string | null } = {},
+): Promise<WorkspaceInfo> {
+  // Rejects with a `CloneError`.
+  return invoke<WorkspaceInfo>("clone_workspace", {
+    url,
+    destination,
+    branch: options.branch ?? null,
+    depth: options.depth ?? null,
+    codexBin: options.codexBin ?? null,
+  });
+}
+
 export async function addWorktree(
   parentId: string,
   branch: string,
+  options: { baseRef?: string; existingBranch?: boolean } = {},
 ): Promise<WorkspaceInfo> {
-  return invoke<WorkspaceInfo>("add_worktree", { parentId, branch });
+  return invoke<WorkspaceInfo>("add_worktree", {
+    parentId,
+    branch,
+    baseRef: options.baseRef ?? null,
+    existingBranch: options.existingBranch ?? null,
+  });
+}
+
+export async function startGitHubIssueWorktree(
+  workspaceId: string,
+  issueNumber: number,
+): Promise<{ workspace: WorkspaceInfo; threadId: string | null; turn: any }> {
+  return invoke("start_github_issue_worktree", { workspaceId, issueNumber });
+}
+
+export async function checkoutPullRequestWorktree(
+  workspaceId: string,
+  prNumber: number,
+  startReview = false,
+): Promise<{
+  workspace: WorkspaceInfo;
+  pullRequest: GitHubPullRequest;
+  review: { threadId: string; review: any } | null;
+}> {
+  return invoke("checkout_pull_request_worktree", {
+    workspaceId,
+    prNumber,
+    startReview,
+  });
 }
 
 export async function updateWorkspaceSettings(
@@ -87,6 +154,78 @@ export async function applyWorktreeChanges(workspaceId: string): Promise<void> {
   return invoke("apply_worktree_changes", { workspaceId });
 }
 
+export async function syncWorktrees(
+  parentId: string,
+): Promise<WorktreeSyncItem[]> {
+  return invoke<WorktreeSyncItem[]>("sync_worktrees", { parentId });
+}
+
+export async function applyWorktreeSync(
+  parentId: string,
+  actions: WorktreeSyncAction[],
+): Promise<WorktreeSyncItem[]> {
+  return invoke<WorktreeSyncItem[]>("apply_worktree_sync", { parentId, actions });
+}
+
+export async function integrateWorktree(
+  workspaceId: string,
+  strategy: WorktreeIntegrationStrategy,
+  options: { dryRun?: boolean; message?: string; cleanup?: boolean } = {},
+): Promise<WorktreeIntegrationResult> {
+  return invoke<WorktreeIntegrationResult>("integrate_worktree", {
+    workspaceId,
+    strategy,
+    dryRun: options.dryRun ?? null,
+    message: options.message ?? null,
+    cleanup: options.cleanup ?? null,
+  });
+}
+
+export async function exportWorkspaceManifest(
+  options: { workspaceIds?: string[]; groupIds?: string[]; path?: string } = {},
+): Promise<WorkspaceManifest> {
+  return invoke<WorkspaceManifest>("export_workspace_manifest", {
+    workspaceIds: options.workspaceIds ?? null,
+    groupIds: options.groupIds ?? null,
+    path: options.path ?? null,
+  });
+}
+
+export async function importWorkspaceManifest(
+  path: string,
+  options: { root?: string; cloneMissing?: boolean } = {},
+): Promise<WorkspaceImportItem[]> {
+  return invoke<WorkspaceImportItem[]>("import_workspace_manifest", {
+    path,
+    root: options.root ?? null,
+    cloneMissing: options.cloneMissing ?? null,
+  });
+}
+
+export async function getWorktreeSetupStatus(
+  workspaceId: string,
+): Promise<WorktreeSetupStatus | null> {
+  return invoke<WorktreeSetupStatus | null>("get_worktree_setup_status", {
+    workspaceId,
+  });
+}
+
+export async function waitForWorktreeSetup(
+  workspaceId: string,
+): Promise<WorktreeSetupStatus | null> {
+  return invoke<WorktreeSetupStatus | null>("wait_for_worktree_setup", {
+    workspaceId,
+  });
+}
+
+export async function rerunWorktreeSetup(workspaceId: string): Promise<void> {
+  return invoke("rerun_worktree_setup", { workspaceId });
+}
+
+export async function cancelWorktreeSetup(workspaceId: string): Promise<void> {
+  return invoke("cancel_worktree_setup", { workspaceId });
+}
+
 export async function openWorkspaceIn(path: string, app: string): Promise<void> {
   return invoke("open_workspace_in", { path, app });
 }
@@ -107,6 +246,7 @@ export async function sendUserMessage(
     model?: string | null;
     effort?: string | null;
     accessMode?: "read-only" | "current" | "full-access";
+    sandboxProfile?: string | null;
     images?: string[];
     collaborationMode?: Record<string, unknown> | null;
   },
@@ -118,6 +258,7 @@ export async function sendUserMessage(
     model: options?.model ?? null,
     effort: options?.effort ?? null,
     accessMode: options?.accessMode ?? null,
+    sandboxProfile: options?.sandboxProfile ?? null,
     images: options?.images ?? null,
     collaborationMode: options?.collaborationMode ?? null,
   });
@@ -161,12 +302,22 @@ export async function getGitStatus(workspace_id: string): Promise<{
   files: GitFileStatus[];
   stagedFiles: GitFileStatus[];
   unstagedFiles: GitFileStatus[];
+  conflictedFiles: GitConflictFile[];
+  repoState: string | null;
   totalAdditions: number;
   totalDeletions: number;
 }> {
   return invoke("get_git_status", { workspaceId: workspace_id });
 }
 
+export async function watchGitStatus(workspace_id: string): Promise<void> {
+  return invoke("watch_git_status", { workspaceId: workspace_id });
+}
+
+export async function unwatchGitStatus(workspace_id: string): Promise<void> {
+  return invoke("unwatch_git_status", { workspaceId: workspace_id });
+}
+
 export async function listGitRoots(
   workspace_id: string,
   depth: number,
@@ -180,6 +331,13 @@ export async function getGitDiffs(
   return invoke("get_git_diffs", { workspaceId: workspace_id });
 }
 
+export async function getGitFileDiff(
+  workspace_id: string,
+  path: string,
+): Promise<GitFileDiff> {
+  return invoke("get_git_file_diff", { workspaceId: workspace_id, path });
+}
+
 export async function getGitLog(
   workspace_id: string,
   limit = 40,
@@ -187,6 +345,28 @@ export async function getGitLog(
   return invoke("get_git_log", { workspaceId: workspace_id, limit });
 }
 
+export async function getGitBlame(
+  workspaceId: string,
+  path: string,
+  startLine?: number | null,
+  endLine?: number | null,
+): Promise<GitBlameHunk[]> {
+  return invoke("get_git_blame", {
+    workspaceId,
+    path,
+    startLine: startLine ?? null,
+    endLine: endLine ?? null,
+  });
+}
+
+export async function getGitFileLog(
+  workspaceId: string,
+  path: string,
+  limit = 40,
+): Promise<GitFileLogEntry[]> {
+  return invoke("get_git_file_log", { workspaceId, path, limit });
+}
+
 export async function getGitRemote(workspace_id: string): Promise<string | null> {
   return invoke("get_git_remote", { workspaceId: workspace_id });
 }
@@ -207,16 +387,60 @@ export async function revertGitAll(workspaceId: string) {
   return invoke("revert_git_all", { workspaceId });
 }
 
+export async function getGitConflicts(
+  workspaceId: string,
+): Promise<GitConflictFile[]> {
+  return invoke("get_git_conflicts", { workspaceId });
+}
+
+export async function getGitConflictVersions(
+  workspaceId: string,
+  path: string,
+): Promise<GitConflictVersions> {
+  return invoke("get_git_conflict_versions", { workspaceId, path });
+}
+
+export async function takeGitConflictOurs(workspaceId: string, path: string) {
+  return invoke("take_git_conflict_ours", { workspaceId, path });
+}
+
+export async function takeGitConflictTheirs(workspaceId: string, path: string) {
+  return invoke("take_git_conflict_theirs", { workspaceId, path });
+}
+
+export async function markGitConflictResolved(workspaceId: string, path: string) {
+  return invoke("mark_git_conflict_resolved", { workspaceId, path });
+}
+
+export async function resolveGitConflictsWithAgent(
+  workspaceId: string,
+  threadId?: string | null,
+) {
+  return invoke<any>("resolve_git_conflicts_with_agent", {
+    workspaceId,
+    threadId: threadId ?? null,
+  });
+}
+
 export async function getGitHubIssues(
   workspace_id: string,
+  query: ForgeListQuery = {},
 ): Promise<GitHubIssuesResponse> {
-  return invoke("get_github_issues", { workspaceId: workspace_id });
+  return invoke("get_github_issues", { workspaceId: workspace_id, query });
+}
+
+export async function getGitHubIssue(
+  workspace_id: string,
+  issueNumber: number,
+): Promise<GitHubIssueDetail> {
+  return invoke("get_github_issue", { workspaceId: workspace_id, issueNumber });
 }
 
 export async function getGitHubPullRequests(
   workspace_id: string,
+  query: ForgeListQuery = {},
 ): Promise<GitHubPullRequestsResponse> {
-  return invoke("get_github_pull_requests", { workspaceId: workspace_id });
+  return invoke("get_github_pull_requests", { workspaceId: workspace_id, query });
 }
 
 export async function getGitHubPullRequestDiff(
@@ -239,6 +463,128 @@ export async function getGitHubPullRequestComments(
   });
 }
 
+export async function getForgeRemote(
+  workspace_id: string,
+): Promise<ForgeRemote> {
+  return invoke("get_forge_remote", { workspaceId: workspace_id });
+}
+
+export async function getForgeIssues(
+  workspace_id: string,
+  query: ForgeListQuery = {},
+): Promise<GitHubIssuesResponse> {
+  return invoke("get_forge_issues", { workspaceId: workspace_id, query });
+}
+
+export async function getForgeMergeRequests(
+  workspace_id: string,
+  query: ForgeListQuery = {},
+): Promise<GitHubPullRequestsResponse> {
+  return invoke("get_forge_merge_requests", { workspaceId: workspace_id, query });
+}
+
+export async function getForgeMergeRequestDiff(
+  workspace_id: string,
+  number: number,
+): Promise<GitHubPullRequestDiff[]> {
+  return invoke("get_forge_merge_request_diff", {
+    workspaceId: workspace_id,
+    number,
+  });
+}
+
+export async function getForgeMergeRequestComments(
+  workspace_id: string,
+  number: number,
+): Promise<GitHubPullRequestComment[]> {
+  return invoke("get_forge_merge_request_comments", {
+    workspaceId: workspace_id,
+    number,
+  });
+}
+
+export async function previewGitHubPullRequestReview(
+  workspace_id: string,
+  prNumber: number,
+  review: GitHubReviewDraft,
+): Promise<GitHubReviewPreview> {
+  return invoke("preview_github_pull_request_review", {
+    workspaceId: workspace_id,
+    prNumber,
+    review,
+  });
+}
+
+export async function submitGitHubPullRequestReview(
+  workspace_id: string,
+  prNumber: number,
+  review: GitHubReviewDraft,
+  digest: string,
+): Promise<GitHubPullRequestReview> {
+  return invoke("submit_github_pull_request_review", {
+    workspaceId: workspace_id,
+    prNumber,
+    review,
+    digest,
+  });
+}
+
+export async function getGitHubChecks(
+  workspace_id: string,
+  target: { gitRef?: string | null; prNumber?: number | null } = {},
+): Promise<GitHubChecksResponse> {
+  return invoke("get_github_checks", {
+    workspaceId: workspace_id,
+    gitRef: target.gitRef ?? null,
+    prNumber: target.prNumber ?? null,
+  });
+}
+
+export async function sendGitHubCheckFailuresToAgent(
+  workspace_id: string,
+  threadId: string | null,
+  target: { gitRef?: string | null; prNumber?: number | null } = {},
+) {
+  return invoke<any>("send_github_check_failures_to_agent", {
+    workspaceId: workspace_id,
+    threadId,
+    gitRef: target.gitRef ?? null,
+    prNumber: target.prNumber ?? null,
+  });
+}
+
+export async function createGitHubPullRequest(
+  workspace_id: string,
+  options: {
+    title: string;
+    body?: string | null;
+    base?: string | null;
+    draft?: boolean;
+    reviewers?: string[];
+  },
+): Promise<GitHubPullRequest> {
+  return invoke("create_github_pull_request", {
+    workspaceId: workspace_id,
+    title: options.title,
+    body: options.body ?? null,
+    base: options.base ?? null,
+    draft: options.draft ?? false,
+    reviewers: options.reviewers ?? [],
+  });
+}
+
+export async function draftGitHubPullRequestWithAgent(
+  workspaceId: string,
+  threadId?: string | null,
+  base?: string | null,
+) {
+  return invoke<GitHubPullRequestDraft>("draft_github_pull_request_with_agent", {
+    workspaceId,
+    threadId: threadId ?? null,
+    base: base ?? null,
+  });
+}
+
 export async function getModelList(workspaceId: string) {
   return invoke<any>("model_list", { workspaceId });
 }
@@ -330,6 +676,18 @@ export async function updateAppSettings(settings: AppSettings): Promise<AppSetti
   return invoke<AppSettings>("update_app_settings", { settings });
 }
 
+export async function setSecret(kind: SecretKind, value: string): Promise<AppSettings> {
+  return invoke<AppSettings>("set_secret", { kind, value });
+}
+
+export async function clearSecret(kind: SecretKind): Promise<AppSettings> {
+  return invoke<AppSettings>("clear_secret", { kind });
+}
+
+export async function getStorageIssues(): Promise<StorageIssue[]> {
+  return invoke<StorageIssue[]>("get_storage_issues");
+}
+
 export async function runCodexDoctor(
   codexBin: string | null,
 ): Promise<CodexDoctorResult> {
@@ -352,6 +710,41 @@ export async function createGitBranch(workspaceId: string, name: string) {
   return invoke("create_git_branch", { workspaceId, name });
 }
 
+export async function listGitStashes(workspaceId: string) {
+  return invoke<GitStashEntry[]>("list_git_stashes", { workspaceId });
+}
+
+export async function stashGitChanges(
+  workspaceId: string,
+  message?: string | null,
+  includeUntracked = false,
+) {
+  return invoke<GitStashEntry>("stash_git_changes", {
+    workspaceId,
+    message: message ?? null,
+    includeUntracked,
+  });
+}
+
+export async function applyGitStash(workspaceId: string, index: number) {
+  return invoke("apply_git_stash", { workspaceId, index });
+}
+
+export async function popGitStash(workspaceId: string, index: number) {
+  return invoke("pop_git_stash", { workspaceId, index });
+}
+
+export async function dropGitStash(workspaceId: string, index: number) {
+  return invoke("drop_git_stash", { workspaceId, index });
+}
+
+export async function getGitStashDiff(
+  workspaceId: string,
+  index: number,
+): Promise<GitFileDiff[]> {
+  return invoke("get_git_stash_diff", { workspaceId, index });
+}
+
 function withModelId(modelId?: string | null) {
   return modelId ? { modelId } : {};
 }
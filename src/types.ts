@@ -3,6 +3,46 @@ export type WorkspaceSettings = {
   sortOrder?: number | null;
   groupId?: string | null;
   gitRoot?: string | null;
+  worktreeSetup?: WorktreeSetupConfig;
+  worktreeRoot?: string | null;
+  worktreeBranchTemplate?: string | null;
+  defaultModel?: string | null;
+  defaultEffort?: string | null;
+  defaultAccessMode?: AccessMode | null;
+  writableRoots?: string[];
+  networkAccess?: boolean | null;
+  approvalPolicy?: ApprovalPolicy | null;
+  sandboxProfile?: string | null;
+  approvalRules?: ApprovalRules;
+};
+
+export type ApprovalRules = {
+  allowCommands: string[];
+  denyCommands: string[];
+  allowPaths: string[];
+  denyPaths: string[];
+};
+
+export type SandboxProfile = {
+  name: string;
+  writableRoots: string[];
+  networkAccess: boolean;
+  approvalPolicy: ApprovalPolicy;
+};
+
+export type ApprovalPolicy = "never" | "on-request" | "on-failure" | "untrusted";
+
+export type WorktreeSetupConfig = {
+  copy: string[];
+  commands: string[];
+};
+
+export type WorktreeSetupStatus = {
+  workspaceId: string;
+  state: "running" | "succeeded" | "failed" | "canceled";
+  copiedFiles: number;
+  command: string | null;
+  error: string | null;
 };
 
 export type WorkspaceGroup = {
@@ -13,8 +53,61 @@ export type WorkspaceGroup = {
 
 export type WorkspaceKind = "main" | "worktree";
 
+export type GitHubIssueLink = {
+  number: number;
+  title: string;
+  url: string;
+  threadId: string | null;
+};
+
+export type GitHubPullRequestLink = {
+  number: number;
+  title: string;
+  url: string;
+  headRefName: string;
+  baseRefName: string;
+};
+
 export type WorktreeInfo = {
   branch: string;
+  issue?: GitHubIssueLink | null;
+  pullRequest?: GitHubPullRequestLink | null;
+};
+
+export type WorktreeSyncStatus = "ok" | "missing" | "untracked" | "stale";
+
+export type WorktreeSyncItem = {
+  path: string;
+  status: WorktreeSyncStatus;
+  workspaceId: string | null;
+  branch: string | null;
+  storedBranch: string | null;
+  locked: boolean;
+  diskUsageBytes: number | null;
+};
+
+export type WorktreeSyncAction = {
+  action: "import" | "prune" | "repair";
+  path: string;
+};
+
+export type WorktreeIntegrationStrategy =
+  | "merge"
+  | "squash"
+  | "rebase"
+  | "cherry-pick";
+
+export type WorktreeIntegrationResult = {
+  strategy: WorktreeIntegrationStrategy;
+  dryRun: boolean;
+  branch: string;
+  targetBranch: string;
+  commits: GitLogEntry[];
+  diff: GitFileDiff[];
+  conflicts: GitConflictFile[];
+  conflictWorkspaceId: string | null;
+  headSha: string | null;
+  removedWorktree: boolean;
 };
 
 export type WorkspaceInfo = {
@@ -29,6 +122,50 @@ export type WorkspaceInfo = {
   settings: WorkspaceSettings;
 };
 
+export type StorageIssue = {
+  file: string;
+  error: string;
+  preservedPath: string | null;
+  restoredFrom: string | null;
+};
+
+export type CloneErrorKind =
+  | "auth"
+  | "notFound"
+  | "network"
+  | "destinationExists"
+  | "other";
+
+export type CloneError = {
+  kind: CloneErrorKind;
+  message: string;
+};
+
+export type WorkspaceManifestEntry = {
+  name: string;
+  path: string;
+  remoteUrl?: string | null;
+  repoSubdir?: string | null;
+  codexBin?: string | null;
+  settings: WorkspaceSettings;
+};
+
+export type WorkspaceManifest = {
+  version: number;
+  groups: WorkspaceGroup[];
+  workspaces: WorkspaceManifestEntry[];
+};
+
+export type WorkspaceImportStatus = "existing" | "resolved" | "cloned" | "failed";
+
+export type WorkspaceImportItem = {
+  name: string;
+  path: string;
+  status: WorkspaceImportStatus;
+  workspace: WorkspaceInfo | null;
+  error: string | null;
+};
+
 export type AppServerEvent = {
   workspace_id: string;
   message: Record<string, unknown>;
@@ -71,11 +208,20 @@ export type ReviewTarget =
 export type AccessMode = "read-only" | "current" | "full-access";
 export type BackendMode = "local" | "remote";
 
+export type SecretKind = "remoteBackendToken" | "githubToken" | "gitlabToken";
+
+export type SecretStoreKind = "keyring" | "encryptedFile";
+
+export type SecretRef = {
+  store: SecretStoreKind;
+  key: string;
+};
+
 export type AppSettings = {
   codexBin: string | null;
   backendMode: BackendMode;
   remoteBackendHost: string;
-  remoteBackendToken: string | null;
+  remoteBackendToken: SecretRef | null;
   defaultAccessMode: AccessMode;
   composerModelShortcut: string | null;
   composerAccessShortcut: string | null;
@@ -92,6 +238,14 @@ export type AppSettings = {
   dictationPreferredLanguage: string | null;
   dictationHoldKey: string | null;
   workspaceGroups: WorkspaceGroup[];
+  diffMaxFileBytes: number;
+  diffMaxTotalBytes: number;
+  forgeHosts: Record<string, ForgeKind>;
+  sandboxProfiles: SandboxProfile[];
+  githubToken: SecretRef | null;
+  gitlabToken: SecretRef | null;
+  worktreeRoot: string | null;
+  worktreeBranchTemplate: string | null;
 };
 
 export type CodexDoctorResult = {
@@ -120,9 +274,22 @@ export type GitFileStatus = {
   deletions: number;
 };
 
+export type GitDiffKind = "text" | "binary" | "image" | "lockfile" | "minified";
+
+export type GitImageDiff = {
+  mimeType: string;
+  before: string | null;
+  after: string | null;
+};
+
 export type GitFileDiff = {
   path: string;
   diff: string;
+  kind: GitDiffKind;
+  additions: number;
+  deletions: number;
+  truncated: boolean;
+  image?: GitImageDiff;
 };
 
 export type GitLogEntry = {
@@ -132,6 +299,22 @@ export type GitLogEntry = {
   timestamp: number;
 };
 
+export type GitFileLogEntry = GitLogEntry & {
+  path: string;
+};
+
+export type GitBlameHunk = {
+  startLine: number;
+  lineCount: number;
+  sha: string;
+  author: string;
+  email: string;
+  timestamp: number;
+  summary: string;
+  origPath: string | null;
+  committed: boolean;
+};
+
 export type GitLogResponse = {
   total: number;
   entries: GitLogEntry[];
@@ -149,9 +332,43 @@ export type GitHubIssue = {
   updatedAt: string;
 };
 
+export type GitHubLabel = {
+  name: string;
+  color: string;
+};
+
+export type GitHubIssueComment = {
+  author: GitHubUser | null;
+  body: string;
+  createdAt: string;
+};
+
+export type GitHubIssueDetail = {
+  number: number;
+  title: string;
+  url: string;
+  state: string;
+  body: string;
+  labels: GitHubLabel[];
+  author: GitHubUser | null;
+  comments: GitHubIssueComment[];
+  updatedAt: string;
+};
+
 export type GitHubIssuesResponse = {
   total: number;
   issues: GitHubIssue[];
+  nextCursor?: string | null;
+};
+
+export type ForgeListQuery = {
+  state?: "open" | "closed" | "merged" | "all";
+  labels?: string[];
+  author?: string | null;
+  assignee?: string | null;
+  search?: string | null;
+  cursor?: string | null;
+  pageSize?: number | null;
 };
 
 export type GitHubUser = {
@@ -174,6 +391,7 @@ export type GitHubPullRequest = {
 export type GitHubPullRequestsResponse = {
   total: number;
   pullRequests: GitHubPullRequest[];
+  nextCursor?: string | null;
 };
 
 export type GitHubPullRequestDiff = {
@@ -190,6 +408,85 @@ export type GitHubPullRequestComment = {
   author: GitHubUser | null;
 };
 
+export type GitHubPullRequestDraft = {
+  threadId: string;
+  title: string;
+  body: string;
+};
+
+export type GitHubReviewEvent = "COMMENT" | "APPROVE" | "REQUEST_CHANGES";
+
+export type GitHubReviewComment = {
+  path: string;
+  line: number;
+  side: "LEFT" | "RIGHT";
+  startLine?: number | null;
+  body: string;
+};
+
+export type GitHubReviewDraft = {
+  event: GitHubReviewEvent;
+  body: string;
+  comments: GitHubReviewComment[];
+};
+
+export type GitHubReviewCommentPreview = GitHubReviewComment & {
+  excerpt: string | null;
+  error: string | null;
+};
+
+export type GitHubReviewPreview = {
+  event: GitHubReviewEvent;
+  body: string;
+  comments: GitHubReviewCommentPreview[];
+  errors: string[];
+  digest: string | null;
+};
+
+export type GitHubPullRequestReview = {
+  id: number;
+  state: string;
+  url: string;
+  submittedAt: string | null;
+};
+
+export type ForgeKind = "github" | "gitlab" | "gitea";
+
+export type ForgeRemote = {
+  kind: ForgeKind;
+  host: string;
+  repo: string;
+  webUrl: string;
+  apiBase: string;
+};
+
+export type GitHubCheckRun = {
+  id: number;
+  name: string;
+  status: string;
+  conclusion: string | null;
+  url: string | null;
+  startedAt: string | null;
+  completedAt: string | null;
+  appSlug: string | null;
+  logExcerpt: string | null;
+};
+
+export type GitHubCommitStatus = {
+  context: string;
+  state: string;
+  description: string | null;
+  url: string | null;
+  updatedAt: string | null;
+};
+
+export type GitHubChecksResponse = {
+  sha: string;
+  state: "success" | "failure" | "pending" | "neutral";
+  checkRuns: GitHubCheckRun[];
+  statuses: GitHubCommitStatus[];
+};
+
 export type TokenUsageBreakdown = {
   totalTokens: number;
   inputTokens: number;
@@ -283,6 +580,37 @@ export type BranchInfo = {
   lastCommit: number;
 };
 
+export type GitConflictKind =
+  | "both-modified"
+  | "both-added"
+  | "deleted-by-us"
+  | "deleted-by-them"
+  | "added-by-us"
+  | "added-by-them"
+  | "both-deleted";
+
+export type GitConflictFile = {
+  path: string;
+  kind: GitConflictKind;
+  baseSha: string | null;
+  oursSha: string | null;
+  theirsSha: string | null;
+};
+
+export type GitConflictVersions = {
+  path: string;
+  base: string | null;
+  ours: string | null;
+  theirs: string | null;
+};
+
+export type GitStashEntry = {
+  index: number;
+  message: string;
+  sha: string;
+  timestamp: number;
+};
+
 export type DebugEntry = {
   id: string;
   timestamp: number;